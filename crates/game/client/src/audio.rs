@@ -0,0 +1,359 @@
+//! Game audio, mixed on music, sound effect and voice buses under a master volume.
+//! Music follows the game state and crossfades between the menu, battle and results
+//! tracks. Sound effects answer character actions and frame cues; voice lines come
+//! from each fighter's manifest.
+//!
+//! Audio is optional: the files load in the background rather than holding up
+//! `GameState::Loading`, and a sound whose file is missing simply stays silent.
+
+use bevy::{
+    asset::LoadState,
+    audio::Volume,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use game_common::{
+    gameplay::character::{
+        CharacterHitMessage,
+        presentation::{PlaySfxMessage, VoiceCue},
+    },
+    prelude::*,
+};
+
+/// Seconds a music track takes to fade in or out.
+const MUSIC_CROSSFADE_SECS: f32 = 1.5;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioSettings>()
+            .init_resource::<AudioAssets>()
+            .init_resource::<ManifestSounds>()
+            .add_systems(
+                Update,
+                (
+                    switch_music.run_if(state_changed::<GameState>),
+                    fade_music,
+                    (play_action_sfx, play_frame_sfx, play_voice_lines)
+                        .run_if(not(in_state(GameState::Loading))),
+                    apply_bus_volumes,
+                    drop_failed_sounds,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Music and action sounds, requested at startup without blocking the loading state.
+#[derive(Resource)]
+pub struct AudioAssets {
+    music_menu: Handle<AudioSource>,
+    music_battle: Handle<AudioSource>,
+    music_results: Handle<AudioSource>,
+    sfx_jump: Handle<AudioSource>,
+    sfx_land: Handle<AudioSource>,
+    sfx_dash: Handle<AudioSource>,
+    sfx_hit: Handle<AudioSource>,
+    sfx_block: Handle<AudioSource>,
+}
+
+impl FromWorld for AudioAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            music_menu: asset_server.load("audio/nightly.ogg"),
+            music_battle: asset_server.load("audio/music/battle.ogg"),
+            music_results: asset_server.load("audio/music/results.ogg"),
+            sfx_jump: asset_server.load("audio/sfx/jump.ogg"),
+            sfx_land: asset_server.load("audio/sfx/land.ogg"),
+            sfx_dash: asset_server.load("audio/sfx/dash.ogg"),
+            sfx_hit: asset_server.load("audio/sfx/hit.ogg"),
+            sfx_block: asset_server.load("audio/sfx/block.ogg"),
+        }
+    }
+}
+
+/// Sounds named by character manifests, for frame cues and voice lines. Each path is
+/// requested once on first use and its handle kept for every later cue.
+#[derive(Resource, Debug, Default)]
+struct ManifestSounds(HashMap<String, Handle<AudioSource>>);
+
+impl ManifestSounds {
+    /// Handle for `path`, or `None` once its file has failed to load.
+    fn get(&mut self, asset_server: &AssetServer, path: &str) -> Option<Handle<AudioSource>> {
+        let handle = match self.0.get(path) {
+            Some(handle) => handle.clone(),
+            None => {
+                let handle = asset_server.load(path.to_owned());
+                self.0.insert(path.to_owned(), handle.clone());
+                handle
+            }
+        };
+        (!matches!(asset_server.load_state(&handle), LoadState::Failed(_))).then_some(handle)
+    }
+}
+
+/// Linear volume of each bus, `0.0` to `1.0`. Every bus is also scaled by `master`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub voice: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.6,
+            sfx: 0.8,
+            voice: 1.0,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        let bus = match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Sfx => self.sfx,
+            AudioBus::Voice => self.voice,
+        };
+        (self.master * bus).clamp(0.0, 1.0)
+    }
+}
+
+/// Bus a playing sound is mixed on.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Voice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MusicCue {
+    Menu,
+    Battle,
+    Results,
+}
+
+impl MusicCue {
+    fn for_state(state: &GameState) -> Option<Self> {
+        match state {
+            GameState::Loading => None,
+            GameState::MainMenu => Some(Self::Menu),
+            GameState::InGame | GameState::Paused => Some(Self::Battle),
+            GameState::GameEnd => Some(Self::Results),
+        }
+    }
+
+    fn track(self, assets: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            Self::Menu => assets.music_menu.clone(),
+            Self::Battle => assets.music_battle.clone(),
+            Self::Results => assets.music_results.clone(),
+        }
+    }
+}
+
+/// Looping music track, fading towards full volume until another track replaces it.
+#[derive(Component, Debug)]
+struct MusicTrack {
+    cue: MusicCue,
+    gain: f32,
+    fading_out: bool,
+}
+
+/// Voice line being said by `speaker`. A new line cuts the previous one off.
+#[derive(Component, Debug)]
+struct VoiceLine {
+    speaker: Entity,
+}
+
+fn switch_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    assets: Res<AudioAssets>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    let Some(cue) = MusicCue::for_state(state.get()) else {
+        return;
+    };
+
+    let mut playing = false;
+    for mut track in &mut tracks {
+        if track.cue == cue && !track.fading_out {
+            playing = true;
+        } else {
+            track.fading_out = true;
+        }
+    }
+    if playing {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Music"),
+        AudioPlayer::new(cue.track(&assets)),
+        // Fades in from silence once `apply_bus_volumes` finds its sink
+        PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        AudioBus::Music,
+        MusicTrack {
+            cue,
+            gain: 0.0,
+            fading_out: false,
+        },
+    ));
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tracks: Query<(Entity, &mut MusicTrack)>,
+) {
+    let step = time.delta_secs() / MUSIC_CROSSFADE_SECS;
+    for (entity, mut track) in &mut tracks {
+        if track.fading_out {
+            track.gain = (track.gain - step).max(0.0);
+            if track.gain == 0.0 {
+                commands.entity(entity).despawn();
+            }
+        } else {
+            track.gain = (track.gain + step).min(1.0);
+        }
+    }
+}
+
+/// Plays `sound` on the SFX bus.
+fn play_sfx(commands: &mut Commands, settings: &AudioSettings, sound: Handle<AudioSource>) {
+    commands.spawn((
+        AudioPlayer::new(sound),
+        PlaybackSettings::DESPAWN.with_volume(Volume::Linear(settings.volume(AudioBus::Sfx))),
+        AudioBus::Sfx,
+    ));
+}
+
+/// Sound effects for movement and combat. Each sound plays at most once per frame, as
+/// replay playback at high speed can simulate several ticks in one.
+#[allow(clippy::too_many_arguments)]
+fn play_action_sfx(
+    mut commands: Commands,
+    assets: Res<AudioAssets>,
+    settings: Res<AudioSettings>,
+    mut jumped: MessageReader<CharacterJumpedMessage>,
+    mut landed: MessageReader<CharacterLandedMessage>,
+    mut dashed: MessageReader<CharacterDashedMessage>,
+    mut hits: MessageReader<CharacterHitMessage>,
+    mut blocks: MessageReader<CharacterBlockedMessage>,
+) {
+    let cues = [
+        (jumped.read().count(), &assets.sfx_jump),
+        (landed.read().count(), &assets.sfx_land),
+        (dashed.read().count(), &assets.sfx_dash),
+        (hits.read().count(), &assets.sfx_hit),
+        (blocks.read().count(), &assets.sfx_block),
+    ];
+    for (count, sound) in cues {
+        if count > 0 {
+            play_sfx(&mut commands, &settings, sound.clone());
+        }
+    }
+}
+
+/// Sound cues of animation frames, see `FrameEvent::Sfx`.
+fn play_frame_sfx(
+    mut commands: Commands,
+    mut sfx_reader: MessageReader<PlaySfxMessage>,
+    asset_server: Res<AssetServer>,
+    mut sounds: ResMut<ManifestSounds>,
+    settings: Res<AudioSettings>,
+) {
+    let mut played = HashSet::new();
+    for sfx in sfx_reader.read() {
+        if played.insert(sfx.sound.as_str())
+            && let Some(sound) = sounds.get(&asset_server, &sfx.sound)
+        {
+            play_sfx(&mut commands, &settings, sound);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_voice_lines(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut sounds: ResMut<ManifestSounds>,
+    settings: Res<AudioSettings>,
+    manifests: Res<Assets<CharacterManifestAsset>>,
+    characters: Query<&CharacterManifestHandle>,
+    fighters: Query<Entity, Added<PlayerSlot>>,
+    voices: Query<(Entity, &VoiceLine)>,
+    mut jumped: MessageReader<CharacterJumpedMessage>,
+    mut dashed: MessageReader<CharacterDashedMessage>,
+    mut hits: MessageReader<CharacterHitMessage>,
+) {
+    let mut lines: Vec<(Entity, VoiceCue)> = Vec::new();
+    lines.extend(fighters.iter().map(|fighter| (fighter, VoiceCue::Intro)));
+    lines.extend(jumped.read().map(|jump| (jump.entity, VoiceCue::Jump)));
+    lines.extend(dashed.read().map(|dash| (dash.entity, VoiceCue::Dash)));
+    lines.extend(hits.read().map(|hit| (hit.defender, VoiceCue::Hurt)));
+    // Latest cue per speaker wins, as it would cut the earlier ones off anyway
+    lines.reverse();
+    let mut speakers = HashSet::new();
+    lines.retain(|(speaker, _)| speakers.insert(*speaker));
+
+    for (speaker, cue) in lines {
+        let Some(sound) = characters
+            .get(speaker)
+            .ok()
+            .and_then(|handle| manifests.get(&handle.0))
+            .and_then(|manifest| manifest.voice_line(cue))
+            .and_then(|path| sounds.get(&asset_server, path))
+        else {
+            continue;
+        };
+
+        for (voice, line) in &voices {
+            if line.speaker == speaker {
+                commands.entity(voice).despawn();
+            }
+        }
+        commands.spawn((
+            AudioPlayer::new(sound),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(settings.volume(AudioBus::Voice))),
+            AudioBus::Voice,
+            VoiceLine { speaker },
+        ));
+    }
+}
+
+/// Keeps every playing sound at its bus volume, times the crossfade gain for music.
+fn apply_bus_volumes(
+    settings: Res<AudioSettings>,
+    mut sinks: Query<(&AudioBus, Option<&MusicTrack>, &mut AudioSink)>,
+) {
+    for (bus, track, mut sink) in &mut sinks {
+        let gain = track.map_or(1.0, |track| track.gain);
+        let volume = Volume::Linear(settings.volume(*bus) * gain);
+        if sink.volume() != volume {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+/// Despawns sounds whose file failed to load. They never get a sink, so they would
+/// otherwise linger, and a music track would keep counting as playing.
+fn drop_failed_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    players: Query<(Entity, &AudioPlayer), Without<AudioSink>>,
+) {
+    for (entity, player) in &players {
+        if matches!(asset_server.load_state(&player.0), LoadState::Failed(_)) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
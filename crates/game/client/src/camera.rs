@@ -0,0 +1,220 @@
+//! The game camera. In matches it frames every fighter, zooming out as they separate
+//! and staying within the `ArenaBounds`; in the sandbox it follows the lone character.
+//! Hits, blocks, heavy landings and frame cues shake it.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, transform::TransformSystems};
+use game_common::{
+    gameplay::{
+        arena::ArenaBounds,
+        character::{
+            CharacterHitMessage,
+            presentation::{CameraShakeMessage, update_character_sprites},
+        },
+        ticks_to_duration,
+    },
+    prelude::*,
+};
+
+/// How quickly the camera catches up with its target, as an exponential rate per second.
+const FOLLOW_RATE: f32 = 6.0;
+/// Room kept around the outermost fighters, in world units.
+const FRAMING_MARGIN: Vec2 = Vec2::new(160.0, 120.0);
+/// Character origins sit mid-body; aim a little higher so jumps stay in view.
+const FRAMING_LIFT: f32 = 60.0;
+/// Projection scale when the fighters are close, and the furthest it zooms out.
+const MIN_SCALE: f32 = 1.0;
+const MAX_SCALE: f32 = 2.0;
+
+/// Shake of an unguarded hit and of a blocked one, as `(pixels, ticks)`.
+const HIT_SHAKE: (f32, u16) = (6.0, 8);
+const BLOCK_SHAKE: (f32, u16) = (3.0, 6);
+/// Landings faster than this shake the camera, harder the faster they are.
+const HEAVY_LANDING_SPEED: f32 = 600.0;
+/// Shake pixels per unit of fall speed above `HEAVY_LANDING_SPEED`.
+const LANDING_SHAKE_PER_SPEED: f32 = 0.02;
+const LANDING_SHAKE_TICKS: u16 = 10;
+
+/// Shake oscillation frequencies along x and y, in radians per second. Different so the
+/// camera wobbles instead of moving along a line.
+const SHAKE_FREQUENCY: Vec2 = Vec2::new(47.0, 61.0);
+
+pub struct GameCameraPlugin;
+
+impl Plugin for GameCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_camera).add_systems(
+            PostUpdate,
+            (frame_characters, start_camera_shake, shake_camera)
+                .chain()
+                .after(update_character_sprites)
+                .before(TransformSystems::Propagate),
+        );
+    }
+}
+
+/// Where the camera looks before any shake, eased towards the characters.
+#[derive(Component, Debug)]
+pub struct CameraRig {
+    pub center: Vec2,
+    pub scale: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            scale: MIN_SCALE,
+        }
+    }
+}
+
+/// Current impact shake of the camera. The strongest shake wins while several overlap.
+#[derive(Component, Debug, Default)]
+pub struct CameraShake {
+    intensity: f32,
+    duration: Duration,
+    remaining: Duration,
+}
+
+impl CameraShake {
+    pub fn start(&mut self, intensity: f32, duration: Duration) {
+        if intensity >= self.amplitude() {
+            self.intensity = intensity;
+            self.duration = duration;
+            self.remaining = duration;
+        }
+    }
+
+    /// Current shake distance in pixels, fading linearly to zero.
+    pub fn amplitude(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 0.0;
+        }
+        self.intensity * self.remaining.as_secs_f32() / self.duration.as_secs_f32()
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Camera"),
+        Camera2d,
+        CameraRig::default(),
+        CameraShake::default(),
+    ));
+}
+
+/// Eases each camera towards the framing of every character. Snaps instead when a
+/// character appears, so a new match does not open with a pan across the stage.
+fn frame_characters(
+    time: Res<Time>,
+    mode: Res<GameMode>,
+    bounds: Res<ArenaBounds>,
+    characters: Query<&Transform, (With<Character>, Without<CameraRig>)>,
+    spawned: Query<(), Added<Character>>,
+    mut cameras: Query<(&Camera, &mut CameraRig, &mut Projection)>,
+) {
+    let Some(area) = characters
+        .iter()
+        .map(|transform| Rect::from_center_size(transform.translation.truncate(), Vec2::ZERO))
+        .reduce(|area, character| area.union(character))
+    else {
+        return;
+    };
+    let sandbox = *mode == GameMode::Sandbox;
+    let blend = 1.0 - (-FOLLOW_RATE * time.delta_secs()).exp();
+    let snap = !spawned.is_empty();
+
+    for (camera, mut rig, mut projection) in &mut cameras {
+        let Some(viewport) = camera.logical_viewport_size() else {
+            continue;
+        };
+
+        let (center, scale) = if sandbox {
+            (area.center(), MIN_SCALE)
+        } else {
+            let center = area.center() + Vec2::Y * FRAMING_LIFT;
+            let fit = (area.size() + FRAMING_MARGIN * 2.0) / viewport;
+            let largest = (bounds.rect().size() / viewport).min_element();
+            let scale = fit.max_element().clamp(MIN_SCALE, MAX_SCALE).min(largest);
+            (center, scale)
+        };
+
+        if snap {
+            rig.center = center;
+            rig.scale = scale;
+        } else {
+            rig.center = rig.center.lerp(center, blend);
+            rig.scale += (scale - rig.scale) * blend;
+        }
+        if !sandbox {
+            rig.center = clamp_to_bounds(rig.center, viewport * rig.scale, bounds.rect());
+        }
+
+        if let Projection::Orthographic(orthographic) = projection.as_mut()
+            && orthographic.scale != rig.scale
+        {
+            orthographic.scale = rig.scale;
+        }
+    }
+}
+
+/// Moves a view of `size` centred on `center` inside `bounds`, centring it on them along
+/// an axis where it does not fit.
+fn clamp_to_bounds(center: Vec2, size: Vec2, bounds: Rect) -> Vec2 {
+    let half = size / 2.0;
+    let min = bounds.min + half;
+    let max = bounds.max - half;
+    Vec2::new(
+        if min.x <= max.x {
+            center.x.clamp(min.x, max.x)
+        } else {
+            bounds.center().x
+        },
+        if min.y <= max.y {
+            center.y.clamp(min.y, max.y)
+        } else {
+            bounds.center().y
+        },
+    )
+}
+
+fn start_camera_shake(
+    mut shake_reader: MessageReader<CameraShakeMessage>,
+    mut hits: MessageReader<CharacterHitMessage>,
+    mut blocks: MessageReader<CharacterBlockedMessage>,
+    mut landings: MessageReader<CharacterLandedMessage>,
+    mut cameras: Query<&mut CameraShake>,
+) {
+    let mut shakes: Vec<(f32, u16)> = shake_reader
+        .read()
+        .map(|shake| (shake.intensity, shake.duration_ticks))
+        .collect();
+    shakes.extend(hits.read().map(|_| HIT_SHAKE));
+    shakes.extend(blocks.read().map(|_| BLOCK_SHAKE));
+    shakes.extend(landings.read().filter_map(|landing| {
+        let excess = landing.fall_speed.to_f32() - HEAVY_LANDING_SPEED;
+        (excess > 0.0).then_some((excess * LANDING_SHAKE_PER_SPEED, LANDING_SHAKE_TICKS))
+    }));
+
+    for mut camera in &mut cameras {
+        for &(intensity, ticks) in &shakes {
+            camera.start(intensity, ticks_to_duration(ticks));
+        }
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    mut cameras: Query<(&CameraRig, &mut CameraShake, &mut Transform)>,
+) {
+    let phase = SHAKE_FREQUENCY * time.elapsed_secs();
+    for (rig, mut shake, mut transform) in &mut cameras {
+        shake.remaining = shake.remaining.saturating_sub(time.delta());
+        let offset = Vec2::new(phase.x.sin(), phase.y.cos()) * shake.amplitude();
+        let translation = rig.center + offset;
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
@@ -0,0 +1,160 @@
+//! Fighter picks for the next local match, shown in the main menu. Player one cycles
+//! through the roster with Q/E, player two with O/P. Every fighter listed in the
+//! roster asset is selectable.
+
+use bevy::prelude::*;
+use game_common::prelude::*;
+
+use crate::game::CharacterAssets;
+
+const PORTRAIT_SCALE: f32 = 2.0;
+const CARD_FONT_SIZE: f32 = 16.0;
+
+pub struct CharacterSelectPlugin;
+
+impl Plugin for CharacterSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CharacterSelection>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_select_screen)
+            .add_systems(
+                Update,
+                (cycle_selection, update_select_screen)
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+/// Roster index each player picked, by `PlayerSlot::index`.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct CharacterSelection {
+    pub picks: [usize; 2],
+}
+
+impl CharacterSelection {
+    pub fn character<'a>(
+        &self,
+        slot: PlayerSlot,
+        roster: &'a CharacterRosterAsset,
+    ) -> &'a RosterCharacter {
+        roster.get(self.picks[slot.index()])
+    }
+}
+
+#[derive(Component, Debug)]
+struct SelectPortrait(PlayerSlot);
+
+#[derive(Component, Debug)]
+struct SelectName(PlayerSlot);
+
+fn spawn_select_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Character select"),
+            DespawnOnExit(GameState::MainMenu),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(48.0),
+                ..default()
+            },
+        ))
+        .with_children(|screen| {
+            for slot in PlayerSlot::ALL {
+                let keys = match slot {
+                    PlayerSlot::One => "Q / E",
+                    PlayerSlot::Two => "O / P",
+                };
+                screen
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn((
+                            Text::new(format!("P{}  {keys}", slot.index() + 1)),
+                            TextFont::from_font_size(CARD_FONT_SIZE),
+                            TextColor(Color::WHITE),
+                        ));
+                        card.spawn((SelectPortrait(slot), ImageNode::default()));
+                        card.spawn((
+                            SelectName(slot),
+                            Text::new(""),
+                            TextFont::from_font_size(CARD_FONT_SIZE),
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+fn cycle_selection(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    mut selection: ResMut<CharacterSelection>,
+) {
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+
+    for (slot, previous, next) in [
+        (PlayerSlot::One, KeyCode::KeyQ, KeyCode::KeyE),
+        (PlayerSlot::Two, KeyCode::KeyO, KeyCode::KeyP),
+    ] {
+        let pick = selection.picks[slot.index()] % roster.len();
+        if keyboard.just_pressed(previous) {
+            selection.picks[slot.index()] = (pick + roster.len() - 1) % roster.len();
+        } else if keyboard.just_pressed(next) {
+            selection.picks[slot.index()] = (pick + 1) % roster.len();
+        }
+    }
+}
+
+fn update_select_screen(
+    selection: Res<CharacterSelection>,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    manifests: Res<Assets<CharacterManifestAsset>>,
+    images: Res<Assets<Image>>,
+    mut portraits: Query<(&SelectPortrait, &mut ImageNode, &mut Node)>,
+    mut names: Query<(&SelectName, &mut Text)>,
+) {
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+
+    for (portrait, mut image, mut node) in &mut portraits {
+        let character = selection.character(portrait.0, roster);
+        if image.image != character.portrait || image.rect != character.portrait_rect {
+            image.image = character.portrait.clone();
+            image.rect = character.portrait_rect;
+        }
+
+        let size = character
+            .portrait_rect
+            .map(|rect| rect.size())
+            .or_else(|| images.get(&character.portrait).map(Image::size_f32))
+            .unwrap_or_default()
+            * PORTRAIT_SCALE;
+        let (width, height) = (Val::Px(size.x), Val::Px(size.y));
+        if node.width != width || node.height != height {
+            node.width = width;
+            node.height = height;
+        }
+    }
+
+    for (name, mut text) in &mut names {
+        let character = selection.character(name.0, roster);
+        let label = manifests
+            .get(&character.manifest)
+            .map_or("?", |manifest| manifest.name.as_str());
+        if text.0 != label {
+            text.0 = label.to_owned();
+        }
+    }
+}
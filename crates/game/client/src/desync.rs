@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use game_common::{
+    gameplay::{
+        checksum::{ChecksumLog, Divergence},
+        replay::{ReplayCommand, ReplayPlayback, handle_replay_commands},
+    },
+    prelude::*,
+};
+
+use crate::storage::{self, FileInbox};
+
+/// Extension of saved checksum logs.
+const CHECKSUM_EXTENSION: &str = "nrsum";
+
+pub struct DesyncCheckPlugin;
+
+impl Plugin for DesyncCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DesyncCheck>()
+            .init_resource::<ChecksumInbox>()
+            .add_systems(OnExit(GameState::InGame), cancel_desync_check)
+            .add_systems(
+                Update,
+                (
+                    // Before the rerun is handled, so no tick of the previous run reaches
+                    // the fresh checksum log
+                    (desync_hotkeys, advance_desync_check).before(handle_replay_commands),
+                    compare_opened_log,
+                )
+                    .run_if(in_state(GameState::InGame).and(resource_exists::<ReplayPlayback>)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum CheckPhase {
+    #[default]
+    Idle,
+    FirstRun,
+    SecondRun,
+}
+
+/// Debug tool that plays the loaded replay twice and compares per-tick checksums.
+#[derive(Resource, Debug, Default)]
+pub struct DesyncCheck {
+    phase: CheckPhase,
+    first_run: Option<ChecksumLog>,
+    /// Checksums of the last complete run, for saving or comparing across platforms.
+    pub last_run: Option<ChecksumLog>,
+}
+
+/// Checksum log picked by the player for comparison.
+#[derive(Resource, Debug, Clone, Default, Deref)]
+struct ChecksumInbox(FileInbox);
+
+/// F9 checks the replay against itself, F10 saves the last run's checksums and F11
+/// compares the last run against a saved log (e.g. one produced by the other platform).
+fn desync_hotkeys(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut check: ResMut<DesyncCheck>,
+    inbox: Res<ChecksumInbox>,
+    mut replay_writer: MessageWriter<ReplayCommand>,
+) {
+    if keyboard.just_pressed(KeyCode::F9) && check.phase == CheckPhase::Idle {
+        info!("Desync check: first run");
+        check.phase = CheckPhase::FirstRun;
+        check.first_run = None;
+        restart_full_run(&mut commands, &mut replay_writer);
+    }
+
+    if keyboard.just_pressed(KeyCode::F10) {
+        match &check.last_run {
+            Some(log) => {
+                if let Err(err) =
+                    storage::save("checksums", CHECKSUM_EXTENSION, log.to_text().as_bytes())
+                {
+                    error!("Failed to save checksum log: {err}");
+                }
+            }
+            None => info!("No checksum run recorded yet, press F9 first"),
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::F11)
+        && let Err(err) = storage::open(CHECKSUM_EXTENSION, inbox.0.clone())
+    {
+        error!("Failed to open checksum log: {err}");
+    }
+}
+
+/// Re-simulates the whole replay with a fresh checksum log.
+fn restart_full_run(commands: &mut Commands, replay_writer: &mut MessageWriter<ReplayCommand>) {
+    commands.insert_resource(ChecksumLog::default());
+    replay_writer.write(ReplayCommand::Rerun);
+}
+
+fn advance_desync_check(
+    mut commands: Commands,
+    mut check: ResMut<DesyncCheck>,
+    playback: Res<ReplayPlayback>,
+    log: Option<Res<ChecksumLog>>,
+    mut replay_writer: MessageWriter<ReplayCommand>,
+) {
+    if check.phase == CheckPhase::Idle || playback.is_seeking() || !playback.is_finished() {
+        return;
+    }
+    let Some(log) = log else {
+        return;
+    };
+
+    match check.phase {
+        CheckPhase::FirstRun => {
+            info!("Desync check: second run");
+            check.first_run = Some(log.clone());
+            check.phase = CheckPhase::SecondRun;
+            restart_full_run(&mut commands, &mut replay_writer);
+        }
+        CheckPhase::SecondRun => {
+            let second_run = log.clone();
+            if let Some(first_run) = check.first_run.take() {
+                report("second run", first_run.first_divergence(&second_run));
+            }
+            check.last_run = Some(second_run);
+            check.phase = CheckPhase::Idle;
+            commands.remove_resource::<ChecksumLog>();
+        }
+        CheckPhase::Idle => {}
+    }
+}
+
+fn compare_opened_log(inbox: Res<ChecksumInbox>, check: Res<DesyncCheck>) {
+    let Some(bytes) = inbox.take() else {
+        return;
+    };
+
+    let Some(opened) = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(ChecksumLog::from_text)
+    else {
+        error!("Opened file is not a checksum log");
+        return;
+    };
+    let Some(last_run) = &check.last_run else {
+        warn!("No checksum run recorded yet, press F9 first");
+        return;
+    };
+
+    report("opened log", last_run.first_divergence(&opened));
+}
+
+fn report(against: &str, divergence: Option<Divergence>) {
+    match divergence {
+        None => info!("Desync check: no divergence against {against}"),
+        Some(Divergence { tick, components }) if components.is_empty() => {
+            warn!("Desync check: {against} ends at a different tick ({tick})");
+        }
+        Some(Divergence { tick, components }) => {
+            warn!("Desync check: {against} diverges at tick {tick} in {components:?}");
+        }
+    }
+}
+
+fn cancel_desync_check(mut commands: Commands, mut check: ResMut<DesyncCheck>) {
+    check.phase = CheckPhase::Idle;
+    check.first_run = None;
+    commands.remove_resource::<ChecksumLog>();
+}
@@ -0,0 +1,65 @@
+//! Effect-sprite cues of character animation frames, see `FrameEvent::Vfx`. Their
+//! sound cues play in `audio`.
+
+use bevy::prelude::*;
+use game_common::{
+    gameplay::{character::presentation::SpawnVfxMessage, ticks_to_duration},
+    prelude::*,
+};
+
+/// Effect sprites draw just in front of the character that spawned them.
+const VFX_DEPTH: f32 = 1.0;
+
+pub struct FrameEffectsPlugin;
+
+impl Plugin for FrameEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (spawn_frame_vfx, fade_frame_vfx));
+    }
+}
+
+/// Effect sprite fading out over its lifetime.
+#[derive(Component, Debug)]
+struct FrameVfx(Timer);
+
+fn spawn_frame_vfx(
+    mut commands: Commands,
+    mut vfx_reader: MessageReader<SpawnVfxMessage>,
+    asset_server: Res<AssetServer>,
+    characters: Query<&Transform, With<Character>>,
+) {
+    for vfx in vfx_reader.read() {
+        let Ok(character) = characters.get(vfx.entity) else {
+            continue;
+        };
+
+        let position = character.translation.truncate() + vfx.offset * character.scale.truncate();
+        let lifetime = ticks_to_duration(vfx.lifetime_ticks);
+        commands.spawn((
+            Name::new("Frame VFX"),
+            Sprite {
+                image: asset_server.load(vfx.image.as_str()),
+                flip_x: vfx.flip_x,
+                ..default()
+            },
+            Transform::from_translation(position.extend(character.translation.z + VFX_DEPTH))
+                .with_scale(character.scale),
+            FrameVfx(Timer::new(lifetime, TimerMode::Once)),
+        ));
+    }
+}
+
+fn fade_frame_vfx(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut effects: Query<(Entity, &mut FrameVfx, &mut Sprite)>,
+) {
+    for (entity, mut vfx, mut sprite) in &mut effects {
+        vfx.0.tick(time.delta());
+        if vfx.0.is_finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_alpha(vfx.0.fraction_remaining());
+        }
+    }
+}
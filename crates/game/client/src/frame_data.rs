@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use game_common::{
+    gameplay::frame_data::{FRAME_METER_TICKS, FrameDataTracker, FrameKind, MoveFrameData},
+    prelude::*,
+};
+
+const HUD_FONT_SIZE: f32 = 14.0;
+const METER_CELL_SIZE: f32 = 6.0;
+
+pub struct FrameDataHudPlugin;
+
+impl Plugin for FrameDataHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            spawn_frame_data_hud.run_if(resource_equals(GameMode::Training)),
+        )
+        .add_systems(
+            Update,
+            (update_frame_data_text, update_frame_meter)
+                .run_if(in_state(GameState::InGame).and(resource_equals(GameMode::Training))),
+        );
+    }
+}
+
+/// Text line showing the last move's frame data for one player.
+#[derive(Component, Debug)]
+struct FrameDataText(PlayerSlot);
+
+/// One tick cell of a player's scrolling frame meter.
+#[derive(Component, Debug)]
+struct FrameMeterCell {
+    slot: PlayerSlot,
+    index: usize,
+}
+
+fn spawn_frame_data_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Frame data HUD"),
+            DespawnOnExit(GameState::InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(12.0),
+                top: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+        ))
+        .with_children(|hud| {
+            for slot in PlayerSlot::ALL {
+                hud.spawn((
+                    FrameDataText(slot),
+                    Text::new(""),
+                    TextFont::from_font_size(HUD_FONT_SIZE),
+                    TextColor(Color::WHITE),
+                ));
+            }
+
+            for slot in PlayerSlot::ALL {
+                hud.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(1.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for index in 0..FRAME_METER_TICKS {
+                        row.spawn((
+                            FrameMeterCell { slot, index },
+                            Node {
+                                width: Val::Px(METER_CELL_SIZE),
+                                height: Val::Px(METER_CELL_SIZE * 2.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::NONE),
+                        ));
+                    }
+                });
+            }
+        });
+}
+
+fn update_frame_data_text(
+    manifest_assets: Res<Assets<CharacterManifestAsset>>,
+    fighters: Query<(&PlayerSlot, &FrameDataTracker, &CharacterManifestHandle)>,
+    mut texts: Query<(&FrameDataText, &mut Text)>,
+) {
+    for (label, mut text) in &mut texts {
+        let Some((_, tracker, manifest_handle)) =
+            fighters.iter().find(|(slot, ..)| **slot == label.0)
+        else {
+            continue;
+        };
+
+        let line = match tracker.last_move {
+            Some(data) => {
+                let clip_name = manifest_assets
+                    .get(&manifest_handle.0)
+                    .and_then(|manifest| manifest.clips.get(data.clip_index as usize))
+                    .map_or("?", |clip| clip.name.as_str());
+                format_frame_data(label.0, clip_name, &data)
+            }
+            None => format!("P{} --", label.0.index() + 1),
+        };
+
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+}
+
+fn format_frame_data(slot: PlayerSlot, clip_name: &str, data: &MoveFrameData) -> String {
+    let advantage =
+        |value: Option<i32>| value.map_or_else(|| "--".to_string(), |v| format!("{v:+}"));
+
+    format!(
+        "P{} {}  startup {}  active {}  recovery {}  on hit {}  on block {}",
+        slot.index() + 1,
+        clip_name,
+        data.startup,
+        data.active,
+        data.recovery,
+        advantage(data.on_hit),
+        advantage(data.on_block),
+    )
+}
+
+fn update_frame_meter(
+    fighters: Query<(&PlayerSlot, &FrameDataTracker)>,
+    mut cells: Query<(&FrameMeterCell, &mut BackgroundColor)>,
+) {
+    for (cell, mut background) in &mut cells {
+        let kind = fighters
+            .iter()
+            .find(|(slot, _)| **slot == cell.slot)
+            .and_then(|(_, tracker)| tracker.meter.get(cell.index).copied());
+
+        let color = kind.map_or(Color::NONE, frame_kind_color);
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+fn frame_kind_color(kind: FrameKind) -> Color {
+    match kind {
+        FrameKind::Idle => Color::srgb(0.25, 0.25, 0.25),
+        FrameKind::Startup => Color::srgb(0.2, 0.8, 0.4),
+        FrameKind::Active => Color::srgb(0.9, 0.2, 0.25),
+        FrameKind::Recovery => Color::srgb(0.2, 0.45, 0.9),
+        FrameKind::Hitstun | FrameKind::Blockstun => Color::srgb(0.95, 0.8, 0.2),
+    }
+}
@@ -4,10 +4,24 @@ use bevy_asset_loader::{
     loading_state::{LoadingState, LoadingStateAppExt, config::ConfigureLoadingState},
     standard_dynamic_asset::StandardDynamicAssetCollection,
 };
-use bevy_enhanced_input::prelude::*;
 use game_common::{gameplay::character::locomotion::Facing, prelude::*};
 use iyes_progress::ProgressPlugin;
 
+use crate::{
+    audio::GameAudioPlugin,
+    camera::GameCameraPlugin,
+    character_select::{CharacterSelectPlugin, CharacterSelection},
+    desync::DesyncCheckPlugin,
+    effects::FrameEffectsPlugin,
+    frame_data::FrameDataHudPlugin,
+    input::{KeyboardLayout, character_actions},
+    input_display::InputDisplayPlugin,
+    replay::ReplayClientPlugin,
+    spectate::SpectateClientPlugin,
+    training::TrainingClientPlugin,
+    versus::VersusClientPlugin,
+};
+
 pub fn run() {
     let mut app = App::new();
 
@@ -60,89 +74,60 @@ pub fn run() {
         LoadingState::new(GameState::Loading)
             .continue_to_state(GameState::MainMenu)
             .with_dynamic_assets_file::<StandardDynamicAssetCollection>("game.assets.ron")
-            .load_collection::<AudioAssets>()
             .load_collection::<CharacterAssets>(),
     )
     .init_state::<GameState>();
 
-    app.add_systems(Startup, spawn_camera).add_systems(
-        OnEnter(GameState::MainMenu),
-        (spawn_character, start_background_audio),
-    );
-
-    app.run();
-}
+    app.add_plugins((
+        GameCameraPlugin,
+        GameAudioPlugin,
+        FrameEffectsPlugin,
+        CharacterSelectPlugin,
+        VersusClientPlugin,
+        TrainingClientPlugin,
+        FrameDataHudPlugin,
+        InputDisplayPlugin,
+        ReplayClientPlugin,
+        SpectateClientPlugin,
+        DesyncCheckPlugin,
+    ));
 
-#[derive(AssetCollection, Resource)]
-struct AudioAssets {
-    #[asset(key = "background_audio")]
-    background_audio: Handle<AudioSource>,
-}
+    app.add_systems(OnEnter(GameState::MainMenu), spawn_character);
 
-fn start_background_audio(mut commands: Commands, audio_assets: Res<AudioAssets>) {
-    commands.spawn((
-        AudioPlayer(audio_assets.background_audio.clone()),
-        PlaybackSettings::LOOP,
-    ));
+    app.run();
 }
 
 #[derive(AssetCollection, Resource)]
 pub struct CharacterAssets {
-    #[asset(key = "naruto_manifest")]
-    pub naruto: Handle<CharacterManifestAsset>,
+    /// Loading finishes once the roster and every manifest it lists are in.
+    #[asset(key = "character_roster")]
+    pub roster: Handle<CharacterRosterAsset>,
 }
 
-fn spawn_character(mut commands: Commands, char_assets: Res<CharacterAssets>) {
+fn spawn_character(
+    mut commands: Commands,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    selection: Res<CharacterSelection>,
+) {
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+
     commands.spawn((
         Name::new("Test character"),
-        Sprite::default(),
+        DespawnOnExit(GameState::MainMenu),
         Transform::from_xyz(300.0, -100.0, 0.0).with_scale(Vec3::splat(2.0)),
-        CharacterManifestHandle(char_assets.naruto.clone()),
+        CharacterManifestHandle(
+            selection
+                .character(PlayerSlot::One, roster)
+                .manifest
+                .clone(),
+        ),
         Character,
         Facing::Left,
         CharacterInput,
         CharacterAnimationState::default(),
-        Actions::<CharacterInput>::spawn(SpawnWith(|context: &mut ActionSpawner<_>| {
-            let crouch = context
-                .spawn((
-                    Action::<actions::Crouch>::new(),
-                    bindings![KeyCode::KeyS, KeyCode::ArrowDown, KeyCode::Numpad1],
-                ))
-                .id();
-
-            context.spawn((
-                Action::<actions::PlatformDrop>::new(),
-                Chord::single(crouch),
-                bindings![KeyCode::Space, KeyCode::KeyK, KeyCode::Numpad2],
-            ));
-
-            context.spawn((
-                Action::<actions::Jump>::new(),
-                bindings![KeyCode::Space, KeyCode::KeyK, KeyCode::Numpad2],
-            ));
-
-            context.spawn((
-                Action::<actions::Move>::new(),
-                DeadZone::default(),
-                Bindings::spawn((
-                    Bidirectional::new(KeyCode::KeyD, KeyCode::KeyA),
-                    Bidirectional::new(KeyCode::ArrowRight, KeyCode::ArrowLeft),
-                )),
-            ));
-
-            context.spawn((
-                Action::<actions::UpModifier>::new(),
-                bindings![KeyCode::KeyW, KeyCode::ArrowUp],
-            ));
-
-            context.spawn((
-                Action::<actions::Dash>::new(),
-                bindings![KeyCode::KeyL, KeyCode::Numpad3],
-            ));
-        })),
+        character_actions(KeyboardLayout::FULL),
     ));
 }
-
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Name::new("Camera"), Camera2d));
-}
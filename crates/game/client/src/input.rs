@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use game_common::prelude::*;
+
+/// Keyboard keys bound to each character action for one player.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyboardLayout {
+    pub left: &'static [KeyCode],
+    pub right: &'static [KeyCode],
+    pub up: &'static [KeyCode],
+    pub crouch: &'static [KeyCode],
+    pub jump: &'static [KeyCode],
+    pub dash: &'static [KeyCode],
+    pub attack: &'static [KeyCode],
+}
+
+impl KeyboardLayout {
+    /// Whole keyboard for a single player (sandbox).
+    pub const FULL: Self = Self {
+        left: &[KeyCode::KeyA, KeyCode::ArrowLeft],
+        right: &[KeyCode::KeyD, KeyCode::ArrowRight],
+        up: &[KeyCode::KeyW, KeyCode::ArrowUp],
+        crouch: &[KeyCode::KeyS, KeyCode::ArrowDown, KeyCode::Numpad1],
+        jump: &[KeyCode::Space, KeyCode::KeyK, KeyCode::Numpad2],
+        dash: &[KeyCode::KeyL, KeyCode::Numpad3],
+        attack: &[KeyCode::KeyJ, KeyCode::Numpad4],
+    };
+
+    /// Left half of a shared keyboard (player one).
+    pub const SPLIT_LEFT: Self = Self {
+        left: &[KeyCode::KeyA],
+        right: &[KeyCode::KeyD],
+        up: &[KeyCode::KeyW],
+        crouch: &[KeyCode::KeyS],
+        jump: &[KeyCode::KeyF],
+        dash: &[KeyCode::KeyG],
+        attack: &[KeyCode::KeyH],
+    };
+
+    /// Right half of a shared keyboard (player two).
+    pub const SPLIT_RIGHT: Self = Self {
+        left: &[KeyCode::ArrowLeft],
+        right: &[KeyCode::ArrowRight],
+        up: &[KeyCode::ArrowUp],
+        crouch: &[KeyCode::ArrowDown, KeyCode::Numpad1],
+        jump: &[KeyCode::KeyK, KeyCode::Numpad2],
+        dash: &[KeyCode::KeyL, KeyCode::Numpad3],
+        attack: &[KeyCode::KeyJ, KeyCode::Numpad4],
+    };
+
+    pub fn for_slot(slot: PlayerSlot) -> Self {
+        match slot {
+            PlayerSlot::One => Self::SPLIT_LEFT,
+            PlayerSlot::Two => Self::SPLIT_RIGHT,
+        }
+    }
+}
+
+fn keys(codes: &'static [KeyCode]) -> SpawnIter<impl Iterator<Item = Binding> + Send + Sync> {
+    SpawnIter(codes.iter().map(|&key| Binding::from(key)))
+}
+
+/// Spawns the full set of character actions, bound to `layout` on the keyboard
+/// and to the standard layout on whichever gamepad the context's `GamepadDevice` selects.
+pub fn character_actions(layout: KeyboardLayout) -> impl Bundle {
+    Actions::<CharacterInput>::spawn(SpawnWith(move |context: &mut ActionSpawner<_>| {
+        let crouch = context
+            .spawn((
+                Action::<actions::Crouch>::new(),
+                Bindings::spawn((
+                    keys(layout.crouch),
+                    Spawn(Binding::from(GamepadButton::DPadDown)),
+                )),
+            ))
+            .id();
+
+        context.spawn((
+            Action::<actions::PlatformDrop>::new(),
+            Chord::single(crouch),
+            Bindings::spawn((
+                keys(layout.jump),
+                Spawn(Binding::from(GamepadButton::South)),
+            )),
+        ));
+
+        context.spawn((
+            Action::<actions::Jump>::new(),
+            Bindings::spawn((
+                keys(layout.jump),
+                Spawn(Binding::from(GamepadButton::South)),
+            )),
+        ));
+
+        context.spawn((
+            Action::<actions::Move>::new(),
+            DeadZone::default(),
+            Bindings::spawn((
+                keys(layout.right),
+                SpawnIter(
+                    layout
+                        .left
+                        .iter()
+                        .map(|&key| (Binding::from(key), Negate::all())),
+                ),
+                Bidirectional::new(GamepadButton::DPadRight, GamepadButton::DPadLeft),
+                Spawn(Binding::from(GamepadAxis::LeftStickX)),
+            )),
+        ));
+
+        context.spawn((
+            Action::<actions::UpModifier>::new(),
+            Bindings::spawn((keys(layout.up), Spawn(Binding::from(GamepadButton::DPadUp)))),
+        ));
+
+        context.spawn((
+            Action::<actions::Dash>::new(),
+            Bindings::spawn((keys(layout.dash), Spawn(Binding::from(GamepadButton::East)))),
+        ));
+
+        context.spawn((
+            Action::<actions::Attack>::new(),
+            Bindings::spawn((
+                keys(layout.attack),
+                Spawn(Binding::from(GamepadButton::West)),
+            )),
+        ));
+    }))
+}
+
+/// Hands a gamepad to the first versus player still waiting for one, the first
+/// time any of its buttons is pressed.
+pub fn assign_gamepads(
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut players: Query<(&PlayerSlot, &mut GamepadDevice)>,
+) {
+    for (gamepad_entity, gamepad) in &gamepads {
+        if gamepad.get_just_pressed().next().is_none() {
+            continue;
+        }
+
+        let already_assigned = players
+            .iter()
+            .any(|(_, device)| matches!(*device, GamepadDevice::Single(e) if e == gamepad_entity));
+        if already_assigned {
+            continue;
+        }
+
+        let next_slot = players
+            .iter()
+            .filter(|(_, device)| matches!(**device, GamepadDevice::None))
+            .map(|(slot, _)| *slot)
+            .min();
+
+        let Some(next_slot) = next_slot else {
+            continue;
+        };
+
+        for (slot, mut device) in &mut players {
+            if *slot == next_slot {
+                *device = GamepadDevice::Single(gamepad_entity);
+                log::info!("Assigned gamepad {gamepad_entity} to {slot:?}");
+            }
+        }
+    }
+}
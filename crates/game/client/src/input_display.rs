@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use game_common::{
+    gameplay::character::input::{InputFrame, InputHistory},
+    prelude::*,
+};
+
+/// Number of run-length entries listed per player.
+const INPUT_DISPLAY_ROWS: usize = 18;
+/// Held-tick counts are capped at this value in the list.
+const MAX_DISPLAYED_TICKS: usize = 99;
+/// Bevy's built-in font has no arrow glyphs, so the log ships its own.
+const INPUT_DISPLAY_FONT: &str = "fonts/DejaVuSansMono.ttf";
+
+pub struct InputDisplayPlugin;
+
+impl Plugin for InputDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputDisplaySettings>()
+            .add_systems(OnEnter(GameState::InGame), spawn_input_display)
+            .add_systems(
+                Update,
+                (toggle_input_display, update_input_display)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Runtime toggle for the on-screen input history (F5).
+#[derive(Resource, Debug, Clone, Default)]
+pub struct InputDisplaySettings {
+    pub visible: bool,
+}
+
+/// Input log column for one player.
+#[derive(Component, Debug)]
+struct InputDisplayText(PlayerSlot);
+
+fn spawn_input_display(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<InputDisplaySettings>,
+) {
+    let font = asset_server.load(INPUT_DISPLAY_FONT);
+    let visibility = if settings.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for slot in PlayerSlot::ALL {
+        let mut node = Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(96.0),
+            ..default()
+        };
+        match slot {
+            PlayerSlot::One => node.left = Val::Px(12.0),
+            PlayerSlot::Two => node.right = Val::Px(12.0),
+        }
+
+        commands.spawn((
+            Name::new(format!("Input display P{}", slot.index() + 1)),
+            DespawnOnExit(GameState::InGame),
+            InputDisplayText(slot),
+            node,
+            visibility,
+            Text::new(""),
+            TextFont::from(font.clone()).with_font_size(14.0),
+            TextColor(Color::WHITE),
+        ));
+    }
+}
+
+fn toggle_input_display(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<InputDisplaySettings>,
+    mut displays: Query<&mut Visibility, With<InputDisplayText>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    settings.visible = !settings.visible;
+    for mut visibility in &mut displays {
+        *visibility = if settings.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Lists each player's recent inputs newest first, one line per run of identical
+/// ticks, read from the same `InputHistory` the simulation consumes (live or replayed).
+fn update_input_display(
+    settings: Res<InputDisplaySettings>,
+    fighters: Query<(&PlayerSlot, &InputHistory)>,
+    mut displays: Query<(&InputDisplayText, &mut Text)>,
+) {
+    if !settings.visible {
+        return;
+    }
+
+    for (display, mut text) in &mut displays {
+        let Some((_, history)) = fighters.iter().find(|(slot, _)| **slot == display.0) else {
+            continue;
+        };
+
+        let log = run_lengths(history)
+            .take(INPUT_DISPLAY_ROWS)
+            .map(|(frame, ticks)| {
+                format!(
+                    "{:>2} {}",
+                    ticks.min(MAX_DISPLAYED_TICKS),
+                    describe_frame(frame)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if text.0 != log {
+            text.0 = log;
+        }
+    }
+}
+
+/// Groups the history into `(frame, held ticks)` runs, newest first. Stick tilt is
+/// left out, so a wobbling analog stick does not split a held direction into runs.
+fn run_lengths(history: &InputHistory) -> impl Iterator<Item = (InputFrame, usize)> + '_ {
+    let mut frames = history.iter().rev().map(|frame| frame.buttons()).peekable();
+
+    std::iter::from_fn(move || {
+        let frame = frames.next()?;
+        let mut ticks = 1;
+        while frames.next_if_eq(&frame).is_some() {
+            ticks += 1;
+        }
+        Some((frame, ticks))
+    })
+}
+
+/// Direction as an arrow (a dot for neutral) followed by the held buttons.
+fn describe_frame(frame: InputFrame) -> String {
+    let up = frame.contains(InputFrame::UP);
+    let down = frame.contains(InputFrame::DOWN);
+    let arrow = match frame.horizontal_sign() {
+        -1 if up => '↖',
+        -1 if down => '↙',
+        -1 => '←',
+        1 if up => '↗',
+        1 if down => '↘',
+        1 => '→',
+        _ if up => '↑',
+        _ if down => '↓',
+        _ => '•',
+    };
+
+    let mut line = arrow.to_string();
+    for (button, label) in [
+        (InputFrame::JUMP, " J"),
+        (InputFrame::DASH, " D"),
+        (InputFrame::ATTACK, " A"),
+    ] {
+        if frame.contains(button) {
+            line.push_str(label);
+        }
+    }
+    line
+}
@@ -1,4 +1,18 @@
+pub mod audio;
+pub mod camera;
+pub mod character_select;
+pub mod desync;
+pub mod effects;
+pub mod frame_data;
 pub mod game;
+pub mod input;
+pub mod input_display;
+pub mod replay;
+pub mod spectate;
+pub mod storage;
+pub mod training;
+pub mod versus;
+
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
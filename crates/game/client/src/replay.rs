@@ -0,0 +1,385 @@
+use bevy::prelude::*;
+use game_common::{
+    gameplay::{
+        character::input::MockedInput,
+        replay::{
+            DEFAULT_STAGE, MAX_REPLAY_SPEED, MIN_REPLAY_SPEED, REPLAY_EXTENSION, Replay,
+            ReplayCommand, ReplayFighter, ReplayHeader, ReplayPlayback, ReplayRecorder,
+            ReplayRestartMessage, handle_replay_commands,
+        },
+        versus::VersusRounds,
+    },
+    prelude::*,
+};
+
+use crate::{
+    character_select::CharacterSelection,
+    game::CharacterAssets,
+    spectate::SpectatorLink,
+    storage::{self, FileInbox},
+    versus::{CPU_SEED, VersusSetup, fighter_bundle, fighter_palette},
+};
+
+/// Ticks skipped by one seek keypress (5 seconds at 60Hz).
+const SEEK_STEP_TICKS: u32 = 300;
+
+pub struct ReplayClientPlugin;
+
+impl Plugin for ReplayClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastReplay>()
+            .init_resource::<ReplayInbox>()
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (
+                    // Training resets and dummy settings are not inputs, so a training
+                    // session could not be played back from its input frames alone
+                    start_recording.run_if(resource_equals(GameMode::Versus)),
+                    (spawn_replay_fighters, spawn_replay_hud)
+                        .run_if(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::InGame),
+                (
+                    finish_recording,
+                    stop_playback.run_if(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (replay_menu_hotkeys, start_inbox_replay).run_if(in_state(GameState::MainMenu)),
+                    (
+                        replay_hotkeys,
+                        // Fighters must be rebuilt before `fast_forward_replay` re-simulates
+                        // from tick zero later this frame
+                        respawn_replay_fighters.after(handle_replay_commands),
+                        update_replay_hud,
+                    )
+                        .run_if(in_state(GameState::InGame).and(resource_exists::<ReplayPlayback>)),
+                ),
+            );
+    }
+}
+
+/// Most recently finished recording, kept until the next match ends.
+#[derive(Resource, Debug, Default)]
+pub struct LastReplay(pub Option<Replay>);
+
+/// Replay file picked by the player.
+#[derive(Resource, Debug, Clone, Default, Deref)]
+pub struct ReplayInbox(FileInbox);
+
+#[derive(Component, Debug)]
+struct ReplayHudText;
+
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    manifests: Res<Assets<CharacterManifestAsset>>,
+    selection: Res<CharacterSelection>,
+    setup: Res<VersusSetup>,
+) {
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+
+    let mut fighters = Vec::with_capacity(PlayerSlot::ALL.len());
+    for slot in PlayerSlot::ALL {
+        let Some(manifest) = manifests.get(&selection.character(slot, roster).manifest) else {
+            warn!("Character manifest not loaded, match will not be recorded");
+            return;
+        };
+        fighters.push(ReplayFighter {
+            manifest_id: manifest.id.clone(),
+            content_hash: manifest.content_hash,
+            spawn: slot.start_position(),
+        });
+    }
+
+    recorder.start(ReplayHeader {
+        game_version: env!("CARGO_PKG_VERSION").to_string(),
+        stage: DEFAULT_STAGE.to_string(),
+        rng_seed: setup.cpu.map_or(0, |_| CPU_SEED),
+        fighters,
+    });
+}
+
+fn finish_recording(mut recorder: ResMut<ReplayRecorder>, mut last_replay: ResMut<LastReplay>) {
+    if let Some(replay) = recorder.finish()
+        && replay.tick_count() > 0
+    {
+        info!("Recorded replay of {} ticks", replay.tick_count());
+        last_replay.0 = Some(replay);
+    }
+}
+
+/// F6 saves the last match, F7 opens a replay file and F8 plays the last match back.
+fn replay_menu_hotkeys(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    last_replay: Res<LastReplay>,
+    inbox: Res<ReplayInbox>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::F6) {
+        match &last_replay.0 {
+            Some(replay) => match replay.encode() {
+                Ok(bytes) => {
+                    if let Err(err) = storage::save("replay", REPLAY_EXTENSION, &bytes) {
+                        error!("Failed to save replay: {err}");
+                    }
+                }
+                Err(err) => error!("Failed to encode replay: {err}"),
+            },
+            None => info!("No match recorded yet"),
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::F7)
+        && let Err(err) = storage::open(REPLAY_EXTENSION, inbox.0.clone())
+    {
+        error!("Failed to open replay: {err}");
+    }
+
+    if keyboard.just_pressed(KeyCode::F8)
+        && let Some(replay) = &last_replay.0
+    {
+        start_playback(
+            &mut commands,
+            ReplayPlayback::new(replay.clone()),
+            &mut mode,
+            &mut next_state,
+        );
+    }
+}
+
+fn start_inbox_replay(
+    mut commands: Commands,
+    inbox: Res<ReplayInbox>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(bytes) = inbox.take() else {
+        return;
+    };
+
+    match Replay::decode(&bytes) {
+        Ok(replay) => start_playback(
+            &mut commands,
+            ReplayPlayback::new(replay),
+            &mut mode,
+            &mut next_state,
+        ),
+        Err(err) => error!("Failed to read replay: {err}"),
+    }
+}
+
+pub(crate) fn start_playback(
+    commands: &mut Commands,
+    playback: ReplayPlayback,
+    mode: &mut GameMode,
+    next_state: &mut NextState<GameState>,
+) {
+    let game_version = env!("CARGO_PKG_VERSION");
+    if playback.replay.header.game_version != game_version {
+        warn!(
+            "Replay was recorded on version {}, running {game_version}; playback may diverge",
+            playback.replay.header.game_version
+        );
+    }
+
+    commands.insert_resource(playback);
+    *mode = GameMode::Replay;
+    next_state.set(GameState::InGame);
+}
+
+fn stop_playback(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    commands.remove_resource::<ReplayPlayback>();
+    time.set_relative_speed(1.0);
+    time.unpause();
+}
+
+fn spawn_replay_fighters(
+    mut commands: Commands,
+    playback: Res<ReplayPlayback>,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    manifests: Res<Assets<CharacterManifestAsset>>,
+) {
+    if let Some(roster) = rosters.get(&char_assets.roster) {
+        spawn_from_header(&mut commands, &playback.replay.header, roster, &manifests);
+    }
+}
+
+/// Rebuilds the fighters from the header, and restarts the round count, when the viewer
+/// rewinds.
+fn respawn_replay_fighters(
+    mut commands: Commands,
+    mut restarts: MessageReader<ReplayRestartMessage>,
+    playback: Res<ReplayPlayback>,
+    fighters: Query<Entity, With<PlayerSlot>>,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    manifests: Res<Assets<CharacterManifestAsset>>,
+) {
+    if restarts.read().count() == 0 {
+        return;
+    }
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+
+    for fighter in &fighters {
+        commands.entity(fighter).despawn();
+    }
+    commands.insert_resource(VersusRounds::default());
+    spawn_from_header(&mut commands, &playback.replay.header, roster, &manifests);
+}
+
+fn spawn_from_header(
+    commands: &mut Commands,
+    header: &ReplayHeader,
+    roster: &CharacterRosterAsset,
+    manifests: &Assets<CharacterManifestAsset>,
+) {
+    let mut handles = Vec::with_capacity(header.fighters.len());
+    for fighter in &header.fighters {
+        let handle = match roster.find(manifests, &fighter.manifest_id) {
+            Some(character) => &character.manifest,
+            None => {
+                let fallback = &roster.get(0).manifest;
+                warn!(
+                    "Replay fighter '{}' is not available, using '{}'",
+                    fighter.manifest_id,
+                    manifests
+                        .get(fallback)
+                        .map_or("?", |manifest| manifest.id.as_str())
+                );
+                fallback
+            }
+        };
+        if let Some(manifest) = manifests.get(handle)
+            && manifest.id == fighter.manifest_id
+            && fighter.content_hash != 0
+            && manifest.content_hash != fighter.content_hash
+        {
+            warn!(
+                "Manifest '{}' changed since the replay was recorded; playback may diverge",
+                manifest.id
+            );
+        }
+
+        handles.push(handle);
+    }
+
+    for ((slot, fighter), handle) in PlayerSlot::ALL
+        .into_iter()
+        .zip(&header.fighters)
+        .zip(&handles)
+    {
+        commands.spawn((
+            fighter_bundle(
+                slot,
+                fighter.spawn,
+                (*handle).clone(),
+                fighter_palette(slot, &handles),
+            ),
+            MockedInput::default(),
+        ));
+    }
+}
+
+/// Space pauses, Period steps one tick, Left/Right seek, Up/Down change speed and
+/// Home rewinds to the start.
+fn replay_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    playback: Res<ReplayPlayback>,
+    mut replay_writer: MessageWriter<ReplayCommand>,
+) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        replay_writer.write(ReplayCommand::TogglePause);
+    }
+    if keyboard.just_pressed(KeyCode::Period) {
+        replay_writer.write(ReplayCommand::Step);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        replay_writer.write(ReplayCommand::Seek(
+            playback.cursor.saturating_sub(SEEK_STEP_TICKS),
+        ));
+    }
+    if keyboard.just_pressed(KeyCode::ArrowRight) {
+        replay_writer.write(ReplayCommand::Seek(playback.cursor + SEEK_STEP_TICKS));
+    }
+    if keyboard.just_pressed(KeyCode::Home) {
+        replay_writer.write(ReplayCommand::Seek(0));
+    }
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        replay_writer.write(ReplayCommand::SetSpeed(
+            (playback.speed * 2.0).min(MAX_REPLAY_SPEED),
+        ));
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        replay_writer.write(ReplayCommand::SetSpeed(
+            (playback.speed * 0.5).max(MIN_REPLAY_SPEED),
+        ));
+    }
+}
+
+fn spawn_replay_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Replay HUD"),
+        DespawnOnExit(GameState::InGame),
+        ReplayHudText,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        Text::new(""),
+        TextFont::from_font_size(16.0),
+        TextColor(Color::WHITE),
+    ));
+}
+
+fn update_replay_hud(
+    playback: Res<ReplayPlayback>,
+    spectator: Option<Res<SpectatorLink>>,
+    mut hud: Query<&mut Text, With<ReplayHudText>>,
+) {
+    let live = spectator.as_ref().is_some_and(|link| link.is_open());
+    let status = if playback.is_seeking() {
+        "SEEKING"
+    } else if playback.is_finished() && live {
+        "BUFFERING"
+    } else if playback.is_finished() {
+        "END"
+    } else if playback.paused {
+        "PAUSED"
+    } else {
+        "PLAYING"
+    };
+    let mut line = format!(
+        "REPLAY {status}  {}/{}  x{}",
+        playback.cursor,
+        playback.replay.tick_count(),
+        playback.speed
+    );
+    if let Some(link) = &spectator {
+        line.push_str(&format!(
+            "  LIVE {}  {} watching",
+            link.code(),
+            link.spectators()
+        ));
+    }
+
+    for mut text in &mut hud {
+        if text.0 != line {
+            text.0 = line.clone();
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Watching a live match on the game server. The server streams a room's confirmed
+//! inputs a few seconds behind the players; they are appended to a replay that the
+//! regular replay viewer plays back, so spectating re-simulates the match locally.
+//!
+//! A spectator joins with `?spectate=<room code>` on the page (optionally
+//! `&server=<host>`), or `GAME_SPECTATE` and `GAME_SERVER_HOST` natively.
+
+use bevy::prelude::*;
+use game_common::{
+    gameplay::{
+        SimTick,
+        character::input::InputFrame,
+        replay::{Replay, ReplayPlayback, ReplayRestartMessage},
+    },
+    net::{
+        ClientMessage, Connection, ConnectionState, LobbyEvent, LobbyRequest, PROTOCOL_VERSION,
+        RoomInfo, ServerAddress, ServerMessage, TransportKind,
+    },
+    prelude::*,
+};
+
+use crate::replay::start_playback;
+
+/// Name spectators introduce themselves with; they never show up in a room's seats.
+const SPECTATOR_NAME: &str = "Spectator";
+
+pub struct SpectateClientPlugin;
+
+impl Plugin for SpectateClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, connect_spectator).add_systems(
+            Update,
+            (
+                poll_spectator,
+                start_spectating.run_if(in_state(GameState::MainMenu)),
+            )
+                .chain()
+                .run_if(resource_exists::<SpectatorLink>),
+        );
+    }
+}
+
+/// Connection to the room being watched.
+#[derive(Resource, Debug)]
+pub struct SpectatorLink {
+    address: ServerAddress,
+    code: String,
+    connection: Connection,
+    greeted: bool,
+    closed: bool,
+    room: Option<RoomInfo>,
+    /// Match received before the viewer could be opened.
+    pending: Option<Replay>,
+}
+
+impl SpectatorLink {
+    fn new(address: ServerAddress, code: String) -> Self {
+        let connection = address.connect();
+        Self {
+            address,
+            code,
+            connection,
+            greeted: false,
+            closed: false,
+            room: None,
+            pending: None,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.closed
+    }
+
+    /// Spectators in the room, this one included.
+    pub fn spectators(&self) -> u32 {
+        self.room.as_ref().map_or(0, |room| room.spectators)
+    }
+
+    fn send(&mut self, message: ClientMessage) {
+        if let Err(err) = self.connection.send(message.channel(), message.encode()) {
+            warn!("Failed to send to the game server: {err}");
+        }
+    }
+}
+
+fn connect_spectator(mut commands: Commands) {
+    let Some((address, code)) = platform::spectate_target() else {
+        return;
+    };
+    info!("Spectating room {code} on {}", address.webtransport_url);
+    commands.insert_resource(SpectatorLink::new(address, code));
+}
+
+/// Says hello once connected, then applies the server's stream to the playback.
+fn poll_spectator(
+    mut link: ResMut<SpectatorLink>,
+    mut playback: Option<ResMut<ReplayPlayback>>,
+    mut sim_tick: ResMut<SimTick>,
+    mut restart_writer: MessageWriter<ReplayRestartMessage>,
+) {
+    if link.closed {
+        return;
+    }
+
+    if link.connection.kind() == TransportKind::WebTransport && link.connection.failed_to_open() {
+        warn!("WebTransport unavailable, falling back to WebSocket");
+        link.connection = link.address.connect_fallback();
+    }
+
+    if !link.greeted && link.connection.is_connected() {
+        link.greeted = true;
+        link.send(ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: SPECTATOR_NAME.to_owned(),
+            resume: None,
+            player: None,
+        });
+        let code = link.code.clone();
+        link.send(ClientMessage::Lobby(LobbyRequest::Spectate { code }));
+    }
+
+    while let Some(bytes) = link.connection.receive() {
+        let message = match ServerMessage::decode(&bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Undecodable message from the game server: {err}");
+                continue;
+            }
+        };
+
+        match message {
+            ServerMessage::SpectateStart { replay } => match Replay::decode(&replay) {
+                // A new match while watching the previous one: restart the viewer
+                Ok(replay) if playback.is_some() => {
+                    if let Some(playback) = playback.as_deref_mut() {
+                        *playback = ReplayPlayback::catch_up(replay);
+                    }
+                    *sim_tick = SimTick::default();
+                    restart_writer.write(ReplayRestartMessage);
+                }
+                Ok(replay) => link.pending = Some(replay),
+                Err(err) => warn!("Invalid match stream: {err}"),
+            },
+            ServerMessage::SpectateChunk { start, replay } => match Replay::decode(&replay) {
+                Ok(chunk) => {
+                    let received = match playback.as_deref() {
+                        Some(playback) => Some(playback.replay.tick_count()),
+                        None => link.pending.as_ref().map(Replay::tick_count),
+                    };
+                    if received != Some(start) {
+                        warn!("Match catch-up from tick {start} does not follow {received:?}");
+                        continue;
+                    }
+                    let extended = match playback.as_deref_mut() {
+                        Some(playback) => playback.extend_catch_up(&chunk),
+                        None => link
+                            .pending
+                            .as_mut()
+                            .map_or(Ok(()), |pending| pending.extend(&chunk)),
+                    };
+                    if let Err(err) = extended {
+                        warn!("Invalid match stream: {err}");
+                    }
+                }
+                Err(err) => warn!("Invalid match stream: {err}"),
+            },
+            ServerMessage::Inputs { tick, frames } => {
+                let replay = match playback.as_deref_mut() {
+                    Some(playback) => Some(&mut playback.replay),
+                    None => link.pending.as_mut(),
+                };
+                if let Some(replay) = replay {
+                    append_tick(replay, tick, &frames);
+                }
+            }
+            ServerMessage::Lobby(LobbyEvent::RoomUpdate(room)) => link.room = Some(room),
+            ServerMessage::Lobby(LobbyEvent::Rejected { reason }) => {
+                warn!("Cannot spectate room {}: {reason}", link.code);
+            }
+            ServerMessage::Lobby(LobbyEvent::RoomLeft) => {
+                info!("Room {} closed", link.code);
+                link.connection.close();
+            }
+            ServerMessage::Disconnect { reason } => {
+                warn!("Disconnected from the game server: {reason}");
+            }
+            _ => {}
+        }
+    }
+
+    if link.connection.state() == ConnectionState::Closed {
+        link.closed = true;
+        if let Some(err) = link.connection.error() {
+            warn!("Spectator connection closed: {err}");
+        }
+    }
+}
+
+/// Opens the replay viewer on a match received while in the main menu.
+fn start_spectating(
+    mut commands: Commands,
+    mut link: ResMut<SpectatorLink>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(replay) = link.pending.take() {
+        start_playback(
+            &mut commands,
+            ReplayPlayback::catch_up(replay),
+            &mut mode,
+            &mut next_state,
+        );
+    }
+}
+
+/// Appends the inputs of `tick` (counted from one) if it is the next one missing.
+fn append_tick(replay: &mut Replay, tick: u32, frames: &[(PlayerSlot, InputFrame)]) {
+    let expected = replay.tick_count() + 1;
+    if tick < expected {
+        return;
+    }
+    if tick > expected {
+        warn!("Match stream skipped from tick {expected} to {tick}");
+        return;
+    }
+
+    let mut row = vec![InputFrame::NONE; replay.fighter_count()];
+    for &(slot, frame) in frames {
+        if let Some(entry) = row.get_mut(slot.index()) {
+            *entry = frame;
+        }
+    }
+    replay.push_tick(&row);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use game_common::net::ServerAddress;
+
+    pub fn spectate_target() -> Option<(ServerAddress, String)> {
+        let code = std::env::var("GAME_SPECTATE")
+            .ok()
+            .filter(|code| !code.is_empty())?;
+        let host = std::env::var("GAME_SERVER_HOST").unwrap_or_else(|_| "localhost".to_owned());
+        Some((ServerAddress::from_host(&host, false), code))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use game_common::net::ServerAddress;
+
+    pub fn spectate_target() -> Option<(ServerAddress, String)> {
+        let location = web_sys::window()?.location();
+        let params = web_sys::UrlSearchParams::new_with_str(&location.search().ok()?).ok()?;
+        let code = params.get("spectate").filter(|code| !code.is_empty())?;
+        let host = params.get("server").or_else(|| location.hostname().ok())?;
+        let secure = location
+            .protocol()
+            .is_ok_and(|protocol| protocol == "https:");
+        Some((ServerAddress::from_host(&host, secure), code))
+    }
+}
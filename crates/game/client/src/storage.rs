@@ -0,0 +1,125 @@
+//! Saving and opening player files: a local directory natively, downloads and
+//! the file picker in the browser.
+
+use std::sync::{Arc, Mutex};
+
+pub use platform::{open, save};
+
+/// Bytes of a file picked by the player. Opening may finish asynchronously, so
+/// systems poll this instead of receiving the bytes directly.
+#[derive(Debug, Clone, Default)]
+pub struct FileInbox(Arc<Mutex<Option<Vec<u8>>>>);
+
+impl FileInbox {
+    pub fn put(&self, bytes: Vec<u8>) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = Some(bytes);
+        }
+    }
+
+    pub fn take(&self) -> Option<Vec<u8>> {
+        self.0.lock().ok().and_then(|mut slot| slot.take())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use anyhow::Context;
+
+    use super::FileInbox;
+
+    const SAVE_DIR: &str = "replays";
+
+    /// Writes `bytes` to a timestamped `<stem>-<secs>.<extension>` file in the save directory.
+    pub fn save(stem: &str, extension: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = PathBuf::from(SAVE_DIR).join(format!("{stem}-{timestamp}.{extension}"));
+        fs::write(&path, bytes)?;
+        log::info!("Saved {}", path.display());
+        Ok(())
+    }
+
+    /// Reads the newest `.<extension>` file in the save directory into `inbox`.
+    pub fn open(extension: &str, inbox: FileInbox) -> anyhow::Result<()> {
+        let newest = fs::read_dir(SAVE_DIR)?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == extension))
+            .max_by_key(|entry| entry.metadata().and_then(|meta| meta.modified()).ok())
+            .with_context(|| format!("no .{extension} files saved yet"))?;
+
+        inbox.put(fs::read(newest.path())?);
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use anyhow::{Context, anyhow};
+    use wasm_bindgen::{JsCast, prelude::*};
+
+    use super::FileInbox;
+
+    fn js_error(err: JsValue) -> anyhow::Error {
+        anyhow!("{err:?}")
+    }
+
+    fn document() -> anyhow::Result<web_sys::Document> {
+        web_sys::window()
+            .and_then(|window| window.document())
+            .context("no document")
+    }
+
+    /// Offers `bytes` to the browser as a `<stem>.<extension>` download.
+    pub fn save(stem: &str, extension: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+        let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(js_error)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+        let anchor: web_sys::HtmlAnchorElement = document()?
+            .create_element("a")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| anyhow!("created element is not an anchor"))?;
+        anchor.set_href(&url);
+        anchor.set_download(&format!("{stem}.{extension}"));
+        anchor.click();
+
+        web_sys::Url::revoke_object_url(&url).map_err(js_error)
+    }
+
+    /// Opens the browser file picker; the chosen file lands in `inbox` once read.
+    pub fn open(extension: &str, inbox: FileInbox) -> anyhow::Result<()> {
+        let input: web_sys::HtmlInputElement = document()?
+            .create_element("input")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| anyhow!("created element is not an input"))?;
+        input.set_type("file");
+        input.set_accept(&format!(".{extension}"));
+
+        let picker = input.clone();
+        let on_change = Closure::<dyn FnMut()>::once(move || {
+            let Some(file) = picker.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            wasm_bindgen_futures::spawn_local(async move {
+                match wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => inbox.put(js_sys::Uint8Array::new(&buffer).to_vec()),
+                    Err(err) => log::error!("Failed to read file: {err:?}"),
+                }
+            });
+        });
+        input.set_onchange(Some(on_change.as_ref().unchecked_ref()));
+        on_change.forget();
+
+        input.click();
+        Ok(())
+    }
+}
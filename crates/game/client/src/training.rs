@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use game_common::{gameplay::training::TrainingCommand, prelude::*};
+
+pub struct TrainingClientPlugin;
+
+impl Plugin for TrainingClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            training_hotkeys
+                .run_if(in_state(GameState::InGame).and(resource_equals(GameMode::Training))),
+        );
+    }
+}
+
+/// F1 cycles the dummy mode, F2 toggles recording, F3 toggles playback and R resets positions.
+fn training_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut training_writer: MessageWriter<TrainingCommand>,
+) {
+    let bindings = [
+        (KeyCode::F1, TrainingCommand::CycleDummyMode),
+        (KeyCode::F2, TrainingCommand::ToggleRecording),
+        (KeyCode::F3, TrainingCommand::TogglePlayback),
+        (KeyCode::KeyR, TrainingCommand::ResetPositions),
+    ];
+
+    for (key, command) in bindings {
+        if keyboard.just_pressed(key) {
+            training_writer.write(command);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use game_common::{
+    gameplay::{
+        ai::{CpuController, CpuDifficulty},
+        training::{TrainingDummy, TrainingSettings},
+        versus::{self, RoundPhase, VersusRounds},
+    },
+    prelude::*,
+};
+
+use crate::{
+    character_select::CharacterSelection,
+    game::CharacterAssets,
+    input::{KeyboardLayout, assign_gamepads, character_actions},
+};
+
+/// Seed for CPU execution errors, fixed so CPU matches are reproducible.
+pub(crate) const CPU_SEED: u64 = 0x5EED_CAFE_F00D_BEEF;
+
+/// Who player two is in the next versus match.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct VersusSetup {
+    /// `Some` when player two is a CPU opponent of the given difficulty.
+    pub cpu: Option<CpuDifficulty>,
+}
+
+pub struct VersusClientPlugin;
+
+impl Plugin for VersusClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VersusSetup>()
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (
+                    spawn_fighters.run_if(
+                        resource_equals(GameMode::Versus).or(resource_equals(GameMode::Training)),
+                    ),
+                    // Replays are always of versus matches
+                    (begin_rounds, spawn_round_hud).run_if(
+                        resource_equals(GameMode::Versus).or(resource_equals(GameMode::Replay)),
+                    ),
+                ),
+            )
+            .add_systems(OnExit(GameState::InGame), end_rounds)
+            .add_systems(
+                Update,
+                (
+                    start_versus.run_if(in_state(GameState::MainMenu)),
+                    (assign_gamepads, leave_match).run_if(in_state(GameState::InGame)),
+                    update_round_hud.run_if(resource_exists::<VersusRounds>),
+                ),
+            );
+    }
+}
+
+#[derive(Component, Debug)]
+struct RoundHudText;
+
+/// Enter (or Start) begins a two-player match, 1/2/3 begin a match against an
+/// easy/normal/hard CPU, and T opens training mode.
+fn start_versus(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut setup: ResMut<VersusSetup>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let cpu = if keyboard.just_pressed(KeyCode::Digit1) {
+        Some(CpuDifficulty::EASY)
+    } else if keyboard.just_pressed(KeyCode::Digit2) {
+        Some(CpuDifficulty::NORMAL)
+    } else if keyboard.just_pressed(KeyCode::Digit3) {
+        Some(CpuDifficulty::HARD)
+    } else {
+        None
+    };
+
+    let two_player = keyboard.just_pressed(KeyCode::Enter)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Start));
+
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        setup.cpu = None;
+        *mode = GameMode::Training;
+        next_state.set(GameState::InGame);
+    } else if cpu.is_some() || two_player {
+        setup.cpu = cpu;
+        *mode = GameMode::Versus;
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn leave_match(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        *mode = GameMode::Sandbox;
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn spawn_fighters(
+    mut commands: Commands,
+    char_assets: Res<CharacterAssets>,
+    rosters: Res<Assets<CharacterRosterAsset>>,
+    selection: Res<CharacterSelection>,
+    setup: Res<VersusSetup>,
+    mode: Res<GameMode>,
+    training: Res<TrainingSettings>,
+) {
+    let Some(roster) = rosters.get(&char_assets.roster) else {
+        return;
+    };
+    let training_mode = *mode == GameMode::Training;
+    let manifests = PlayerSlot::ALL.map(|slot| &selection.character(slot, roster).manifest);
+
+    for slot in PlayerSlot::ALL {
+        let mut fighter = commands.spawn(fighter_bundle(
+            slot,
+            start_position(slot, *mode, &training),
+            manifests[slot.index()].clone(),
+            fighter_palette(slot, &manifests),
+        ));
+
+        match (slot, setup.cpu) {
+            (PlayerSlot::Two, _) if training_mode => {
+                fighter.insert(TrainingDummy);
+            }
+            (PlayerSlot::Two, Some(difficulty)) => {
+                fighter.insert(CpuController::new(difficulty, CPU_SEED));
+            }
+            // Alone against a CPU or dummy, the human gets the whole keyboard and any gamepad
+            (PlayerSlot::One, _) if training_mode || setup.cpu.is_some() => {
+                fighter.insert(character_actions(KeyboardLayout::FULL));
+            }
+            _ => {
+                fighter.insert((
+                    GamepadDevice::None,
+                    character_actions(KeyboardLayout::for_slot(slot)),
+                ));
+            }
+        }
+    }
+}
+
+fn begin_rounds(mut commands: Commands) {
+    commands.insert_resource(VersusRounds::default());
+}
+
+fn end_rounds(mut commands: Commands) {
+    commands.remove_resource::<VersusRounds>();
+}
+
+fn spawn_round_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Round HUD"),
+        DespawnOnExit(GameState::InGame),
+        RoundHudText,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(""),
+        TextFont::from_font_size(20.0),
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Center),
+    ));
+}
+
+/// Shows the rounds won by each player and the round clock, or who took the round.
+fn update_round_hud(rounds: Res<VersusRounds>, mut hud: Query<&mut Text, With<RoundHudText>>) {
+    let [one, two] = rounds.wins;
+    let status = match rounds.phase {
+        RoundPhase::Fighting => format!("{:02}", rounds.seconds_left()),
+        RoundPhase::RoundOver => "ROUND OVER".to_owned(),
+        RoundPhase::MatchOver => match rounds.winner() {
+            Some(slot) => format!("PLAYER {} WINS", slot.index() + 1),
+            None => "DRAW".to_owned(),
+        },
+    };
+    let line = format!("P1  {one}   {status}   {two}  P2");
+
+    for mut text in &mut hud {
+        if text.0 != line {
+            text.0 = line.clone();
+        }
+    }
+}
+
+/// Where `slot` stands when a match of `mode` begins.
+pub(crate) fn start_position(
+    slot: PlayerSlot,
+    mode: GameMode,
+    training: &TrainingSettings,
+) -> Vec2 {
+    if mode == GameMode::Training {
+        return training.reset_positions[slot.index()];
+    }
+
+    slot.start_position()
+}
+
+/// Player two wears the first alternate palette in a mirror match so both fighters
+/// can be told apart; everyone else keeps the original colours.
+pub(crate) fn fighter_palette(
+    slot: PlayerSlot,
+    manifests: &[&Handle<CharacterManifestAsset>],
+) -> CharacterPalette {
+    match (slot, manifests) {
+        (PlayerSlot::Two, [first, second]) if first == second => CharacterPalette(1),
+        _ => CharacterPalette(0),
+    }
+}
+
+/// Components shared by every match fighter, before its input source is attached.
+pub(crate) fn fighter_bundle(
+    slot: PlayerSlot,
+    position: Vec2,
+    manifest: Handle<CharacterManifestAsset>,
+    palette: CharacterPalette,
+) -> impl Bundle {
+    (
+        versus::fighter_bundle(slot, position, manifest),
+        Name::new(format!("Player {}", slot.index() + 1)),
+        DespawnOnExit(GameState::InGame),
+        palette,
+    )
+}
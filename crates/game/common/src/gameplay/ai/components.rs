@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::gameplay::character::input::{InputFrame, MockedInput};
+
+/// Tunables that make a CPU opponent easier or harder to beat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CpuDifficulty {
+    /// Fixed ticks between the CPU seeing something happen and reacting to it.
+    pub reaction_delay_ticks: u16,
+    /// Chance (0..1) per tick that the chosen input is fumbled.
+    pub execution_error_rate: f32,
+}
+
+impl CpuDifficulty {
+    pub const EASY: Self = Self {
+        reaction_delay_ticks: 24,
+        execution_error_rate: 0.25,
+    };
+
+    pub const NORMAL: Self = Self {
+        reaction_delay_ticks: 14,
+        execution_error_rate: 0.1,
+    };
+
+    pub const HARD: Self = Self {
+        reaction_delay_ticks: 6,
+        execution_error_rate: 0.02,
+    };
+}
+
+impl Default for CpuDifficulty {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// High-level plan the CPU is currently executing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuIntent {
+    /// Close the distance to the opponent.
+    #[default]
+    Approach,
+    /// Hold a mid-range spacing, backing off when crowded.
+    Zone,
+    /// Guard against an incoming approach.
+    Block,
+    /// Rush in and attack while the opponent is recovering or in reach.
+    Punish,
+}
+
+/// Coarse classification of what the opponent is doing, read from its `MoveState` and clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpponentMove {
+    #[default]
+    Neutral,
+    Walking,
+    Dashing,
+    Airborne,
+    Attacking,
+    Guarding,
+    Recovering,
+}
+
+/// What the CPU could see on a given tick.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuObservation {
+    /// Signed horizontal distance from the CPU to its opponent.
+    pub distance_x: f32,
+    /// Signed vertical distance from the CPU to its opponent.
+    pub distance_y: f32,
+    pub grounded: bool,
+    pub dash_ready: bool,
+    pub opponent_move: OpponentMove,
+    /// Whether the opponent is moving towards the CPU.
+    pub opponent_closing: bool,
+}
+
+/// Drives a character through `MockedInput`, exactly like a human pressing buttons.
+#[derive(Component, Debug)]
+#[require(MockedInput)]
+pub struct CpuController {
+    pub difficulty: CpuDifficulty,
+    pub intent: CpuIntent,
+    /// Observations waiting out the reaction delay, oldest first.
+    pub(crate) pending: VecDeque<CpuObservation>,
+    /// Frame produced on the previous tick, used to release buttons between presses.
+    pub(crate) last_frame: InputFrame,
+    rng_state: u64,
+}
+
+impl CpuController {
+    pub fn new(difficulty: CpuDifficulty, seed: u64) -> Self {
+        Self {
+            difficulty,
+            intent: CpuIntent::default(),
+            pending: VecDeque::new(),
+            last_frame: InputFrame::NONE,
+            // xorshift must never be seeded with zero
+            rng_state: seed | 1,
+        }
+    }
+
+    /// Deterministic xorshift64* roll in `0.0..1.0`, so CPU matches replay identically.
+    pub(crate) fn roll(&mut self) -> f32 {
+        let mut x = self.rng_state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng_state = x;
+        let bits = x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40;
+        bits as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl Default for CpuController {
+    fn default() -> Self {
+        Self::new(CpuDifficulty::default(), 0x9E37_79B9_7F4A_7C15)
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+pub mod components;
+mod systems;
+
+pub use components::{CpuController, CpuDifficulty, CpuIntent, CpuObservation, OpponentMove};
+
+use crate::gameplay::character::input::sample_character_input;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            systems::drive_cpu_controllers.before(sample_character_input),
+        );
+    }
+}
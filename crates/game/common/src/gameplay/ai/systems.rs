@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use super::components::{CpuController, CpuIntent, CpuObservation, OpponentMove};
+use crate::gameplay::{
+    character::{
+        Character,
+        input::{InputFrame, MockedInput},
+        locomotion::components::{MoveState, SimPosition, Velocity},
+        presentation::{CharacterAnimationState, CharacterManifestAsset, CharacterManifestHandle},
+    },
+    fixed::Fx,
+    versus::Opponent,
+};
+
+/// Spacing the CPU tries to keep while zoning.
+const ZONE_RANGE: f32 = 260.0;
+/// Beyond this distance approaching is always the top priority.
+const FAR_RANGE: f32 = 420.0;
+/// Distance at which an incoming dash or jump is worth guarding against.
+const THREAT_RANGE: f32 = 200.0;
+/// Distance the CPU can cover with a dash or jump before the opponent recovers.
+const PUNISH_RANGE: f32 = 240.0;
+/// Distance at which the CPU's attack reaches its opponent.
+const ATTACK_RANGE: f32 = 110.0;
+/// Score bonus for sticking with the current intent, to avoid flip-flopping every tick.
+const INTENT_HYSTERESIS: f32 = 0.1;
+
+/// Samples each CPU's view of its opponent, waits out the reaction delay, picks the
+/// highest-utility intent and writes the resulting buttons to `MockedInput`.
+pub fn drive_cpu_controllers(
+    manifest_assets: Res<Assets<CharacterManifestAsset>>,
+    fighters: Query<
+        (
+            &SimPosition,
+            &MoveState,
+            &Velocity,
+            Option<&CharacterManifestHandle>,
+            Option<&CharacterAnimationState>,
+        ),
+        With<Character>,
+    >,
+    mut controllers: Query<(
+        &mut CpuController,
+        &mut MockedInput,
+        &SimPosition,
+        &MoveState,
+        Option<&Opponent>,
+    )>,
+) {
+    for (mut controller, mut mocked, position, state, opponent) in &mut controllers {
+        let Some((opp_position, opp_state, opp_velocity, opp_manifest, opp_anim)) =
+            opponent.and_then(|o| fighters.get(o.0).ok())
+        else {
+            controller.pending.clear();
+            mocked.0 = InputFrame::NONE;
+            continue;
+        };
+
+        let distance = (opp_position.0 - position.0).to_vec2();
+        let clip_name = opp_manifest
+            .zip(opp_anim)
+            .and_then(|(handle, anim)| {
+                manifest_assets
+                    .get(&handle.0)?
+                    .clips
+                    .get(anim.clip_index as usize)
+            })
+            .map(|clip| clip.name.as_str());
+
+        controller.pending.push_back(CpuObservation {
+            distance_x: distance.x,
+            distance_y: distance.y,
+            grounded: state.grounded,
+            dash_ready: !state.dashing && state.dash_cooldown_ticks == 0,
+            opponent_move: classify_move(opp_state, opp_velocity, clip_name),
+            opponent_closing: opp_velocity.0.x.signum() as f32 * distance.x < 0.0,
+        });
+
+        // React only to what happened `reaction_delay_ticks` ago
+        let delay = usize::from(controller.difficulty.reaction_delay_ticks);
+        while controller.pending.len() > delay + 1 {
+            controller.pending.pop_front();
+        }
+        if controller.pending.len() <= delay {
+            mocked.0 = InputFrame::NONE;
+            continue;
+        }
+        let Some(seen) = controller.pending.pop_front() else {
+            continue;
+        };
+
+        controller.intent = choose_intent(&seen, controller.intent);
+        let mut frame = plan_frame(controller.intent, &seen);
+
+        // Buttons are tapped for a single tick, then released, like a human would
+        if controller.last_frame.contains(InputFrame::JUMP) {
+            frame.remove(InputFrame::JUMP);
+        }
+        if controller.last_frame.contains(InputFrame::DASH) {
+            frame.remove(InputFrame::DASH);
+        }
+        if controller.last_frame.contains(InputFrame::ATTACK) {
+            frame.remove(InputFrame::ATTACK);
+        }
+
+        // Execution errors: occasionally drop the input or mash the wrong direction
+        if controller.roll() < controller.difficulty.execution_error_rate {
+            frame = if controller.roll() < 0.5 {
+                InputFrame::NONE
+            } else {
+                frame.with_horizontal(-frame.horizontal())
+            };
+        }
+
+        controller.last_frame = frame;
+        mocked.0 = frame;
+    }
+}
+
+fn classify_move(state: &MoveState, velocity: &Velocity, clip_name: Option<&str>) -> OpponentMove {
+    if state.dashing {
+        OpponentMove::Dashing
+    } else if !state.grounded {
+        OpponentMove::Airborne
+    } else if clip_name == Some("attack") {
+        OpponentMove::Attacking
+    } else if state.defending {
+        OpponentMove::Guarding
+    } else if state.guard_releasing || matches!(clip_name, Some("jump_land" | "hurt")) {
+        OpponentMove::Recovering
+    } else if velocity.0.x.abs() > Fx::from_int(10) {
+        OpponentMove::Walking
+    } else {
+        OpponentMove::Neutral
+    }
+}
+
+/// Utility scores in `0.0..=1.0` for each intent given what the CPU saw.
+fn score_intents(seen: &CpuObservation) -> [(CpuIntent, f32); 4] {
+    let distance = seen.distance_x.abs();
+
+    let approach = ((distance - ZONE_RANGE) / (FAR_RANGE - ZONE_RANGE)).clamp(0.0, 1.0);
+
+    let zone = if distance < ZONE_RANGE {
+        0.4 + 0.4 * (1.0 - distance / ZONE_RANGE)
+    } else {
+        0.3
+    };
+
+    let incoming = (matches!(
+        seen.opponent_move,
+        OpponentMove::Dashing | OpponentMove::Airborne
+    ) && seen.opponent_closing)
+        || seen.opponent_move == OpponentMove::Attacking;
+    let block = if incoming && seen.grounded && distance < THREAT_RANGE {
+        0.9
+    } else {
+        0.0
+    };
+
+    let punish = match seen.opponent_move {
+        OpponentMove::Recovering if distance < PUNISH_RANGE => 1.0,
+        OpponentMove::Guarding if distance < PUNISH_RANGE => 0.35,
+        OpponentMove::Neutral | OpponentMove::Walking if distance < ATTACK_RANGE => 0.85,
+        _ => 0.0,
+    };
+
+    [
+        (CpuIntent::Approach, approach),
+        (CpuIntent::Zone, zone),
+        (CpuIntent::Block, block),
+        (CpuIntent::Punish, punish),
+    ]
+}
+
+fn choose_intent(seen: &CpuObservation, current: CpuIntent) -> CpuIntent {
+    score_intents(seen)
+        .into_iter()
+        .map(|(intent, score)| {
+            let bonus = if intent == current {
+                INTENT_HYSTERESIS
+            } else {
+                0.0
+            };
+            (intent, score + bonus)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(current, |(intent, _)| intent)
+}
+
+fn plan_frame(intent: CpuIntent, seen: &CpuObservation) -> InputFrame {
+    let distance = seen.distance_x.abs();
+    let toward = InputFrame::NONE.with_horizontal(seen.distance_x);
+    let away = InputFrame::NONE.with_horizontal(-seen.distance_x);
+
+    match intent {
+        CpuIntent::Approach => {
+            if seen.dash_ready && seen.grounded && distance > FAR_RANGE {
+                toward | InputFrame::DASH
+            } else {
+                toward
+            }
+        }
+        CpuIntent::Zone => {
+            if distance < ZONE_RANGE - 40.0 {
+                away
+            } else if distance > ZONE_RANGE + 40.0 {
+                toward
+            } else {
+                InputFrame::NONE
+            }
+        }
+        CpuIntent::Block => InputFrame::DOWN,
+        CpuIntent::Punish => {
+            if seen.grounded && distance < ATTACK_RANGE {
+                InputFrame::ATTACK
+            } else if seen.dash_ready && seen.grounded {
+                toward | InputFrame::DASH
+            } else if seen.grounded {
+                toward | InputFrame::JUMP
+            } else {
+                toward
+            }
+        }
+    }
+}
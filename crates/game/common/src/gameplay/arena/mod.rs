@@ -1 +1,30 @@
+//! Extents of the stage fights take place on.
 
+use bevy::prelude::*;
+
+/// Area of the stage in render space. The ground is at `y = 0`; the fight camera never
+/// shows anything outside these bounds.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ArenaBounds {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Default for ArenaBounds {
+    fn default() -> Self {
+        Self {
+            left: -960.0,
+            right: 960.0,
+            bottom: -240.0,
+            top: 900.0,
+        }
+    }
+}
+
+impl ArenaBounds {
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.left, self.bottom, self.right, self.top)
+    }
+}
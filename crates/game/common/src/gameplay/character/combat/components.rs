@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+/// Message sent when an attack connects with a character that was not guarding.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct CharacterHitMessage {
+    pub attacker: Entity,
+    pub defender: Entity,
+    pub damage: f32,
+}
+
+/// Attack and stun state of a fighter. Attacks start from input, end when their clip
+/// finishes, and connect at most once.
+#[derive(Component, Debug, Default)]
+pub struct CombatState {
+    /// An attack clip is playing.
+    pub attacking: bool,
+    /// The attack in progress already hit or was blocked.
+    pub attack_connected: bool,
+    /// Ticks left unable to act after an unguarded hit.
+    pub hitstun_ticks: u16,
+    /// Ticks left held in guard after blocking.
+    pub blockstun_ticks: u16,
+}
+
+impl CombatState {
+    /// Starts a fresh attack.
+    pub fn start_attack(&mut self) {
+        self.attacking = true;
+        self.attack_connected = false;
+    }
+}
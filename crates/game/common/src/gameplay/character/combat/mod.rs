@@ -1 +1,28 @@
+use bevy::prelude::*;
 
+pub mod components;
+mod systems;
+
+pub use components::{CharacterHitMessage, CombatState};
+pub use systems::{resolve_attacks, tick_combat_timers};
+
+use super::{
+    input::process_character_input,
+    presentation::{advance_character_animations, update_character_animation_state},
+};
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<CharacterHitMessage>().add_systems(
+            FixedUpdate,
+            (
+                tick_combat_timers.before(process_character_input),
+                resolve_attacks
+                    .after(update_character_animation_state)
+                    .before(advance_character_animations),
+            ),
+        );
+    }
+}
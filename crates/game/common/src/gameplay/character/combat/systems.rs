@@ -0,0 +1,178 @@
+use bevy::{ecs::message::MessageWriter, prelude::*};
+
+use super::components::{CharacterHitMessage, CombatState};
+use crate::gameplay::{
+    character::{
+        Character, Vitals,
+        locomotion::components::{
+            CharacterBlockedMessage, Facing, Locks, MoveState, PushVelocity, SimPosition, Velocity,
+        },
+        presentation::{
+            CharacterAnimationState, CharacterManifestAsset, CharacterManifestHandle, FrameBox,
+            Hitbox,
+        },
+    },
+    fixed::{Fx, FxVec2},
+    versus::Opponent,
+};
+
+/// A blocked attack still deals this fraction of its damage.
+const CHIP_DAMAGE_DIVISOR: u16 = 8;
+
+/// Counts down hit and block stun, ahead of input processing so a stun running out
+/// frees the fighter on the same tick. Hitstun is mirrored into `Locks`.
+pub fn tick_combat_timers(mut query: Query<(&mut CombatState, &mut Locks)>) {
+    for (mut combat, mut locks) in &mut query {
+        combat.hitstun_ticks = combat.hitstun_ticks.saturating_sub(1);
+        combat.blockstun_ticks = combat.blockstun_ticks.saturating_sub(1);
+        locks.hitstun_locked = combat.hitstun_ticks > 0;
+    }
+}
+
+/// One attack landing on its target this tick.
+struct Contact {
+    attacker: Entity,
+    defender: Entity,
+    hitbox: Hitbox,
+    blocked: bool,
+    /// Sign of the push away from the attacker.
+    direction: i32,
+}
+
+/// Lands each attacking fighter's current hitbox on its opponent's hurtbox, once per
+/// attack. Guarding opponents block and take chip damage; anyone else takes the full
+/// hit, is knocked back and loses the move they were doing.
+#[allow(clippy::type_complexity)]
+pub fn resolve_attacks(
+    manifest_assets: Res<Assets<CharacterManifestAsset>>,
+    mut hit_writer: MessageWriter<CharacterHitMessage>,
+    mut block_writer: MessageWriter<CharacterBlockedMessage>,
+    mut fighters: ParamSet<(
+        Query<
+            (
+                Entity,
+                Option<&Opponent>,
+                &CharacterManifestHandle,
+                &CharacterAnimationState,
+                &SimPosition,
+                &Facing,
+                &CombatState,
+                &MoveState,
+            ),
+            With<Character>,
+        >,
+        Query<
+            (
+                &mut CombatState,
+                &mut MoveState,
+                &mut Velocity,
+                &mut PushVelocity,
+                &mut Locks,
+                &mut Vitals,
+            ),
+            With<Character>,
+        >,
+    )>,
+) {
+    // Collect first, so fighters trading hits on the same tick both connect
+    let mut contacts = Vec::new();
+    let bodies = fighters.p0();
+    for (attacker, opponent, manifest_handle, anim_state, position, facing, combat, _) in &bodies {
+        if !combat.attacking || combat.attack_connected {
+            continue;
+        }
+        let Some(hitbox) = manifest_assets
+            .get(&manifest_handle.0)
+            .and_then(|manifest| manifest.clips.get(anim_state.clip_index as usize))
+            .and_then(|clip| clip.frames.get(anim_state.frame_index as usize))
+            .and_then(|frame| frame.hitbox)
+        else {
+            continue;
+        };
+        let Some((defender, _, defender_handle, _, defender_position, defender_facing, _, guard)) =
+            opponent.and_then(|opponent| bodies.get(opponent.0).ok())
+        else {
+            continue;
+        };
+        let Some(hurtbox) = manifest_assets
+            .get(&defender_handle.0)
+            .map(|manifest| manifest.hurtbox)
+        else {
+            continue;
+        };
+
+        if overlaps(
+            place(hitbox.area, position.0, *facing),
+            place(hurtbox, defender_position.0, *defender_facing),
+        ) {
+            contacts.push(Contact {
+                attacker,
+                defender,
+                hitbox,
+                blocked: guard.defending,
+                direction: match facing {
+                    Facing::Right => 1,
+                    Facing::Left => -1,
+                },
+            });
+        }
+    }
+
+    let mut fighters = fighters.p1();
+    for contact in contacts {
+        if let Ok((mut combat, ..)) = fighters.get_mut(contact.attacker) {
+            combat.attack_connected = true;
+        }
+        let Ok((mut combat, mut state, mut velocity, mut push, mut locks, mut vitals)) =
+            fighters.get_mut(contact.defender)
+        else {
+            continue;
+        };
+
+        let hitbox = contact.hitbox;
+        let knockback = Fx::from_f32(hitbox.knockback) * contact.direction;
+        if contact.blocked {
+            let chip = hitbox.damage / CHIP_DAMAGE_DIVISOR;
+            vitals.health = vitals.health.saturating_sub(chip);
+            combat.blockstun_ticks = hitbox.blockstun_ticks;
+            push.0.x = knockback * Fx::from_ratio(1, 2);
+
+            block_writer.write(CharacterBlockedMessage {
+                attacker: contact.attacker,
+                defender: contact.defender,
+                damage_blocked: f32::from(hitbox.damage - chip),
+                chip_damage: f32::from(chip),
+            });
+        } else {
+            vitals.health = vitals.health.saturating_sub(hitbox.damage);
+            combat.attacking = false;
+            combat.hitstun_ticks = hitbox.hitstun_ticks;
+            locks.hitstun_locked = hitbox.hitstun_ticks > 0;
+            state.dashing = false;
+            state.dash_ticks = 0;
+            velocity.0.x = Fx::ZERO;
+            push.0.x = knockback;
+
+            hit_writer.write(CharacterHitMessage {
+                attacker: contact.attacker,
+                defender: contact.defender,
+                damage: f32::from(hitbox.damage),
+            });
+        }
+    }
+}
+
+/// `area` placed on a character at `position`, as its min and max corners.
+fn place(area: FrameBox, position: FxVec2, facing: Facing) -> (FxVec2, FxVec2) {
+    let mut offset = FxVec2::from_vec2(area.offset);
+    if facing == Facing::Left {
+        offset.x = -offset.x;
+    }
+    let center = position + offset;
+    let half = FxVec2::from_vec2(area.size * 0.5);
+    (center - half, center + half)
+}
+
+fn overlaps(a: (FxVec2, FxVec2), b: (FxVec2, FxVec2)) -> bool {
+    a.0.x < b.1.x && b.0.x < a.1.x && a.0.y < b.1.y && b.0.y < a.1.y
+}
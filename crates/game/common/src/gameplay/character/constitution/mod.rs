@@ -1 +1,29 @@
+use bevy::prelude::*;
 
+/// Health and super meter pools of a fighter.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vitals {
+    pub health: u16,
+    pub max_health: u16,
+    pub meter: u16,
+    pub max_meter: u16,
+}
+
+impl Default for Vitals {
+    fn default() -> Self {
+        Self {
+            health: 1000,
+            max_health: 1000,
+            meter: 0,
+            max_meter: 300,
+        }
+    }
+}
+
+impl Vitals {
+    /// Restores health and fills the meter completely.
+    pub fn refill(&mut self) {
+        self.health = self.max_health;
+        self.meter = self.max_meter;
+    }
+}
@@ -23,3 +23,7 @@ pub struct Dash;
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct PlatformDrop;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct Attack;
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+use crate::gameplay::fixed::Fx;
+
+/// Stick deflection below which the `Move` axis counts as centred.
+const MOVE_DEADZONE: f32 = 0.05;
+
+/// Largest stick tilt value, see `InputFrame::TILT`.
+const FULL_TILT: u8 = u8::MAX;
+
+bitflags! {
+    /// One fixed tick of character input: direction and button bits in the low byte,
+    /// stick tilt of the held horizontal direction in the high byte.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+    #[serde(transparent)]
+    pub struct InputFrame: u16 {
+        const NONE   = 0b0000_0000;
+        const LEFT   = 0b0000_0001;
+        const RIGHT  = 0b0000_0010;
+        const UP     = 0b0000_0100;
+        const DOWN   = 0b0000_1000;
+        const JUMP   = 0b0001_0000;
+        const DASH   = 0b0010_0000;
+        const ATTACK = 0b0100_0000;
+        /// How far an analog stick is tilted towards `LEFT` or `RIGHT`, in 255ths.
+        /// Zero stands for full tilt, which is also what digital inputs produce.
+        const TILT   = 0xff00;
+    }
+}
+
+impl InputFrame {
+    /// Horizontal axis value in `-1.0..=1.0`, as the `Move` action reported it.
+    pub fn horizontal(self) -> f32 {
+        self.horizontal_fx().to_f32()
+    }
+
+    /// Horizontal axis value as a fixed-point fraction, for the simulation.
+    pub fn horizontal_fx(self) -> Fx {
+        match self.tilt() {
+            0 => Fx::from_int(self.horizontal_sign()),
+            tilt => Fx::from_ratio(self.horizontal_sign() * i32::from(tilt), FULL_TILT.into()),
+        }
+    }
+
+    /// Horizontal direction as `-1`, `0` or `1`, regardless of stick tilt.
+    pub fn horizontal_sign(self) -> i32 {
+        match (self.contains(Self::LEFT), self.contains(Self::RIGHT)) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn with_horizontal(mut self, axis: f32) -> Self {
+        self.set(Self::LEFT, axis < -MOVE_DEADZONE);
+        self.set(Self::RIGHT, axis > MOVE_DEADZONE);
+        let tilt = (axis.abs().min(1.0) * f32::from(FULL_TILT)).round() as u8;
+        let tilt = if tilt == FULL_TILT || self.horizontal_sign() == 0 {
+            0
+        } else {
+            tilt
+        };
+        Self::from_bits_retain(self.buttons().bits() | (u16::from(tilt) << 8))
+    }
+
+    /// Direction and button bits only, as shown by the input display.
+    pub fn buttons(self) -> Self {
+        self.difference(Self::TILT)
+    }
+
+    fn tilt(self) -> u8 {
+        (self.bits() >> 8) as u8
+    }
+
+    /// Swaps left and right, converting between screen-relative and facing-relative input.
+    pub fn mirrored(self) -> Self {
+        let mut mirrored = self.difference(Self::LEFT | Self::RIGHT);
+        mirrored.set(Self::LEFT, self.contains(Self::RIGHT));
+        mirrored.set(Self::RIGHT, self.contains(Self::LEFT));
+        mirrored
+    }
+}
+
+/// Number of fixed ticks kept in an `InputHistory` (10 seconds at 60Hz).
+pub const INPUT_HISTORY_TICKS: usize = 600;
+
+/// Per-tick input frames sampled from a character's action entities, oldest first.
+#[derive(Component, Debug, Default, Clone)]
+pub struct InputHistory {
+    frames: VecDeque<InputFrame>,
+}
+
+impl InputHistory {
+    pub fn push(&mut self, frame: InputFrame) {
+        if self.frames.len() >= INPUT_HISTORY_TICKS {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Frame sampled on the most recent tick.
+    pub fn latest(&self) -> InputFrame {
+        self.frames.back().copied().unwrap_or_default()
+    }
+
+    /// Whether `buttons` are held on the most recent tick but weren't on the one before.
+    pub fn just_pressed(&self, buttons: InputFrame) -> bool {
+        let previous = self.frames.iter().rev().nth(1).copied().unwrap_or_default();
+        self.latest().contains(buttons) && !previous.contains(buttons)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InputFrame> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+/// Scripted input recorded into a character's `InputHistory` in place of its bound
+/// actions. Used by the CPU controller, training dummies and input playback.
+#[derive(Component, Debug, Default, Clone, Copy, Deref, DerefMut)]
+pub struct MockedInput(pub InputFrame);
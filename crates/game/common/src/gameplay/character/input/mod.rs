@@ -5,17 +5,35 @@ use bevy::{
 use bevy_enhanced_input::{EnhancedInputPlugin, context::InputContextAppExt};
 
 pub mod actions;
+pub mod frame;
 mod systems;
 
+pub use frame::{INPUT_HISTORY_TICKS, InputFrame, InputHistory, MockedInput};
+pub use systems::{process_character_input, sample_character_input};
+
 #[derive(Component, Debug, Default)]
+#[require(InputHistory)]
 pub struct CharacterInput;
 
+/// Samples and applies each character's input on the fixed tick.
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (sample_character_input, process_character_input).chain(),
+        );
+    }
+}
+
+/// Binds keyboards and gamepads to the `CharacterInput` actions. Characters without it
+/// are driven by `MockedInput` alone.
+pub struct InputDevicePlugin;
+
+impl Plugin for InputDevicePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EnhancedInputPlugin)
-            .add_input_context::<CharacterInput>()
-            .add_systems(FixedUpdate, systems::process_character_input);
+            .add_input_context::<CharacterInput>();
     }
 }
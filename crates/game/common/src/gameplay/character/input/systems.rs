@@ -2,30 +2,29 @@ use bevy::ecs::message::MessageWriter;
 use bevy::prelude::*;
 use bevy_enhanced_input::action::{Action, relationship::Actions};
 
-use crate::gameplay::character::{
-    input::{
-        CharacterInput,
-        actions::{Crouch, Dash, Jump, Move, PlatformDrop},
-    },
-    locomotion::components::{
-        AirState, CharacterDashedMessage, CharacterGuardStateChangedMessage,
-        CharacterJumpedMessage, CharacterPlatformDroppedMessage, Facing, Locks, MoveState,
-        MoveStats, Velocity,
+use crate::gameplay::{
+    character::{
+        combat::CombatState,
+        input::{
+            CharacterInput,
+            actions::{Attack, Crouch, Dash, Jump, Move, UpModifier},
+            frame::{InputFrame, InputHistory, MockedInput},
+        },
+        locomotion::components::{
+            AirState, CharacterDashedMessage, CharacterGuardStateChangedMessage,
+            CharacterJumpedMessage, CharacterPlatformDroppedMessage, Facing, Locks, MoveState,
+            MoveStats, Velocity,
+        },
     },
+    fixed::Fx,
 };
 
-#[allow(clippy::too_many_arguments)]
+/// Applies this tick's `InputFrame` from each character's `InputHistory` to locomotion.
 pub fn process_character_input(
-    time: Res<Time>,
     mut jumped_writer: MessageWriter<CharacterJumpedMessage>,
     mut dashed_writer: MessageWriter<CharacterDashedMessage>,
     mut guard_writer: MessageWriter<CharacterGuardStateChangedMessage>,
     mut drop_writer: MessageWriter<CharacterPlatformDroppedMessage>,
-    jumps: Query<&Action<Jump>>,
-    dashes: Query<&Action<Dash>>,
-    crouches: Query<&Action<Crouch>>,
-    platform_drops: Query<&Action<PlatformDrop>>,
-    movements: Query<&Action<Move>>,
     mut players: Query<(
         Entity,
         &mut Velocity,
@@ -33,61 +32,70 @@ pub fn process_character_input(
         &MoveStats,
         &Facing,
         Option<&Locks>,
-        &Actions<CharacterInput>,
+        &InputHistory,
+        &mut CombatState,
     )>,
 ) {
-    let dt = time.delta_secs();
-
-    for (entity, mut velocity, mut state, stats, facing, locks, actions) in &mut players {
+    for (entity, mut velocity, mut state, stats, facing, locks, history, mut combat) in &mut players
+    {
+        let frame = history.latest();
+        let move_dir = frame.horizontal_sign();
         let is_hitstun = locks.is_some_and(|l| l.hitstun_locked);
-        let is_move_locked = locks.is_some_and(|l| l.move_locked) || is_hitstun;
-        let is_jump_locked = locks.is_some_and(|l| l.jump_locked) || is_hitstun;
 
-        // Update timers
-        if state.jump_buffer_timer > 0.0 {
-            state.jump_buffer_timer -= dt;
+        // Process Attack input: starts on the press from the ground, and commits the
+        // character until its clip ends
+        if history.just_pressed(InputFrame::ATTACK)
+            && !is_hitstun
+            && !locks.is_some_and(|l| l.move_locked)
+            && combat.blockstun_ticks == 0
+            && state.grounded
+            && !state.dashing
+            && !combat.attacking
+        {
+            combat.start_attack();
+            velocity.0.x = Fx::ZERO;
         }
 
-        if state.dash_timer > 0.0 {
-            state.dash_timer -= dt;
-            if state.dash_timer <= 0.0 {
+        let is_move_locked = locks.is_some_and(|l| l.move_locked) || is_hitstun || combat.attacking;
+        let is_jump_locked = locks.is_some_and(|l| l.jump_locked) || is_hitstun || combat.attacking;
+
+        // Update timers
+        state.jump_buffer_ticks = state.jump_buffer_ticks.saturating_sub(1);
+
+        if state.dash_ticks > 0 {
+            state.dash_ticks -= 1;
+            if state.dash_ticks == 0 {
                 state.dashing = false;
             }
         }
 
-        if state.dash_cooldown_timer > 0.0 {
-            state.dash_cooldown_timer -= dt;
-        }
+        state.dash_cooldown_ticks = state.dash_cooldown_ticks.saturating_sub(1);
 
         if state.grounded {
-            state.coyote_timer = stats.coyote_time;
-        } else if state.coyote_timer > 0.0 {
-            state.coyote_timer -= dt;
+            state.coyote_ticks = stats.coyote_ticks;
+        } else {
+            state.coyote_ticks = state.coyote_ticks.saturating_sub(1);
         }
 
         // Process Platform Drop input (Chord of Crouch + Jump)
-        if let Some(drop) = platform_drops.iter_many(actions).next()
-            && **drop
-            && !is_move_locked
-        {
+        if frame.contains(InputFrame::DOWN | InputFrame::JUMP) && !is_move_locked {
             log::info!("Dropped from platform");
             drop_writer.write(CharacterPlatformDroppedMessage(entity));
         }
 
-        // Process Crouch input (S Key - behaves as Defend/Guard stance for now)
+        // Process Crouch input (S Key - behaves as Defend/Guard stance for now). Blockstun
+        // holds the guard up even if the button is let go.
         let was_defending = state.defending;
-        if let Some(crouch) = crouches.iter_many(actions).next() {
-            let is_crouch_held = **crouch;
-            state.crouching = is_crouch_held;
-
-            if is_crouch_held && !is_move_locked && state.grounded {
-                state.defending = true;
-                state.guard_releasing = false;
-                velocity.0.x = 0.0;
-            } else if state.defending {
-                state.defending = false;
-                state.guard_releasing = true;
-            }
+        let is_crouch_held = frame.contains(InputFrame::DOWN);
+        state.crouching = is_crouch_held;
+
+        if (is_crouch_held && !is_move_locked && state.grounded) || combat.blockstun_ticks > 0 {
+            state.defending = true;
+            state.guard_releasing = false;
+            velocity.0.x = Fx::ZERO;
+        } else if state.defending {
+            state.defending = false;
+            state.guard_releasing = true;
         }
 
         if state.defending != was_defending {
@@ -98,81 +106,57 @@ pub fn process_character_input(
         }
 
         // Process Dash input (L Key)
-        if let Some(dash) = dashes.iter_many(actions).next()
-            && **dash
+        if frame.contains(InputFrame::DASH)
             && !is_move_locked
             && !state.defending
-            && state.dash_cooldown_timer <= 0.0
+            && state.dash_cooldown_ticks == 0
         {
-            let facing_sign = match facing {
-                Facing::Right => 1.0,
-                Facing::Left => -1.0,
+            let input_dir = match (move_dir, facing) {
+                (0, Facing::Right) => 1,
+                (0, Facing::Left) => -1,
+                (dir, _) => dir,
             };
-            let input_dir = movements
-                .iter_many(actions)
-                .next()
-                .map_or(facing_sign, |m| {
-                    if m.abs() > 0.05 {
-                        m.signum()
-                    } else {
-                        facing_sign
-                    }
-                });
 
-            velocity.0.x = input_dir * stats.dash_speed;
+            velocity.0.x = stats.dash_speed * input_dir;
             state.dashing = true;
-            state.dash_timer = stats.dash_duration;
-            state.dash_cooldown_timer = stats.dash_cooldown;
+            state.dash_ticks = stats.dash_duration_ticks;
+            state.dash_cooldown_ticks = stats.dash_cooldown_ticks;
 
             dashed_writer.write(CharacterDashedMessage {
                 entity,
-                direction: Vec2::new(input_dir, 0.0),
+                direction: Vec2::new(input_dir as f32, 0.0),
                 is_air_dash: !state.grounded,
             });
         }
 
         // Process Move input
-        let mut raw_move_input = 0.0;
-        if let Some(movement) = movements.iter_many(actions).next() {
-            raw_move_input = **movement;
-            if !is_move_locked && !state.defending && !state.dashing {
-                let speed = if state.grounded {
-                    stats.ground_speed
-                } else {
-                    stats.air_speed
-                };
-                velocity.0.x = raw_move_input * speed;
-            }
+        if !is_move_locked && !state.defending && !state.dashing {
+            let speed = if state.grounded {
+                stats.ground_speed
+            } else {
+                stats.air_speed
+            };
+            velocity.0.x = speed * frame.horizontal_fx();
         }
 
         // Process Jump input buffer
-        if let Some(jump) = jumps.iter_many(actions).next()
-            && **jump
-            && !is_jump_locked
-            && !state.defending
-        {
-            state.jump_buffer_timer = stats.jump_buffer_time;
+        if frame.contains(InputFrame::JUMP) && !is_jump_locked && !state.defending {
+            state.jump_buffer_ticks = stats.jump_buffer_ticks;
         }
 
         // Execute Jump (Ground Jump or Double Jump)
-        let can_ground_jump = state.grounded || state.coyote_timer > 0.0;
+        let can_ground_jump = state.grounded || state.coyote_ticks > 0;
         let can_air_jump = !can_ground_jump && state.jumps_remaining > 0;
 
-        if state.jump_buffer_timer > 0.0 && !is_jump_locked && (can_ground_jump || can_air_jump) {
+        if state.jump_buffer_ticks > 0 && !is_jump_locked && (can_ground_jump || can_air_jump) {
             velocity.0.y = stats.jump_speed;
-            state.jump_buffer_timer = 0.0;
-            state.coyote_timer = 0.0;
-
-            let input_sign = if raw_move_input.abs() > 0.05 {
-                raw_move_input.signum()
-            } else {
-                0.0
-            };
+            state.jump_buffer_ticks = 0;
+            state.coyote_ticks = 0;
 
             if can_ground_jump {
                 // Ground Jump Physics: Preserve running momentum + inject instant drift boost
-                if input_sign != 0.0 {
-                    velocity.0.x += input_sign * stats.jump_drift_boost;
+                if move_dir != 0 {
+                    velocity.0.x += stats.jump_drift_boost * move_dir;
                 }
                 state.grounded = false;
                 state.mode = AirState::Rising;
@@ -184,8 +168,8 @@ pub fn process_character_input(
                 });
             } else {
                 // Air Jump / Double Jump Physics: Instant horizontal redirection
-                if input_sign != 0.0 {
-                    velocity.0.x = input_sign * stats.air_drift_speed;
+                if move_dir != 0 {
+                    velocity.0.x = stats.air_drift_speed * move_dir;
                 }
                 state.mode = AirState::Rising;
                 state.jumps_remaining = state.jumps_remaining.saturating_sub(1);
@@ -198,3 +182,61 @@ pub fn process_character_input(
         }
     }
 }
+
+/// Records this tick's `InputFrame` for every character. Scripted controllers push
+/// their `MockedInput` frame directly, so it is simulated on the very tick it was
+/// written; other characters record the current value of their bound actions.
+#[allow(clippy::too_many_arguments)]
+pub fn sample_character_input(
+    jumps: Query<&Action<Jump>>,
+    dashes: Query<&Action<Dash>>,
+    crouches: Query<&Action<Crouch>>,
+    up_modifiers: Query<&Action<UpModifier>>,
+    movements: Query<&Action<Move>>,
+    attacks: Query<&Action<Attack>>,
+    mut players: Query<(
+        Option<&MockedInput>,
+        Option<&Actions<CharacterInput>>,
+        &mut InputHistory,
+    )>,
+) {
+    for (mocked, actions, mut history) in &mut players {
+        let actions = match (mocked, actions) {
+            (Some(mocked), _) => {
+                history.push(mocked.0);
+                continue;
+            }
+            (None, Some(actions)) => actions,
+            (None, None) => continue,
+        };
+
+        let mut frame = InputFrame::NONE;
+
+        if let Some(movement) = movements.iter_many(actions).next() {
+            frame = frame.with_horizontal(**movement);
+        }
+
+        frame.set(
+            InputFrame::UP,
+            up_modifiers.iter_many(actions).next().is_some_and(|a| **a),
+        );
+        frame.set(
+            InputFrame::DOWN,
+            crouches.iter_many(actions).next().is_some_and(|a| **a),
+        );
+        frame.set(
+            InputFrame::JUMP,
+            jumps.iter_many(actions).next().is_some_and(|a| **a),
+        );
+        frame.set(
+            InputFrame::DASH,
+            dashes.iter_many(actions).next().is_some_and(|a| **a),
+        );
+        frame.set(
+            InputFrame::ATTACK,
+            attacks.iter_many(actions).next().is_some_and(|a| **a),
+        );
+
+        history.push(frame);
+    }
+}
@@ -1,8 +1,14 @@
 use bevy::{ecs::message::Message, prelude::*};
 
+use crate::gameplay::fixed::{Fx, FxVec2};
+
 /// Message sent when a character touches down on the ground.
 #[derive(Message, Debug, Clone, Copy)]
-pub struct CharacterLandedMessage(pub Entity);
+pub struct CharacterLandedMessage {
+    pub entity: Entity,
+    /// Downward speed at touchdown, in units per second.
+    pub fall_speed: Fx,
+}
 
 /// Message sent when a character executes a jump impulse (ground jump or double jump).
 #[derive(Message, Debug, Clone, Copy)]
@@ -46,47 +52,50 @@ pub struct CharacterBlockedMessage {
 #[derive(Message, Debug, Clone, Copy)]
 pub struct CharacterPlatformDroppedMessage(pub Entity);
 
+/// Locomotion tuning. Speeds are units per second and accelerations units per
+/// second squared, applied per tick via `Fx::per_tick`. Durations are `FixedUpdate`
+/// ticks.
 #[derive(Component, Debug)]
 pub struct MoveStats {
-    pub ground_speed: f32,
-    pub air_speed: f32,
-    pub ground_accel: f32,
-    pub air_accel: f32,
-    pub friction: f32,
-    pub jump_speed: f32,
+    pub ground_speed: Fx,
+    pub air_speed: Fx,
+    pub ground_accel: Fx,
+    pub air_accel: Fx,
+    pub friction: Fx,
+    pub jump_speed: Fx,
     pub max_jumps: u8,
-    pub jump_drift_boost: f32,
-    pub air_drift_speed: f32,
-    pub dash_speed: f32,
-    pub dash_friction: f32,
-    pub dash_duration: f32,
-    pub dash_cooldown: f32,
-    pub gravity: f32,
-    pub max_fall_speed: f32,
-    pub coyote_time: f32,
-    pub jump_buffer_time: f32,
+    pub jump_drift_boost: Fx,
+    pub air_drift_speed: Fx,
+    pub dash_speed: Fx,
+    pub dash_friction: Fx,
+    pub dash_duration_ticks: u16,
+    pub dash_cooldown_ticks: u16,
+    pub gravity: Fx,
+    pub max_fall_speed: Fx,
+    pub coyote_ticks: u16,
+    pub jump_buffer_ticks: u16,
 }
 
 impl Default for MoveStats {
     fn default() -> Self {
         Self {
-            ground_speed: 250.0,
-            air_speed: 200.0,
-            ground_accel: 1500.0,
-            air_accel: 800.0,
-            friction: 15.0,
-            jump_speed: 550.0,
+            ground_speed: Fx::from_int(250),
+            air_speed: Fx::from_int(200),
+            ground_accel: Fx::from_int(1500),
+            air_accel: Fx::from_int(800),
+            friction: Fx::from_int(15),
+            jump_speed: Fx::from_int(550),
             max_jumps: 2,
-            jump_drift_boost: 150.0,
-            air_drift_speed: 320.0,
-            dash_speed: 650.0,
-            dash_friction: 28.0,
-            dash_duration: 0.18,
-            dash_cooldown: 0.35,
-            gravity: 1400.0,
-            max_fall_speed: 800.0,
-            coyote_time: 0.1,
-            jump_buffer_time: 0.1,
+            jump_drift_boost: Fx::from_int(150),
+            air_drift_speed: Fx::from_int(320),
+            dash_speed: Fx::from_int(650),
+            dash_friction: Fx::from_int(28),
+            dash_duration_ticks: 11,
+            dash_cooldown_ticks: 21,
+            gravity: Fx::from_int(1400),
+            max_fall_speed: Fx::from_int(800),
+            coyote_ticks: 6,
+            jump_buffer_ticks: 6,
         }
     }
 }
@@ -99,10 +108,14 @@ pub struct MoveState {
     pub defending: bool,
     pub guard_releasing: bool,
     pub jumps_remaining: u8,
-    pub coyote_timer: f32,
-    pub jump_buffer_timer: f32,
-    pub dash_timer: f32,
-    pub dash_cooldown_timer: f32,
+    /// Ticks left in which a ground jump is still allowed after walking off a ledge.
+    pub coyote_ticks: u16,
+    /// Ticks left in which a buffered jump press will still fire.
+    pub jump_buffer_ticks: u16,
+    /// Ticks left in the current dash.
+    pub dash_ticks: u16,
+    /// Ticks until the next dash is allowed.
+    pub dash_cooldown_ticks: u16,
     pub mode: AirState,
 }
 
@@ -115,10 +128,10 @@ impl Default for MoveState {
             defending: false,
             guard_releasing: false,
             jumps_remaining: 2,
-            coyote_timer: 0.0,
-            jump_buffer_timer: 0.0,
-            dash_timer: 0.0,
-            dash_cooldown_timer: 0.0,
+            coyote_ticks: 0,
+            jump_buffer_ticks: 0,
+            dash_ticks: 0,
+            dash_cooldown_ticks: 0,
             mode: AirState::Grounded,
         }
     }
@@ -134,13 +147,25 @@ impl MoveState {
         self.mode = AirState::Grounded;
         self.jumps_remaining = max_jumps;
     }
+
+    /// Grounded and not committed to a dash or guard, so the character may switch sides.
+    pub fn is_neutral(&self) -> bool {
+        self.grounded && !self.dashing && !self.defending && !self.guard_releasing
+    }
 }
 
+/// Simulated position. `Transform` is derived from it for rendering and is never
+/// read back by gameplay systems.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct SimPosition(pub FxVec2);
+
+/// Locomotion velocity in units per second.
 #[derive(Component, Default, Debug)]
-pub struct Velocity(pub Vec2);
+pub struct Velocity(pub FxVec2);
 
+/// Knockback velocity in units per second, decaying independently of locomotion.
 #[derive(Component, Default, Debug)]
-pub struct PushVelocity(pub Vec2);
+pub struct PushVelocity(pub FxVec2);
 
 #[derive(Component, Default, Debug)]
 pub struct Locks {
@@ -157,6 +182,16 @@ pub enum Facing {
     Right,
 }
 
+/// How a character decides which way it faces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FacingMode {
+    /// Face the direction of horizontal travel (platformer-style sandbox).
+    #[default]
+    Velocity,
+    /// Face the `Opponent`, switching sides only on neutral grounded frames.
+    Opponent,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AirState {
     #[default]
@@ -6,12 +6,29 @@ mod systems;
 pub use components::{
     AirState, CharacterBlockedMessage, CharacterDashedMessage, CharacterGuardStateChangedMessage,
     CharacterJumpedMessage, CharacterLandedMessage, CharacterPlatformDroppedMessage,
-    CharacterTurnedMessage, Facing, Locks, MoveState, MoveStats, PushVelocity, Velocity,
+    CharacterTurnedMessage, Facing, FacingMode, Locks, MoveState, MoveStats, PushVelocity,
+    SimPosition, Velocity,
 };
 
 #[derive(Component, Debug, Default)]
-#[require(MoveStats, MoveState, Velocity, PushVelocity, Locks, Facing)]
-pub struct CharacterLocomotion;
+#[require(
+    MoveStats,
+    MoveState,
+    SimPosition,
+    Velocity,
+    PushVelocity,
+    Locks,
+    Facing
+)]
+pub struct CharacterLocomotion {
+    pub facing_mode: FacingMode,
+}
+
+impl CharacterLocomotion {
+    pub fn with_facing_mode(facing_mode: FacingMode) -> Self {
+        Self { facing_mode }
+    }
+}
 
 pub struct LocomotionPlugin;
 
@@ -24,6 +41,7 @@ impl Plugin for LocomotionPlugin {
             .add_message::<CharacterGuardStateChangedMessage>()
             .add_message::<CharacterBlockedMessage>()
             .add_message::<CharacterPlatformDroppedMessage>()
+            .add_systems(FixedFirst, systems::init_sim_positions)
             .add_systems(
                 FixedUpdate,
                 (
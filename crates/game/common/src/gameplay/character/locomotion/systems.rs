@@ -1,22 +1,35 @@
 use bevy::{ecs::message::MessageWriter, prelude::*};
 
-use super::components::{
-    AirState, CharacterLandedMessage, CharacterTurnedMessage, Facing, Locks, MoveState, MoveStats,
-    PushVelocity, Velocity,
+use super::{
+    CharacterLocomotion,
+    components::{
+        AirState, CharacterLandedMessage, CharacterTurnedMessage, Facing, FacingMode, Locks,
+        MoveState, MoveStats, PushVelocity, SimPosition, Velocity,
+    },
+};
+use crate::gameplay::{
+    fixed::{Fx, FxVec2},
+    versus::Opponent,
 };
 
-pub fn apply_gravity(
-    time: Res<Time>,
-    mut query: Query<(&mut Velocity, &mut MoveState, &MoveStats)>,
-) {
-    let dt = time.delta_secs();
+/// Horizontal speed or distance below which facing is left unchanged.
+const FACING_DEADZONE: Fx = Fx::from_ratio(1, 10);
+
+/// Seeds the simulated position of newly spawned characters from their spawn `Transform`.
+pub fn init_sim_positions(mut query: Query<(&Transform, &mut SimPosition), Added<SimPosition>>) {
+    for (transform, mut position) in &mut query {
+        position.0 = FxVec2::from_vec2(transform.translation.truncate());
+    }
+}
+
+pub fn apply_gravity(mut query: Query<(&mut Velocity, &mut MoveState, &MoveStats)>) {
     for (mut velocity, mut state, stats) in &mut query {
         if !state.grounded {
-            velocity.0.y -= stats.gravity * dt;
+            velocity.0.y -= stats.gravity.per_tick();
             if velocity.0.y < -stats.max_fall_speed {
                 velocity.0.y = -stats.max_fall_speed;
             }
-            state.mode = if velocity.0.y > 0.0 {
+            state.mode = if velocity.0.y > Fx::ZERO {
                 AirState::Rising
             } else {
                 AirState::Falling
@@ -26,9 +39,8 @@ pub fn apply_gravity(
 }
 
 pub fn apply_velocity(
-    time: Res<Time>,
     mut query: Query<(
-        &mut Transform,
+        &mut SimPosition,
         &mut Velocity,
         &mut PushVelocity,
         &MoveState,
@@ -36,13 +48,12 @@ pub fn apply_velocity(
         Option<&Locks>,
     )>,
 ) {
-    let dt = time.delta_secs();
-    for (mut transform, mut velocity, mut push_vel, state, stats, locks) in &mut query {
+    for (mut position, mut velocity, mut push_vel, state, stats, locks) in &mut query {
         let is_hitstun = locks.is_some_and(|l| l.hitstun_locked);
 
         // Combine locomotion velocity and knockback push velocity
         let total_vel = velocity.0 + push_vel.0;
-        transform.translation += Vec3::new(total_vel.x, total_vel.y, 0.0) * dt;
+        position.0 += total_vel.per_tick();
 
         // Apply fast friction decay to high-velocity dash impulses
         if state.dashing && !is_hitstun {
@@ -50,17 +61,18 @@ pub fn apply_velocity(
             let current_abs = velocity.0.x.abs();
             if current_abs > target_speed {
                 let sign = velocity.0.x.signum();
-                let new_abs = (current_abs - stats.dash_friction * 50.0 * dt).max(target_speed);
-                velocity.0.x = sign * new_abs;
+                let new_abs =
+                    (current_abs - (stats.dash_friction * 50).per_tick()).max(target_speed);
+                velocity.0.x = new_abs * sign;
             }
         }
 
         // Apply knockback decay to PushVelocity
-        if push_vel.0.length_squared() > 0.001 {
-            let decay = stats.friction * 50.0 * dt;
+        if push_vel.0 != FxVec2::ZERO {
+            let decay = (stats.friction * 50).per_tick();
             let current_len = push_vel.0.length();
             if current_len <= decay {
-                push_vel.0 = Vec2::ZERO;
+                push_vel.0 = FxVec2::ZERO;
             } else {
                 push_vel.0 *= (current_len - decay) / current_len;
             }
@@ -72,49 +84,82 @@ pub fn check_ground(
     mut landed_writer: MessageWriter<CharacterLandedMessage>,
     mut query: Query<(
         Entity,
-        &mut Transform,
+        &mut SimPosition,
         &mut Velocity,
         &mut MoveState,
         &MoveStats,
     )>,
 ) {
-    for (entity, mut transform, mut velocity, mut state, stats) in &mut query {
-        if transform.translation.y <= 0.0 {
-            transform.translation.y = 0.0;
-            if velocity.0.y < 0.0 {
-                velocity.0.y = 0.0;
+    for (entity, mut position, mut velocity, mut state, stats) in &mut query {
+        if position.0.y <= Fx::ZERO {
+            position.0.y = Fx::ZERO;
+            let fall_speed = -velocity.0.y.min(Fx::ZERO);
+            if velocity.0.y < Fx::ZERO {
+                velocity.0.y = Fx::ZERO;
             }
             if !state.grounded {
                 state.grounded = true;
                 state.mode = AirState::Grounded;
                 state.jumps_remaining = stats.max_jumps;
-                landed_writer.write(CharacterLandedMessage(entity));
+                landed_writer.write(CharacterLandedMessage { entity, fall_speed });
             }
         }
     }
 }
 
+/// Turns characters according to their `FacingMode`: towards their velocity in the
+/// sandbox, or towards their opponent in versus.
 pub fn update_facing(
     mut turned_writer: MessageWriter<CharacterTurnedMessage>,
-    mut query: Query<(Entity, &mut Facing, &Velocity, Option<&Locks>)>,
+    positions: Query<&SimPosition, With<CharacterLocomotion>>,
+    mut query: Query<(
+        Entity,
+        &CharacterLocomotion,
+        &SimPosition,
+        &mut Facing,
+        &Velocity,
+        &MoveState,
+        Option<&Opponent>,
+        Option<&Locks>,
+    )>,
 ) {
-    for (entity, mut facing, velocity, locks) in &mut query {
+    for (entity, locomotion, position, mut facing, velocity, state, opponent, locks) in &mut query {
         if locks.is_some_and(|l| l.turn_locked || l.hitstun_locked) {
             continue;
         }
 
-        if velocity.0.x > 0.1 && *facing != Facing::Right {
-            *facing = Facing::Right;
-            turned_writer.write(CharacterTurnedMessage {
-                entity,
-                facing: Facing::Right,
-            });
-        } else if velocity.0.x < -0.1 && *facing != Facing::Left {
-            *facing = Facing::Left;
+        let target = match locomotion.facing_mode {
+            FacingMode::Velocity => velocity_facing(velocity.0.x),
+            FacingMode::Opponent => {
+                // Side switches only happen on grounded neutral frames, never mid-air or mid-action
+                if !state.is_neutral() {
+                    continue;
+                }
+                let Some(opponent_position) = opponent.and_then(|o| positions.get(o.0).ok()) else {
+                    continue;
+                };
+                velocity_facing(opponent_position.0.x - position.0.x)
+            }
+        };
+
+        if let Some(target) = target
+            && *facing != target
+        {
+            *facing = target;
             turned_writer.write(CharacterTurnedMessage {
                 entity,
-                facing: Facing::Left,
+                facing: target,
             });
         }
     }
 }
+
+fn velocity_facing(dx: Fx) -> Option<Facing> {
+    if dx > FACING_DEADZONE {
+        Some(Facing::Right)
+    } else if dx < -FACING_DEADZONE {
+        Some(Facing::Left)
+    } else {
+        None
+    }
+}
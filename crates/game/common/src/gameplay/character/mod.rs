@@ -9,21 +9,26 @@ pub mod input;
 pub mod locomotion;
 pub mod presentation;
 
+pub use combat::{CharacterHitMessage, CombatState};
+pub use constitution::Vitals;
 pub use input::{CharacterInput, actions};
 pub use locomotion::CharacterLocomotion;
 
 #[derive(Component, Debug, Default)]
-#[require(CharacterLocomotion)]
+#[require(CharacterLocomotion, CombatState, Vitals)]
 pub struct Character;
 
+/// Simulates characters: input, movement, combat and animation. Input devices and
+/// drawing come separately from `InputDevicePlugin` and `PresentationPlugin`.
 pub struct CharacterPlugin;
 
 impl Plugin for CharacterPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            combat::CombatPlugin,
             input::InputPlugin,
             locomotion::LocomotionPlugin,
-            presentation::PresentationPlugin,
+            presentation::AnimationPlugin,
         ));
     }
 }
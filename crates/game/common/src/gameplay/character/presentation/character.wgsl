@@ -0,0 +1,126 @@
+// Character sprite frames, recoloured by the character's palette and crossfaded with
+// the frame shown before a clip change.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+const FLIP_X: u32 = 1u;
+const FLIP_Y: u32 = 2u;
+const PREVIOUS_FLIP_X: u32 = 4u;
+const PREVIOUS_FLIP_Y: u32 = 8u;
+
+const PALETTE_SWAP: u32 = 1u;
+const PALETTE_LOOKUP: u32 = 2u;
+
+const MAX_PALETTE_COLORS: u32 = 16u;
+// Largest distance between linear colours that still counts as the same sheet colour.
+const COLOR_TOLERANCE: f32 = 0.004;
+
+struct CharacterMaterialParams {
+    canvas: vec4<f32>,
+    uv_rect: vec4<f32>,
+    placement: vec4<f32>,
+    previous_uv_rect: vec4<f32>,
+    previous_placement: vec4<f32>,
+    flags: u32,
+    palette_mode: u32,
+    color_count: u32,
+    lookup_row: u32,
+    crossfade: f32,
+    sources: array<vec4<f32>, 16>,
+    replacements: array<vec4<f32>, 16>,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> params: CharacterMaterialParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var sheet_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var sheet_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var lookup_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(4) var lookup_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(5) var previous_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(6) var previous_sampler: sampler;
+
+// Sheet UV showing local pixel `position` of a frame drawn at `placement`.
+fn frame_uv(
+    position: vec2<f32>,
+    placement: vec4<f32>,
+    uv_rect: vec4<f32>,
+    flip_x: bool,
+    flip_y: bool,
+) -> vec2<f32> {
+    var local = (position - placement.xy) / max(placement.zw - placement.xy, vec2(1.0));
+    // Local pixels grow upwards, sheet UVs downwards.
+    local.y = 1.0 - local.y;
+    if flip_x {
+        local.x = 1.0 - local.x;
+    }
+    if flip_y {
+        local.y = 1.0 - local.y;
+    }
+    return mix(uv_rect.xy, uv_rect.zw, clamp(local, vec2(0.0), vec2(1.0)));
+}
+
+// 1.0 inside the frame drawn at `placement`, 0.0 around it.
+fn coverage(position: vec2<f32>, placement: vec4<f32>) -> f32 {
+    let inside = all(position >= placement.xy) && all(position <= placement.zw);
+    return select(0.0, 1.0, inside);
+}
+
+fn recolor(color: vec4<f32>) -> vec4<f32> {
+    if params.palette_mode == PALETTE_SWAP {
+        let count = min(params.color_count, MAX_PALETTE_COLORS);
+        for (var i = 0u; i < count; i++) {
+            if distance(color.rgb, params.sources[i].rgb) < COLOR_TOLERANCE {
+                return vec4(params.replacements[i].rgb, color.a);
+            }
+        }
+    } else if params.palette_mode == PALETTE_LOOKUP {
+        let width = textureDimensions(lookup_texture).x;
+        for (var i = 0u; i < width; i++) {
+            let source = textureLoad(lookup_texture, vec2(i, 0u), 0);
+            if distance(color.rgb, source.rgb) < COLOR_TOLERANCE {
+                let replacement = textureLoad(lookup_texture, vec2(i, params.lookup_row), 0);
+                return vec4(replacement.rgb, color.a);
+            }
+        }
+    }
+    return color;
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    // The quad's UVs run top to bottom over `canvas`.
+    let position = vec2(
+        mix(params.canvas.x, params.canvas.z, mesh.uv.x),
+        mix(params.canvas.w, params.canvas.y, mesh.uv.y),
+    );
+
+    let current_uv = frame_uv(
+        position,
+        params.placement,
+        params.uv_rect,
+        (params.flags & FLIP_X) != 0u,
+        (params.flags & FLIP_Y) != 0u,
+    );
+    var current = recolor(textureSample(sheet_texture, sheet_sampler, current_uv));
+    current.a *= coverage(position, params.placement);
+
+    // Sampled unconditionally: texture sampling has to stay in uniform control flow.
+    let previous_uv = frame_uv(
+        position,
+        params.previous_placement,
+        params.previous_uv_rect,
+        (params.flags & PREVIOUS_FLIP_X) != 0u,
+        (params.flags & PREVIOUS_FLIP_Y) != 0u,
+    );
+    var previous = recolor(textureSample(previous_texture, previous_sampler, previous_uv));
+    previous.a *= coverage(position, params.previous_placement) * params.crossfade;
+
+    // The fading frame is laid over the current one, so overlapping pixels crossfade
+    // and pixels of only the current frame show at once.
+    let current_weight = current.a * (1.0 - previous.a);
+    let alpha = previous.a + current_weight;
+    if alpha <= 0.0 {
+        discard;
+    }
+    let rgb = (previous.rgb * previous.a + current.rgb * current_weight) / alpha;
+    return vec4(rgb, alpha);
+}
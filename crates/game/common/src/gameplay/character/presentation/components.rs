@@ -1,8 +1,10 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
-use super::manifest::CharacterManifestAsset;
+use super::{manifest::CharacterManifestAsset, material::CharacterFrame};
 
 bitflags! {
     /// Bitflags representing character animation playback and sprite rendering state.
@@ -56,6 +58,48 @@ impl CharacterAnimationState {
     }
 }
 
+/// Which of the manifest's palettes the character is drawn with. `0` keeps the sprite
+/// sheet's own colours; `n` selects `CharacterManifestAsset::palettes[n - 1]`.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CharacterPalette(pub u8);
+
+/// Frame a character was last drawn with and, after a clip change under
+/// `InterpolationMode::ShaderCrossfade`, the frame it is fading out from.
+#[derive(Component, Debug, Clone, Default)]
+pub struct CharacterCrossfade {
+    pub clip_index: u16,
+    pub shown: Option<CharacterFrame>,
+    pub fading: Option<CharacterFrame>,
+    /// `Time<Fixed>::elapsed` when the fade began.
+    pub started: Duration,
+}
+
+/// Sound effect cue from a `FrameEvent::Sfx`.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct PlaySfxMessage {
+    pub entity: Entity,
+    pub sound: String,
+}
+
+/// Effect sprite cue from a `FrameEvent::Vfx`. `offset` is already mirrored to the
+/// character's facing; `flip_x` tells whether the image should be too.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct SpawnVfxMessage {
+    pub entity: Entity,
+    pub image: String,
+    pub offset: Vec2,
+    pub flip_x: bool,
+    pub lifetime_ticks: u16,
+}
+
+/// Camera shake cue from a `FrameEvent::CameraShake`.
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct CameraShakeMessage {
+    pub entity: Entity,
+    pub intensity: f32,
+    pub duration_ticks: u16,
+}
+
 /// Handle pointing to the loaded `CharacterManifestAsset`.
 #[derive(Component, Debug, Clone, Deref, DerefMut)]
 pub struct CharacterManifestHandle(pub Handle<CharacterManifestAsset>);
@@ -66,6 +110,8 @@ pub struct CharacterManifestHandle(pub Handle<CharacterManifestAsset>);
 pub struct AnimationVideoSettings {
     pub interpolation_mode: InterpolationMode,
     pub default_sprite_scale: f32,
+    /// Ticks over which `ShaderCrossfade` blends out the last frame of a clip.
+    pub crossfade_ticks: u16,
 }
 
 impl Default for AnimationVideoSettings {
@@ -73,6 +119,7 @@ impl Default for AnimationVideoSettings {
         Self {
             interpolation_mode: InterpolationMode::Discrete60Hz,
             default_sprite_scale: 2.0,
+            crossfade_ticks: 4,
         }
     }
 }
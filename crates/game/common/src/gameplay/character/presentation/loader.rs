@@ -4,7 +4,10 @@ use bevy::{
     prelude::*,
 };
 
-use super::manifest::{CharacterManifestAsset, LoadedSpriteSheet};
+use super::{
+    manifest::{CharacterManifestAsset, LoadedPalette, LoadedSpriteSheet, PaletteDef},
+    material::MAX_PALETTE_COLORS,
+};
 
 #[derive(Default, TypePath)]
 pub struct CharacterManifestLoader;
@@ -30,8 +33,7 @@ impl AssetLoader for CharacterManifestLoader {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
-        let mut manifest: CharacterManifestAsset = ron::de::from_bytes(&bytes)?;
-        manifest.build_lookup_cache();
+        let mut manifest = CharacterManifestAsset::from_ron(&bytes)?;
 
         let mut loaded_sheets = HashMap::default();
 
@@ -55,12 +57,41 @@ impl AssetLoader for CharacterManifestLoader {
                 LoadedSpriteSheet {
                     image_handle,
                     atlas_layout_handle: layout_handle,
+                    pivot: sheet_def.pivot,
                 },
             );
         }
 
         manifest.loaded_sheets = loaded_sheets;
 
+        for palette in &manifest.palettes {
+            let loaded = match palette {
+                PaletteDef::Swap(colors) => {
+                    if colors.len() > MAX_PALETTE_COLORS {
+                        anyhow::bail!(
+                            "palette swaps {} colours, at most {MAX_PALETTE_COLORS} are supported",
+                            colors.len()
+                        );
+                    }
+                    let parse = |hex: &str| {
+                        Srgba::hex(hex)
+                            .map(LinearRgba::from)
+                            .map_err(|err| anyhow::anyhow!("invalid palette colour {hex}: {err}"))
+                    };
+                    let colors = colors
+                        .iter()
+                        .map(|(source, replacement)| Ok((parse(source)?, parse(replacement)?)))
+                        .collect::<anyhow::Result<_>>()?;
+                    LoadedPalette::Swap(colors)
+                }
+                PaletteDef::Lookup { image, row } => LoadedPalette::Lookup {
+                    image: load_context.load(image),
+                    row: *row,
+                },
+            };
+            manifest.loaded_palettes.push(loaded);
+        }
+
         Ok(manifest)
     }
 
@@ -1,6 +1,8 @@
 use bevy::{platform::collections::HashMap, prelude::*};
 use serde::{Deserialize, Serialize};
 
+use crate::gameplay::replay::content_hash;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum LoopMode {
     #[default]
@@ -10,6 +12,17 @@ pub enum LoopMode {
     PingPong,
 }
 
+/// Which part of a move a frame belongs to, used to label the frame meter.
+/// Frame counts themselves are measured from simulated ticks, not read from here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FramePhase {
+    #[default]
+    Neutral,
+    Startup,
+    Active,
+    Recovery,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpriteSheetDef {
     pub image: String,
@@ -21,6 +34,10 @@ pub struct SpriteSheetDef {
     pub padding: Option<UVec2>,
     #[serde(default)]
     pub offset: Option<UVec2>,
+    /// Pixel of each frame, from its top-left corner, placed on the character's position
+    /// unless the frame sets its own. The frame centre when omitted.
+    #[serde(default)]
+    pub pivot: Option<Vec2>,
     // TODO: Add support for normal maps / emission maps for dynamic 2D lighting per sprite sheet
     // TODO: Add support for multi-resolution asset variants (SD vs HD texture paths)
 }
@@ -29,9 +46,80 @@ pub struct SpriteSheetDef {
 pub struct LoadedSpriteSheet {
     pub image_handle: Handle<Image>,
     pub atlas_layout_handle: Handle<TextureAtlasLayout>,
+    pub pivot: Option<Vec2>,
     // TODO: Store optional normal map & emission texture handles for dynamic 2D lighting
 }
 
+/// Alternate colours for a character, shown by `CharacterPalette` index `n` for the
+/// manifest's `n - 1`th palette. Sprite sheets stay as they are; the colours are
+/// replaced while rendering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PaletteDef {
+    /// `(source, replacement)` pairs of `#rrggbb` colours, at most
+    /// `MAX_PALETTE_COLORS` of them.
+    Swap(Vec<(String, String)>),
+    /// Lookup texture whose first row lists the source colours and whose row `row`
+    /// holds their replacements, column by column.
+    Lookup { image: String, row: u32 },
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadedPalette {
+    Swap(Vec<(LinearRgba, LinearRgba)>),
+    Lookup { image: Handle<Image>, row: u32 },
+}
+
+/// Moment a character can say one of its `voice_lines` at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoiceCue {
+    /// The match begins.
+    Intro,
+    Jump,
+    Dash,
+    /// The character takes an unguarded hit.
+    Hurt,
+}
+
+/// Presentation cue attached to an animation frame. Fired as a message when the frame
+/// starts playing, see `PlaySfxMessage`, `SpawnVfxMessage` and `CameraShakeMessage`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FrameEvent {
+    /// Sound effect, by asset path.
+    Sfx(String),
+    /// Short-lived effect sprite, by image path, drawn `offset` pixels from the
+    /// character's position and mirrored with it.
+    Vfx {
+        image: String,
+        #[serde(default)]
+        offset: Vec2,
+        lifetime_ticks: u16,
+    },
+    /// Camera shake of up to `intensity` pixels, fading out over `duration_ticks`.
+    CameraShake { intensity: f32, duration_ticks: u16 },
+}
+
+/// Axis-aligned box in world units, centred `offset` from the character's position.
+/// Offsets are given for a right-facing character and mirrored with it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameBox {
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+/// Area an attack frame strikes, and what connecting with it does to the defender.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hitbox {
+    pub area: FrameBox,
+    pub damage: u16,
+    /// Ticks the defender can't act after an unguarded hit.
+    pub hitstun_ticks: u16,
+    /// Ticks a guarding defender is held in guard.
+    pub blockstun_ticks: u16,
+    /// Push away from the attacker in units per second, halved on block.
+    #[serde(default)]
+    pub knockback: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationFrame {
     /// TextureAtlas index for this frame
@@ -40,20 +128,27 @@ pub struct AnimationFrame {
     /// Duration of this frame step in fixed 60Hz ticks (e.g. 3 ticks = ~0.05s)
     pub duration_ticks: u16,
 
-    /// Anchor/pivot offset (normalized or pixel coordinates)
+    /// Pixel of the frame, from its top-left corner, placed on the character's position.
+    /// Overrides the sheet's `pivot`; mirrored along with the frame when flipped.
     pub pivot: Option<Vec2>,
-    // TODO: Hitboxes & Hurtboxes
-    // - Add per-frame combat hurtbox shapes (AABB / capsule colliders for taking damage)
-    // - Add per-frame combat hitbox shapes (active hit volumes with damage, hitstun, knockback vector)
+
+    /// Move phase this frame belongs to (startup / active / recovery)
+    #[serde(default)]
+    pub phase: FramePhase,
+
+    /// Cues fired on the first tick the frame plays
+    #[serde(default)]
+    pub events: Vec<FrameEvent>,
+
+    /// Area this frame strikes while its attack hasn't connected yet
+    #[serde(default)]
+    pub hitbox: Option<Hitbox>,
+    // TODO: Per-frame hurtboxes
+    // - Override the manifest's `hurtbox` on frames that lean in, duck or stretch out
 
     // TODO: Cancel Windows & Action Triggers
     // - Add `cancel_window`: Option<CancelWindowDef> allowing action cancels into jump, dash, or special moves on specific ticks
     // - Add `invulnerability_type`: Option<InvulnerabilityType> (e.g. Full, Strike, Grab, Upper-body)
-
-    // TODO: Audio & Visual Feedback Triggers
-    // - Add `sfx_event`: Option<String> / `sfx_handle` to play step/swing sound effects on keyframe ticks
-    // - Add `vfx_spawns`: Vec<VfxSpawnDef> (e.g. dust particles on footstep or impact flash on swing)
-    // - Add `camera_shake`: Option<CameraShakeImpulse> to trigger camera impact rumble on heavy landing/impact frames
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +172,13 @@ pub struct CharacterManifestAsset {
     #[serde(default)]
     pub sheets: HashMap<String, SpriteSheetDef>,
     pub clips: Vec<CharacterAnimationClip>,
+    /// Area of the body attacks can hit
+    pub hurtbox: FrameBox,
+    #[serde(default)]
+    pub palettes: Vec<PaletteDef>,
+    /// Audio paths of the lines the character says at each cue
+    #[serde(default)]
+    pub voice_lines: HashMap<VoiceCue, String>,
 
     /// Lookup cache mapping string clip names ("idle", "walk", "jump_up") to u16 clip indices
     #[serde(skip)]
@@ -84,6 +186,12 @@ pub struct CharacterManifestAsset {
     /// Map of dynamically resolved loaded sprite sheet handles keyed by sheet identifier
     #[serde(skip)]
     pub loaded_sheets: HashMap<String, LoadedSpriteSheet>,
+    /// `palettes`, resolved to linear colours and texture handles
+    #[serde(skip)]
+    pub loaded_palettes: Vec<LoadedPalette>,
+    /// Hash of the manifest file bytes, recorded in replays to detect edited fighters
+    #[serde(skip)]
+    pub content_hash: u64,
     // TODO: Asset Loading & Hot-Reloading Improvements
     // - Support binary RON or bincode format serialization for optimized production builds
     // - Support hot-reloading cache invalidation when sprite sheets or manifests are modified on disk
@@ -91,6 +199,15 @@ pub struct CharacterManifestAsset {
 }
 
 impl CharacterManifestAsset {
+    /// Parses a manifest file, without the sprite sheets and palettes the loader
+    /// resolves. Everything the simulation reads is there.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, ron::de::SpannedError> {
+        let mut manifest: Self = ron::de::from_bytes(bytes)?;
+        manifest.build_lookup_cache();
+        manifest.content_hash = content_hash(bytes);
+        Ok(manifest)
+    }
+
     pub fn build_lookup_cache(&mut self) {
         self.clip_name_to_index.clear();
         for (idx, clip) in self.clips.iter().enumerate() {
@@ -103,4 +220,15 @@ impl CharacterManifestAsset {
     pub fn get_clip_index(&self, name: &str) -> Option<u16> {
         self.clip_name_to_index.get(name).copied()
     }
+
+    /// Audio path of the line said at `cue`, if the character has one.
+    pub fn voice_line(&self, cue: VoiceCue) -> Option<&str> {
+        self.voice_lines.get(&cue).map(String::as_str)
+    }
+
+    /// Palette shown for `CharacterPalette` index `index`; `None` for the original
+    /// colours, including indices past the last palette.
+    pub fn palette(&self, index: u8) -> Option<&LoadedPalette> {
+        self.loaded_palettes.get(usize::from(index).checked_sub(1)?)
+    }
 }
@@ -0,0 +1,203 @@
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+    shader::ShaderRef,
+    sprite_render::{AlphaMode2d, Material2d},
+};
+
+use super::manifest::LoadedPalette;
+
+/// Most colours one `PaletteDef::Swap` may replace.
+pub const MAX_PALETTE_COLORS: usize = 16;
+
+pub(super) const SHADER_PATH: &str =
+    "embedded://game_common/gameplay/character/presentation/character.wgsl";
+
+const FLIP_X: u32 = 1 << 0;
+const FLIP_Y: u32 = 1 << 1;
+const PREVIOUS_FLIP_X: u32 = 1 << 2;
+const PREVIOUS_FLIP_Y: u32 = 1 << 3;
+
+const PALETTE_NONE: u32 = 0;
+const PALETTE_SWAP: u32 = 1;
+const PALETTE_LOOKUP: u32 = 2;
+
+/// One sprite frame as drawn on a character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterFrame {
+    pub sheet: Handle<Image>,
+    /// Pixels of the frame within `sheet`.
+    pub rect: URect,
+    pub sheet_size: UVec2,
+    /// Where the frame is drawn, in the character's local pixels (y up).
+    pub placement: IRect,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl CharacterFrame {
+    fn uv_rect(&self) -> Vec4 {
+        let size = self.sheet_size.as_vec2().max(Vec2::ONE);
+        let min = self.rect.min.as_vec2() / size;
+        let max = self.rect.max.as_vec2() / size;
+        Vec4::new(min.x, min.y, max.x, max.y)
+    }
+
+    fn flags(&self, flip_x: u32, flip_y: u32) -> u32 {
+        let mut flags = 0;
+        if self.flip_x {
+            flags |= flip_x;
+        }
+        if self.flip_y {
+            flags |= flip_y;
+        }
+        flags
+    }
+}
+
+fn placement_vec(placement: IRect) -> Vec4 {
+    let placement = placement.as_rect();
+    Vec4::new(
+        placement.min.x,
+        placement.min.y,
+        placement.max.x,
+        placement.max.y,
+    )
+}
+
+/// Uniforms of `CharacterMaterial`; mirrors `CharacterMaterialParams` in
+/// `character.wgsl`.
+#[derive(ShaderType, Debug, Clone, PartialEq, Default)]
+pub struct CharacterMaterialParams {
+    /// Area covered by the quad, in the character's local pixels.
+    pub canvas: Vec4,
+    /// Current frame within the sheet, as UV `(min.x, min.y, max.x, max.y)`.
+    pub uv_rect: Vec4,
+    /// Where the current frame is drawn within `canvas`.
+    pub placement: Vec4,
+    pub previous_uv_rect: Vec4,
+    pub previous_placement: Vec4,
+    pub flags: u32,
+    pub palette_mode: u32,
+    pub color_count: u32,
+    pub lookup_row: u32,
+    /// Weight of the previous frame, `0.0` once the crossfade is over.
+    pub crossfade: f32,
+    pub sources: [Vec4; MAX_PALETTE_COLORS],
+    pub replacements: [Vec4; MAX_PALETTE_COLORS],
+}
+
+/// Draws one character frame from its sprite sheet, recoloured by the character's
+/// palette and optionally blended with the frame shown before a clip change. Each
+/// character owns one instance.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, PartialEq, Default)]
+pub struct CharacterMaterial {
+    #[uniform(0)]
+    pub params: CharacterMaterialParams,
+    #[texture(1)]
+    #[sampler(2)]
+    pub sheet: Handle<Image>,
+    /// Texture of a `PaletteDef::Lookup` palette.
+    #[texture(3)]
+    #[sampler(4)]
+    pub lookup: Option<Handle<Image>>,
+    /// Sheet of the frame being faded out.
+    #[texture(5)]
+    #[sampler(6)]
+    pub previous_sheet: Option<Handle<Image>>,
+}
+
+impl CharacterMaterial {
+    /// Shows `frame`, faded with `previous` at the given weight of the previous frame.
+    /// Returns the area the quad has to cover.
+    pub fn set_frames(
+        &mut self,
+        frame: &CharacterFrame,
+        previous: Option<(&CharacterFrame, f32)>,
+    ) -> IRect {
+        let params = &mut self.params;
+        self.sheet = frame.sheet.clone();
+        params.uv_rect = frame.uv_rect();
+        params.placement = placement_vec(frame.placement);
+        params.flags = frame.flags(FLIP_X, FLIP_Y);
+
+        let mut canvas = frame.placement;
+        match previous {
+            Some((previous, weight)) if weight > 0.0 => {
+                self.previous_sheet = Some(previous.sheet.clone());
+                params.previous_uv_rect = previous.uv_rect();
+                params.previous_placement = placement_vec(previous.placement);
+                params.flags |= previous.flags(PREVIOUS_FLIP_X, PREVIOUS_FLIP_Y);
+                params.crossfade = weight.min(1.0);
+                canvas = canvas.union(previous.placement);
+            }
+            _ => {
+                self.previous_sheet = None;
+                params.previous_uv_rect = Vec4::ZERO;
+                params.previous_placement = Vec4::ZERO;
+                params.crossfade = 0.0;
+            }
+        }
+
+        params.canvas = placement_vec(canvas);
+        canvas
+    }
+
+    /// Recolours with `palette`, or shows the sheet's own colours for `None`.
+    pub fn set_palette(&mut self, palette: Option<&LoadedPalette>) {
+        let params = &mut self.params;
+        params.sources = Default::default();
+        params.replacements = Default::default();
+        params.color_count = 0;
+        params.lookup_row = 0;
+        self.lookup = None;
+
+        match palette {
+            None => params.palette_mode = PALETTE_NONE,
+            Some(LoadedPalette::Swap(colors)) => {
+                params.palette_mode = PALETTE_SWAP;
+                params.color_count = colors.len().min(MAX_PALETTE_COLORS) as u32;
+                for (i, (source, replacement)) in colors.iter().take(MAX_PALETTE_COLORS).enumerate()
+                {
+                    params.sources[i] = source.to_vec4();
+                    params.replacements[i] = replacement.to_vec4();
+                }
+            }
+            Some(LoadedPalette::Lookup { image, row }) => {
+                params.palette_mode = PALETTE_LOOKUP;
+                params.lookup_row = *row;
+                self.lookup = Some(image.clone());
+            }
+        }
+    }
+}
+
+impl Material2d for CharacterMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Quads covering an area of a character's local pixels, shared by every character
+/// drawing over the same area.
+#[derive(Resource, Debug, Default)]
+pub struct CharacterQuads(HashMap<IRect, Handle<Mesh>>);
+
+impl CharacterQuads {
+    pub fn get(&mut self, area: IRect, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.0
+            .entry(area)
+            .or_insert_with(|| {
+                let area = area.as_rect();
+                let quad = Mesh::from(Rectangle::from_size(area.size()))
+                    .translated_by(area.center().extend(0.0));
+                meshes.add(quad)
+            })
+            .clone()
+    }
+}
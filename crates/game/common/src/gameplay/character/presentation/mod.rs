@@ -1,30 +1,43 @@
-use bevy::{asset::AssetApp, prelude::*};
+use bevy::{
+    asset::{AssetApp, embedded_asset},
+    prelude::*,
+    sprite_render::Material2dPlugin,
+};
 
 pub mod components;
 pub mod loader;
 pub mod manifest;
+pub mod material;
+pub mod roster;
 pub mod systems;
 
 pub use components::{
-    AnimationPlaybackFlags, AnimationVideoSettings, CharacterAnimationState,
-    CharacterManifestHandle, InterpolationMode,
+    AnimationPlaybackFlags, AnimationVideoSettings, CameraShakeMessage, CharacterAnimationState,
+    CharacterCrossfade, CharacterManifestHandle, CharacterPalette, InterpolationMode,
+    PlaySfxMessage, SpawnVfxMessage,
 };
 pub use loader::CharacterManifestLoader;
 pub use manifest::{
-    AnimationFrame, CharacterAnimationClip, CharacterManifestAsset, LoadedSpriteSheet, LoopMode,
-    SpriteSheetDef,
+    AnimationFrame, CharacterAnimationClip, CharacterManifestAsset, FrameBox, FrameEvent,
+    FramePhase, Hitbox, LoadedPalette, LoadedSpriteSheet, LoopMode, PaletteDef, SpriteSheetDef,
+    VoiceCue,
 };
+pub use material::{CharacterFrame, CharacterMaterial, CharacterQuads, MAX_PALETTE_COLORS};
+pub use roster::{CharacterRosterAsset, CharacterRosterLoader, RosterCharacter};
 pub use systems::{
-    advance_character_animations, update_character_animation_state, update_character_sprites,
+    advance_character_animations, attach_character_materials, sync_character_transforms,
+    update_character_animation_state, update_character_sprites,
 };
 
-pub struct PresentationPlugin;
+/// Plays character animations on the fixed tick. Hitboxes and move phases come from the
+/// clip frames, so this is part of the simulation rather than of rendering.
+pub struct AnimationPlugin;
 
-impl Plugin for PresentationPlugin {
+impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.init_asset::<CharacterManifestAsset>()
-            .init_asset_loader::<CharacterManifestLoader>()
-            .init_resource::<AnimationVideoSettings>()
+        app.add_message::<PlaySfxMessage>()
+            .add_message::<SpawnVfxMessage>()
+            .add_message::<CameraShakeMessage>()
             .add_systems(
                 FixedUpdate,
                 (
@@ -33,6 +46,28 @@ impl Plugin for PresentationPlugin {
                 )
                     .chain(),
             )
-            .add_systems(PostUpdate, update_character_sprites);
+            .add_systems(FixedPostUpdate, sync_character_transforms);
+    }
+}
+
+/// Loads character manifests and rosters through the asset server and draws the
+/// characters.
+pub struct PresentationPlugin;
+
+impl Plugin for PresentationPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "character.wgsl");
+
+        app.add_plugins(Material2dPlugin::<CharacterMaterial>::default())
+            .init_asset::<CharacterManifestAsset>()
+            .init_asset_loader::<CharacterManifestLoader>()
+            .init_asset::<CharacterRosterAsset>()
+            .init_asset_loader::<CharacterRosterLoader>()
+            .init_resource::<AnimationVideoSettings>()
+            .init_resource::<CharacterQuads>()
+            .add_systems(
+                PostUpdate,
+                (attach_character_materials, update_character_sprites).chain(),
+            );
     }
 }
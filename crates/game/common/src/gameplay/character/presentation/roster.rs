@@ -0,0 +1,130 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::manifest::CharacterManifestAsset;
+
+/// Roster file as written on disk (`*.roster.ron`).
+#[derive(Debug, Deserialize)]
+struct RosterDef {
+    characters: Vec<RosterEntryDef>,
+}
+
+impl RosterDef {
+    fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        let roster: Self = ron::de::from_bytes(bytes)?;
+        if roster.characters.is_empty() {
+            anyhow::bail!("character roster lists no characters");
+        }
+        Ok(roster)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RosterEntryDef {
+    /// Path of the character's manifest.
+    manifest: String,
+    /// Image shown on the character select screen.
+    portrait: String,
+    /// Part of `portrait` to show as `(x, y, width, height)` in pixels, e.g. one frame
+    /// of a sprite sheet. The whole image when omitted.
+    #[serde(default)]
+    portrait_rect: Option<(u32, u32, u32, u32)>,
+}
+
+/// A selectable fighter. Its display name is the manifest's `name`.
+#[derive(Debug, Clone)]
+pub struct RosterCharacter {
+    pub manifest: Handle<CharacterManifestAsset>,
+    pub portrait: Handle<Image>,
+    pub portrait_rect: Option<Rect>,
+}
+
+/// Every fighter that can be picked. Listed manifests load as dependencies of the
+/// roster, so a new fighter only needs a manifest and a roster entry.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct CharacterRosterAsset {
+    characters: Vec<RosterCharacter>,
+}
+
+impl CharacterRosterAsset {
+    /// Never zero; the loader rejects empty rosters.
+    pub fn len(&self) -> usize {
+        self.characters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RosterCharacter> {
+        self.characters.iter()
+    }
+
+    /// The character at `index`, wrapping around the roster.
+    pub fn get(&self, index: usize) -> &RosterCharacter {
+        &self.characters[index % self.characters.len()]
+    }
+
+    /// Paths of the manifests a roster file lists, relative to the asset root, for
+    /// reading the fighters without an asset server.
+    pub fn manifest_paths(bytes: &[u8]) -> anyhow::Result<Vec<String>> {
+        Ok(RosterDef::parse(bytes)?
+            .characters
+            .into_iter()
+            .map(|entry| entry.manifest)
+            .collect())
+    }
+
+    /// The character whose manifest has the given `CharacterManifestAsset::id`.
+    pub fn find<'a>(
+        &'a self,
+        manifests: &Assets<CharacterManifestAsset>,
+        manifest_id: &str,
+    ) -> Option<&'a RosterCharacter> {
+        self.characters.iter().find(|character| {
+            manifests
+                .get(&character.manifest)
+                .is_some_and(|manifest| manifest.id == manifest_id)
+        })
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct CharacterRosterLoader;
+
+impl AssetLoader for CharacterRosterLoader {
+    type Asset = CharacterRosterAsset;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let characters = RosterDef::parse(&bytes)?
+            .characters
+            .into_iter()
+            .map(|entry| RosterCharacter {
+                manifest: load_context.load(&entry.manifest),
+                portrait: load_context.load(&entry.portrait),
+                portrait_rect: entry.portrait_rect.map(|(x, y, width, height)| {
+                    Rect::new(x as f32, y as f32, (x + width) as f32, (y + height) as f32)
+                }),
+            })
+            .collect();
+
+        Ok(CharacterRosterAsset { characters })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["roster.ron"]
+    }
+}
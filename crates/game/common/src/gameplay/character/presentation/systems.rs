@@ -1,12 +1,23 @@
 use bevy::ecs::message::MessageReader;
 use bevy::prelude::*;
-use bevy_enhanced_input::action::{Action, relationship::Actions};
 
-use super::{components::*, manifest::LoopMode};
-use crate::gameplay::character::{
-    Character,
-    input::{CharacterInput, actions::Move},
-    locomotion::components::{AirState, CharacterLandedMessage, Facing, MoveState, Velocity},
+use super::{
+    components::*,
+    manifest::{FrameEvent, LoopMode},
+    material::{CharacterFrame, CharacterMaterial, CharacterQuads},
+};
+use crate::gameplay::{
+    character::{
+        Character, CharacterLocomotion,
+        combat::CombatState,
+        input::InputHistory,
+        locomotion::components::{
+            AirState, CharacterLandedMessage, CharacterTurnedMessage, Facing, FacingMode,
+            MoveState, SimPosition, Velocity,
+        },
+    },
+    fixed::Fx,
+    replay::ReplayPlayback,
 };
 
 fn try_play_clip(
@@ -26,28 +37,49 @@ fn try_play_clip(
     false
 }
 
+/// Presentation boundary: copies each character's simulated position into its `Transform`.
+pub fn sync_character_transforms(mut query: Query<(&SimPosition, &mut Transform)>) {
+    for (position, mut transform) in &mut query {
+        let translation = position.0.to_vec2();
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
+
 /// Synchronizes character locomotion (Velocity, Grounded state, Facing, Landed Message) to presentation animation state.
 pub fn update_character_animation_state(
     manifest_assets: Res<Assets<super::manifest::CharacterManifestAsset>>,
-    movements: Query<&Action<Move>>,
     mut landed_messages: MessageReader<CharacterLandedMessage>,
+    mut turned_messages: MessageReader<CharacterTurnedMessage>,
     mut query: Query<
         (
             Entity,
             &CharacterManifestHandle,
+            &CharacterLocomotion,
             &Facing,
             &mut MoveState,
             &Velocity,
-            &Actions<CharacterInput>,
+            &InputHistory,
+            &mut CombatState,
             &mut CharacterAnimationState,
         ),
         With<Character>,
     >,
 ) {
-    let landed_entities: Vec<Entity> = landed_messages.read().map(|msg| msg.0).collect();
-
-    for (entity, manifest_handle, facing, mut move_state, velocity, actions, mut anim_state) in
-        &mut query
+    let landed_entities: Vec<Entity> = landed_messages.read().map(|msg| msg.entity).collect();
+    let turned_entities: Vec<Entity> = turned_messages.read().map(|msg| msg.entity).collect();
+
+    for (
+        entity,
+        manifest_handle,
+        locomotion,
+        facing,
+        mut move_state,
+        velocity,
+        input_history,
+        mut combat,
+        mut anim_state,
+    ) in &mut query
     {
         let Some(manifest) = manifest_assets.get(&manifest_handle.0) else {
             continue;
@@ -70,25 +102,48 @@ pub fn update_character_animation_state(
             .get(anim_state.clip_index as usize)
             .is_some_and(|c| c.name == "defend");
 
-        // Check if movement key is actively held by checking movement action input value
-        let has_movement_input = movements
-            .iter_many(actions)
-            .next()
-            .is_some_and(|m| m.abs() > 0.05);
+        let is_playing_turn = manifest
+            .clips
+            .get(anim_state.clip_index as usize)
+            .is_some_and(|c| c.name == "turn");
+
+        let is_playing_attack = manifest
+            .clips
+            .get(anim_state.clip_index as usize)
+            .is_some_and(|c| c.name == "attack");
+
+        // Check if movement key is actively held on this tick's input frame
+        let has_movement_input = input_history.latest().horizontal_sign() != 0;
 
         // Check if CharacterLandedMessage was sent for this entity during touchdown
         let just_landed_message = landed_entities.contains(&entity);
 
+        // Side switches against an opponent play the turn-around clip (velocity turns do not)
+        let just_turned_message =
+            locomotion.facing_mode == FacingMode::Opponent && turned_entities.contains(&entity);
+
         // TODO: Action Cancel Windows & Combo Chaining
         // - Check if current frame falls within `current_frame.cancel_window` to allow interrupting current animation with attack, jump, or dash inputs.
 
-        // TODO: Camera Shake & Landing Feedback
-        // - On `just_landed_message`, compute impact fall velocity and trigger `commands.trigger(CameraShakeEvent { intensity, duration })` for heavy landings.
+        // 2. Hitstun and attacks override locomotion clips
+        if combat.hitstun_ticks > 0 {
+            try_play_clip(manifest, &mut anim_state, "hurt");
+            continue;
+        }
 
-        // TODO: Audio Triggers
-        // - Emit `PlaySfxMessage` / audio commands on landing touchdown or state transitions.
+        if combat.attacking {
+            if is_playing_attack && !anim_state.is_finished() {
+                continue;
+            }
+            if !is_playing_attack && try_play_clip(manifest, &mut anim_state, "attack") {
+                move_state.guard_releasing = false;
+                continue;
+            }
+            // The attack clip ran out (or the character has none); back to locomotion
+            combat.attacking = false;
+        }
 
-        // 2. State machine transitions
+        // 3. State machine transitions
         if move_state.grounded {
             // Defend / Guard animation handling
             if move_state.defending {
@@ -118,8 +173,16 @@ pub fn update_character_animation_state(
                 continue;
             }
 
+            if just_turned_message && try_play_clip(manifest, &mut anim_state, "turn") {
+                continue;
+            }
+
+            if is_playing_turn && !anim_state.is_finished() {
+                continue;
+            }
+
             // Walk vs Idle clip transition
-            if has_movement_input && velocity.0.x.abs() > 10.0 {
+            if has_movement_input && velocity.0.x.abs() > Fx::from_int(10) {
                 try_play_clip(manifest, &mut anim_state, "walk");
             } else {
                 try_play_clip(manifest, &mut anim_state, "idle");
@@ -131,7 +194,7 @@ pub fn update_character_animation_state(
                 continue;
             }
 
-            if velocity.0.y > 0.0 || move_state.mode == AirState::Rising {
+            if velocity.0.y > Fx::ZERO || move_state.mode == AirState::Rising {
                 try_play_clip(manifest, &mut anim_state, "jump_up");
             } else {
                 try_play_clip(manifest, &mut anim_state, "jump_down");
@@ -141,11 +204,26 @@ pub fn update_character_animation_state(
 }
 
 /// Advances character animation frames in FixedUpdate (60Hz timestep).
+/// Fires each frame's `FrameEvent`s on the first tick it plays, except while a replay
+/// seeks.
 pub fn advance_character_animations(
     manifest_assets: Res<Assets<super::manifest::CharacterManifestAsset>>,
-    mut query: Query<(&CharacterManifestHandle, &mut CharacterAnimationState), With<Character>>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut sfx_writer: MessageWriter<PlaySfxMessage>,
+    mut vfx_writer: MessageWriter<SpawnVfxMessage>,
+    mut shake_writer: MessageWriter<CameraShakeMessage>,
+    mut query: Query<
+        (
+            Entity,
+            &CharacterManifestHandle,
+            &mut CharacterAnimationState,
+        ),
+        With<Character>,
+    >,
 ) {
-    for (manifest_handle, mut state) in &mut query {
+    let fire_events = playback.is_none_or(|playback| !playback.is_seeking());
+
+    for (entity, manifest_handle, mut state) in &mut query {
         // 1. Handle hitstop / freeze-frame impact pauses
         if state.hitstop_ticks > 0 {
             state.hitstop_ticks -= 1;
@@ -175,12 +253,55 @@ pub fn advance_character_animations(
             continue;
         }
 
-        // 4. Increment tick counter for current frame
-        state.elapsed_ticks += 1;
-
         let current_frame = &clip.frames[state.frame_index as usize];
 
-        // 5. Advance frame if elapsed_ticks reaches target duration_ticks
+        // 4. Fire the frame's presentation cues on its first tick
+        if fire_events && state.elapsed_ticks == 0 {
+            let flip_x = state.flags.contains(AnimationPlaybackFlags::FLIP_X);
+            let mirror = if flip_x {
+                Vec2::new(-1.0, 1.0)
+            } else {
+                Vec2::ONE
+            };
+            for event in &current_frame.events {
+                match event {
+                    FrameEvent::Sfx(sound) => {
+                        sfx_writer.write(PlaySfxMessage {
+                            entity,
+                            sound: sound.clone(),
+                        });
+                    }
+                    FrameEvent::Vfx {
+                        image,
+                        offset,
+                        lifetime_ticks,
+                    } => {
+                        vfx_writer.write(SpawnVfxMessage {
+                            entity,
+                            image: image.clone(),
+                            offset: *offset * mirror,
+                            flip_x,
+                            lifetime_ticks: *lifetime_ticks,
+                        });
+                    }
+                    FrameEvent::CameraShake {
+                        intensity,
+                        duration_ticks,
+                    } => {
+                        shake_writer.write(CameraShakeMessage {
+                            entity,
+                            intensity: *intensity,
+                            duration_ticks: *duration_ticks,
+                        });
+                    }
+                }
+            }
+        }
+
+        // 5. Increment tick counter for current frame
+        state.elapsed_ticks += 1;
+
+        // 6. Advance frame if elapsed_ticks reaches target duration_ticks
         if state.elapsed_ticks >= current_frame.duration_ticks {
             state.elapsed_ticks = 0;
 
@@ -209,34 +330,76 @@ pub fn advance_character_animations(
                 }
             }
 
-            // TODO: Per-Frame Combat Hitboxes & Hurtboxes
-            // - On frame step change, despawn active hitboxes from previous frame and spawn new `Hitbox` colliders for `clip.frames[frame_index]`.
-            // - Update `Hurtbox` shapes and attachment offsets.
-
-            // TODO: Frame Audio & Visual Effects (VFX)
-            // - Trigger footstep dust particle emitters (`VfxSpawnDef`).
-            // - Trigger swing/step sound effects defined on keyframe data (`current_frame.sfx_event`).
-
             // TODO: Root Motion Support
             // - Extract frame displacement delta and apply directly to character `Velocity` or `Transform`.
         }
     }
 }
 
-/// Updates character sprite atlas indices, texture sheets, and applies visual settings in PostUpdate.
-pub fn update_character_sprites(
-    manifest_assets: Res<Assets<super::manifest::CharacterManifestAsset>>,
-    video_settings: Res<AnimationVideoSettings>,
-    mut query: Query<
+/// Gives characters the quad and `CharacterMaterial` they are drawn with.
+pub fn attach_character_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<CharacterMaterial>>,
+    query: Query<
+        Entity,
         (
-            &CharacterManifestHandle,
-            &CharacterAnimationState,
-            &mut Sprite,
+            With<CharacterManifestHandle>,
+            Without<MeshMaterial2d<CharacterMaterial>>,
         ),
-        With<Character>,
     >,
 ) {
-    for (manifest_handle, anim_state, mut sprite) in &mut query {
+    for entity in &query {
+        commands.entity(entity).insert((
+            Mesh2d::default(),
+            MeshMaterial2d(materials.add(CharacterMaterial::default())),
+            CharacterCrossfade::default(),
+        ));
+    }
+}
+
+/// Where a frame of `rect`'s size is drawn relative to the character's origin, which
+/// `pivot` (pixels from the frame's top-left corner) is placed on. Flipping mirrors the
+/// frame around the pivot, so feet stay put when a character turns around.
+fn frame_placement(rect: URect, pivot: Option<Vec2>, flip_x: bool, flip_y: bool) -> IRect {
+    let size = rect.size().as_ivec2();
+    let pivot = pivot.map_or(size / 2, |pivot| pivot.round().as_ivec2());
+
+    // Local pixels grow upwards, frame pixels downwards.
+    let (left, right) = (pivot.x, size.x - pivot.x);
+    let (above, below) = (pivot.y, size.y - pivot.y);
+    let (left, right) = if flip_x { (right, left) } else { (left, right) };
+    let (above, below) = if flip_y {
+        (below, above)
+    } else {
+        (above, below)
+    };
+    IRect::new(-left, -below, right, above)
+}
+
+/// Points each character's material at its current sprite frame and palette and sizes its
+/// quad to the frame, in PostUpdate. Under `ShaderCrossfade` the last frame of the previous
+/// clip fades out over `AnimationVideoSettings::crossfade_ticks`.
+#[allow(clippy::too_many_arguments)]
+pub fn update_character_sprites(
+    manifest_assets: Res<Assets<super::manifest::CharacterManifestAsset>>,
+    atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+    fixed_time: Res<Time<Fixed>>,
+    video_settings: Res<AnimationVideoSettings>,
+    mut quads: ResMut<CharacterQuads>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CharacterMaterial>>,
+    mut query: Query<(
+        &CharacterManifestHandle,
+        &CharacterAnimationState,
+        Option<&CharacterPalette>,
+        &MeshMaterial2d<CharacterMaterial>,
+        &mut Mesh2d,
+        &mut CharacterCrossfade,
+    )>,
+) {
+    for (manifest_handle, anim_state, palette, material_handle, mut mesh, mut crossfade) in
+        &mut query
+    {
         let Some(manifest) = manifest_assets.get(&manifest_handle.0) else {
             continue;
         };
@@ -252,28 +415,84 @@ pub fn update_character_sprites(
         let Some(sheet) = loaded_sheet else {
             continue;
         };
+        let Some(layout) = atlas_layouts.get(&sheet.atlas_layout_handle) else {
+            continue;
+        };
+        let Some(animation_frame) = clip.frames.get(anim_state.frame_index as usize) else {
+            continue;
+        };
+        let Some(&rect) = layout.textures.get(animation_frame.sprite_index) else {
+            continue;
+        };
+        let Some(current) = materials.get(&material_handle.0) else {
+            continue;
+        };
 
-        if let Some(frame) = clip.frames.get(anim_state.frame_index as usize) {
-            sprite.image = sheet.image_handle.clone();
-            sprite.texture_atlas = Some(TextureAtlas {
-                layout: sheet.atlas_layout_handle.clone(),
-                index: frame.sprite_index,
-            });
-
-            sprite.flip_x = anim_state.flags.contains(AnimationPlaybackFlags::FLIP_X);
-            sprite.flip_y = anim_state.flags.contains(AnimationPlaybackFlags::FLIP_Y);
-        }
+        let flip_x = anim_state.flags.contains(AnimationPlaybackFlags::FLIP_X);
+        let flip_y = anim_state.flags.contains(AnimationPlaybackFlags::FLIP_Y);
+        let pivot = animation_frame.pivot.or(sheet.pivot);
+        let frame = CharacterFrame {
+            sheet: sheet.image_handle.clone(),
+            rect,
+            sheet_size: layout.size,
+            placement: frame_placement(rect, pivot, flip_x, flip_y),
+            flip_x,
+            flip_y,
+        };
 
-        match video_settings.interpolation_mode {
+        let fade_weight = match video_settings.interpolation_mode {
             InterpolationMode::Discrete60Hz => {
                 // Strict 60Hz rendering - no extra visual smoothing applied
+                0.0
             }
             InterpolationMode::TransformInterpolated => {
                 // TODO: Sub-pixel position interpolation applied via Bevy transform accumulation
+                0.0
             }
             InterpolationMode::ShaderCrossfade => {
-                // TODO: Shader crossfade uniform updates for advanced 2D visual blending between previous and current clips
+                if crossfade.clip_index != anim_state.clip_index {
+                    crossfade.fading = crossfade.shown.take();
+                    crossfade.started = fixed_time.elapsed();
+                }
+                // Ticks since the clip changed, including the part of the next tick
+                // already elapsed, so the fade runs smoothly between ticks.
+                let ticks = fixed_time
+                    .elapsed()
+                    .saturating_sub(crossfade.started)
+                    .as_secs_f32()
+                    / fixed_time.timestep().as_secs_f32()
+                    + fixed_time.overstep_fraction();
+                let duration = f32::from(video_settings.crossfade_ticks.max(1));
+                (1.0 - ticks / duration).max(0.0)
             }
+        };
+        if fade_weight <= 0.0 {
+            crossfade.fading = None;
+        }
+        crossfade.clip_index = anim_state.clip_index;
+
+        let mut material = current.clone();
+        let previous = crossfade
+            .fading
+            .as_ref()
+            .map(|previous| (previous, fade_weight));
+        let canvas = material.set_frames(&frame, previous);
+        material.set_palette(manifest.palette(palette.map_or(0, |palette| palette.0)));
+
+        let quad = quads.get(canvas, &mut meshes);
+        if mesh.0 != quad {
+            mesh.0 = quad;
+        }
+        if crossfade.shown.as_ref() != Some(&frame) {
+            crossfade.shown = Some(frame);
+        }
+
+        // Only touch the asset when something changed, so unchanged materials are not
+        // re-uploaded every frame.
+        if material != *current
+            && let Some(target) = materials.get_mut(&material_handle.0)
+        {
+            *target = material;
         }
     }
 }
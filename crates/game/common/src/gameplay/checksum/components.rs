@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::gameplay::fixed::{Fx, FxVec2};
+
+/// Number of recent tick checksums kept in `SimChecksums`.
+pub const CHECKSUM_HISTORY_TICKS: usize = 600;
+
+/// 64-bit FNV-1a hasher over explicit little-endian field bytes.
+///
+/// Unlike `std::hash::Hasher` implementations, its output is stable across
+/// platforms, builds and process runs, so checksums from a native server and a
+/// WASM client can be compared directly.
+#[derive(Debug, Clone, Copy)]
+pub struct StateHasher(u64);
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StateHasher {
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        self
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes(&[value])
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.u8(value as u8)
+    }
+
+    pub fn u16(&mut self, value: u16) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i32(&mut self, value: i32) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Hashes the exact bit pattern, so `0.0` and `-0.0` differ like they would in a desync.
+    pub fn f32(&mut self, value: f32) -> &mut Self {
+        self.u32(value.to_bits())
+    }
+
+    pub fn vec2(&mut self, value: Vec2) -> &mut Self {
+        self.f32(value.x).f32(value.y)
+    }
+
+    pub fn fx(&mut self, value: Fx) -> &mut Self {
+        self.i32(value.to_bits())
+    }
+
+    pub fn fx_vec2(&mut self, value: FxVec2) -> &mut Self {
+        self.fx(value.x).fx(value.y)
+    }
+
+    pub fn vec3(&mut self, value: Vec3) -> &mut Self {
+        self.f32(value.x).f32(value.y).f32(value.z)
+    }
+
+    pub fn quat(&mut self, value: Quat) -> &mut Self {
+        self.f32(value.x).f32(value.y).f32(value.z).f32(value.w)
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Gameplay components covered by the simulation checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChecksumComponent {
+    Position,
+    MoveState,
+    Velocity,
+    PushVelocity,
+    Transform,
+    Facing,
+    Locks,
+    AnimationState,
+    Combat,
+    Vitals,
+}
+
+impl ChecksumComponent {
+    pub const ALL: [Self; 10] = [
+        Self::Position,
+        Self::MoveState,
+        Self::Velocity,
+        Self::PushVelocity,
+        Self::Transform,
+        Self::Facing,
+        Self::Locks,
+        Self::AnimationState,
+        Self::Combat,
+        Self::Vitals,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Hash of every character's gameplay state at the end of one fixed tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TickChecksum {
+    pub tick: u32,
+    /// Combined hash over all components, for a cheap equality check.
+    pub total: u64,
+    /// Per-component hashes across all characters, indexed by `ChecksumComponent::index`.
+    pub components: [u64; ChecksumComponent::ALL.len()],
+}
+
+impl TickChecksum {
+    /// Components whose hashes differ between `self` and `other`.
+    pub fn diff(&self, other: &Self) -> Vec<ChecksumComponent> {
+        ChecksumComponent::ALL
+            .into_iter()
+            .filter(|component| {
+                self.components[component.index()] != other.components[component.index()]
+            })
+            .collect()
+    }
+}
+
+/// Rolling window of the latest tick checksums.
+#[derive(Resource, Debug, Default)]
+pub struct SimChecksums {
+    history: VecDeque<TickChecksum>,
+}
+
+impl SimChecksums {
+    pub fn push(&mut self, checksum: TickChecksum) {
+        if self.history.len() >= CHECKSUM_HISTORY_TICKS {
+            self.history.pop_front();
+        }
+        self.history.push_back(checksum);
+    }
+
+    pub fn latest(&self) -> Option<&TickChecksum> {
+        self.history.back()
+    }
+
+    /// Checksum recorded for `tick`, if still in the window.
+    pub fn get(&self, tick: u32) -> Option<&TickChecksum> {
+        self.history
+            .iter()
+            .rev()
+            .find(|checksum| checksum.tick == tick)
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// Unbounded checksum log of a whole run. Insert it to start logging.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ChecksumLog {
+    pub entries: Vec<TickChecksum>,
+}
+
+/// First point where two checksum logs disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: u32,
+    /// Differing components; empty when one log simply ends earlier.
+    pub components: Vec<ChecksumComponent>,
+}
+
+impl ChecksumLog {
+    /// Compares two runs entry by entry and reports the first mismatching tick.
+    pub fn first_divergence(&self, other: &Self) -> Option<Divergence> {
+        let mismatch = self
+            .entries
+            .iter()
+            .zip(&other.entries)
+            .find(|(a, b)| a.tick != b.tick || a.total != b.total);
+
+        if let Some((a, b)) = mismatch {
+            return Some(Divergence {
+                tick: a.tick.min(b.tick),
+                components: a.diff(b),
+            });
+        }
+
+        let shorter = self.entries.len().min(other.entries.len());
+        let longer = if self.entries.len() > other.entries.len() {
+            self
+        } else {
+            other
+        };
+        longer.entries.get(shorter).map(|extra| Divergence {
+            tick: extra.tick,
+            components: Vec::new(),
+        })
+    }
+
+    /// Plain-text form, one `tick total component...` line of hex per tick, for
+    /// comparing logs produced on different platforms.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&format!("{:x} {:016x}", entry.tick, entry.total));
+            for hash in entry.components {
+                text.push_str(&format!(" {hash:016x}"));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Option<Self> {
+        let entries = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                let tick = u32::from_str_radix(fields.next()?, 16).ok()?;
+                let total = u64::from_str_radix(fields.next()?, 16).ok()?;
+                let mut components = [0; ChecksumComponent::ALL.len()];
+                for hash in &mut components {
+                    *hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+                }
+                Some(TickChecksum {
+                    tick,
+                    total,
+                    components,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self { entries })
+    }
+}
@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+pub mod components;
+mod systems;
+
+pub use components::{
+    CHECKSUM_HISTORY_TICKS, ChecksumComponent, ChecksumLog, Divergence, SimChecksums, StateHasher,
+    TickChecksum,
+};
+
+use crate::gameplay::character::presentation::sync_character_transforms;
+
+pub struct ChecksumPlugin;
+
+impl Plugin for ChecksumPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimChecksums>().add_systems(
+            FixedPostUpdate,
+            systems::hash_simulation_state.after(sync_character_transforms),
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use super::components::{ChecksumComponent, ChecksumLog, SimChecksums, StateHasher, TickChecksum};
+use crate::gameplay::{
+    SimTick,
+    character::{
+        Character, CombatState, Vitals,
+        locomotion::{Facing, Locks, MoveState, PushVelocity, SimPosition, Velocity},
+        presentation::CharacterAnimationState,
+    },
+    versus::PlayerSlot,
+};
+
+type ChecksumQueryData = (
+    Option<&'static PlayerSlot>,
+    &'static SimPosition,
+    &'static MoveState,
+    &'static Velocity,
+    &'static PushVelocity,
+    &'static Transform,
+    &'static Facing,
+    &'static Locks,
+    Option<&'static CharacterAnimationState>,
+    &'static CombatState,
+    &'static Vitals,
+);
+
+/// Hashes the gameplay state of every character once the tick has fully simulated.
+pub fn hash_simulation_state(
+    tick: Res<SimTick>,
+    mut checksums: ResMut<SimChecksums>,
+    log: Option<ResMut<ChecksumLog>>,
+    characters: Query<ChecksumQueryData, With<Character>>,
+) {
+    // Entity ids differ between runs, so characters are hashed in player slot order
+    let mut characters = characters.iter().collect::<Vec<_>>();
+    characters.sort_by_key(|(slot, ..)| slot.map_or(u8::MAX as usize, |slot| slot.index()));
+
+    let mut hashers = [StateHasher::default(); ChecksumComponent::ALL.len()];
+    for (_, position, state, velocity, push, transform, facing, locks, animation, combat, vitals) in
+        characters
+    {
+        hashers[ChecksumComponent::Position.index()].fx_vec2(position.0);
+        hashers[ChecksumComponent::MoveState.index()]
+            .bool(state.grounded)
+            .bool(state.crouching)
+            .bool(state.dashing)
+            .bool(state.defending)
+            .bool(state.guard_releasing)
+            .u8(state.jumps_remaining)
+            .u16(state.coyote_ticks)
+            .u16(state.jump_buffer_ticks)
+            .u16(state.dash_ticks)
+            .u16(state.dash_cooldown_ticks)
+            .u8(state.mode as u8);
+        hashers[ChecksumComponent::Velocity.index()].fx_vec2(velocity.0);
+        hashers[ChecksumComponent::PushVelocity.index()].fx_vec2(push.0);
+        hashers[ChecksumComponent::Transform.index()]
+            .vec3(transform.translation)
+            .quat(transform.rotation)
+            .vec3(transform.scale);
+        hashers[ChecksumComponent::Facing.index()].u8(*facing as u8);
+        hashers[ChecksumComponent::Locks.index()]
+            .bool(locks.move_locked)
+            .bool(locks.jump_locked)
+            .bool(locks.turn_locked)
+            .bool(locks.hitstun_locked);
+        if let Some(animation) = animation {
+            hashers[ChecksumComponent::AnimationState.index()]
+                .u16(animation.clip_index)
+                .u16(animation.frame_index)
+                .u16(animation.elapsed_ticks)
+                .u16(animation.hitstop_ticks)
+                .u8(animation.flags.bits());
+        }
+        hashers[ChecksumComponent::Combat.index()]
+            .bool(combat.attacking)
+            .bool(combat.attack_connected)
+            .u16(combat.hitstun_ticks)
+            .u16(combat.blockstun_ticks);
+        hashers[ChecksumComponent::Vitals.index()]
+            .u16(vitals.health)
+            .u16(vitals.meter);
+    }
+
+    let components = hashers.map(|hasher| hasher.finish());
+    let mut total = StateHasher::default();
+    for hash in components {
+        total.bytes(&hash.to_le_bytes());
+    }
+
+    let checksum = TickChecksum {
+        tick: tick.0,
+        total: total.finish(),
+        components,
+    };
+    checksums.push(checksum);
+    if let Some(mut log) = log {
+        log.entries.push(checksum);
+    }
+}
@@ -0,0 +1,336 @@
+//! Deterministic Q16.16 fixed-point numbers for the gameplay simulation.
+//!
+//! Float results can differ between x86 native builds and WASM, so positions and
+//! velocities are simulated as integers and only converted to `f32` at the
+//! presentation boundary.
+//!
+//! Arithmetic saturates at the ends of the range instead of overflowing, and division
+//! by zero saturates towards the sign of the dividend (`0 / 0` is zero), so results
+//! are the same in debug and release builds on every platform.
+
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::gameplay::SIM_TICK_HZ;
+
+const FRAC_BITS: u32 = 16;
+
+/// Signed Q16.16 fixed-point scalar (range ±32768, resolution 1/65536).
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Fx(i32);
+
+impl Fx {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << FRAC_BITS);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Self(saturate((value as i64) << FRAC_BITS))
+    }
+
+    /// `numerator / denominator`, truncated towards zero.
+    pub const fn from_ratio(numerator: i32, denominator: i32) -> Self {
+        Self(divide(widen(numerator), denominator as i64))
+    }
+
+    /// Nearest fixed-point value to `value`. Meant for tuning constants and spawn
+    /// positions entering the simulation, never for feeding simulated values back in.
+    pub fn from_f32(value: f32) -> Self {
+        Self((value * Self::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::ONE.0 as f32
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    /// `-1`, `0` or `1` depending on the sign.
+    pub fn signum(self) -> i32 {
+        self.0.signum()
+    }
+
+    /// Portion of a per-second rate applied on a single simulation tick.
+    pub fn per_tick(self) -> Self {
+        Self(self.0 / SIM_TICK_HZ as i32)
+    }
+}
+
+/// `value` as the raw bits of a Q16.16 number, in a type wide enough to divide it.
+const fn widen(value: i32) -> i64 {
+    (value as i64) << FRAC_BITS
+}
+
+/// Clamps a widened intermediate result into the `i32` range.
+const fn saturate(value: i64) -> i32 {
+    if value > i32::MAX as i64 {
+        i32::MAX
+    } else if value < i32::MIN as i64 {
+        i32::MIN
+    } else {
+        value as i32
+    }
+}
+
+/// Saturating division, truncated towards zero. Dividing by zero gives the end of the
+/// range on the dividend's side, or zero for `0 / 0`.
+const fn divide(numerator: i64, denominator: i64) -> i32 {
+    if denominator == 0 {
+        match numerator.signum() {
+            1 => i32::MAX,
+            -1 => i32::MIN,
+            _ => 0,
+        }
+    } else {
+        saturate(numerator / denominator)
+    }
+}
+
+impl Add for Fx {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Fx {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Neg for Fx {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl Mul for Fx {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(saturate((self.0 as i64 * rhs.0 as i64) >> FRAC_BITS))
+    }
+}
+
+impl Mul<i32> for Fx {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Self(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Div for Fx {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self(divide(widen(self.0), rhs.0 as i64))
+    }
+}
+
+impl AddAssign for Fx {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fx {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Fx {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// Two-component fixed-point vector.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FxVec2 {
+    pub x: Fx,
+    pub y: Fx,
+}
+
+impl FxVec2 {
+    pub const ZERO: Self = Self::new(Fx::ZERO, Fx::ZERO);
+
+    pub const fn new(x: Fx, y: Fx) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_vec2(value: Vec2) -> Self {
+        Self::new(Fx::from_f32(value.x), Fx::from_f32(value.y))
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+
+    /// Euclidean length using an integer square root.
+    pub fn length(self) -> Fx {
+        let x = self.x.0 as i128;
+        let y = self.y.0 as i128;
+        Fx(saturate(((x * x + y * y) as u128).isqrt() as i64))
+    }
+
+    pub fn per_tick(self) -> Self {
+        Self::new(self.x.per_tick(), self.y.per_tick())
+    }
+}
+
+impl Add for FxVec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for FxVec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<Fx> for FxVec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Fx) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl AddAssign for FxVec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl MulAssign<Fx> for FxVec2 {
+    fn mul_assign(&mut self, rhs: Fx) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: Fx = Fx::from_bits(i32::MAX);
+    const MIN: Fx = Fx::from_bits(i32::MIN);
+
+    #[test]
+    fn conversions_saturate() {
+        assert_eq!(Fx::from_int(3).to_bits(), 3 << 16);
+        assert_eq!(Fx::from_int(40_000), MAX);
+        assert_eq!(Fx::from_int(-40_000), MIN);
+        assert_eq!(Fx::from_f32(1e9), MAX);
+        assert_eq!(Fx::from_f32(-1e9), MIN);
+        assert_eq!(Fx::from_f32(0.5), Fx::from_ratio(1, 2));
+        assert_eq!(Fx::from_f32(-2.25).to_f32(), -2.25);
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        assert_eq!(MAX + Fx::ONE, MAX);
+        assert_eq!(MIN - Fx::ONE, MIN);
+        assert_eq!(-MIN, MAX);
+        assert_eq!(MIN.abs(), MAX);
+        assert_eq!(Fx::from_int(300) * Fx::from_int(300), MAX);
+        assert_eq!(Fx::from_int(-300) * Fx::from_int(300), MIN);
+        assert_eq!(Fx::from_int(20_000) * 2, MAX);
+        assert_eq!(Fx::from_int(-20_000) * 2, MIN);
+        assert_eq!(Fx::from_int(20_000) / Fx::from_ratio(1, 4), MAX);
+
+        let mut accumulated = MAX;
+        accumulated += Fx::ONE;
+        accumulated *= Fx::from_int(2);
+        assert_eq!(accumulated, MAX);
+    }
+
+    #[test]
+    fn multiplies_and_divides() {
+        assert_eq!(Fx::from_ratio(3, 2) * Fx::from_int(4), Fx::from_int(6));
+        assert_eq!(Fx::from_ratio(-3, 2) * 4, Fx::from_int(-6));
+        assert_eq!(Fx::from_int(7) / Fx::from_int(2), Fx::from_ratio(7, 2));
+        assert_eq!(Fx::from_ratio(1, 3).to_bits(), 21_845);
+        // Truncated towards zero on both sides
+        assert_eq!(Fx::from_ratio(-1, 3).to_bits(), -21_845);
+    }
+
+    #[test]
+    fn division_by_zero_saturates_towards_the_dividend() {
+        assert_eq!(Fx::ONE / Fx::ZERO, MAX);
+        assert_eq!(-Fx::ONE / Fx::ZERO, MIN);
+        assert_eq!(Fx::ZERO / Fx::ZERO, Fx::ZERO);
+        assert_eq!(Fx::from_ratio(5, 0), MAX);
+        assert_eq!(Fx::from_ratio(-5, 0), MIN);
+        assert_eq!(Fx::from_ratio(0, 0), Fx::ZERO);
+    }
+
+    #[test]
+    fn per_tick_truncates_towards_zero() {
+        let hz = SIM_TICK_HZ as i32;
+        assert_eq!(Fx::from_int(hz).per_tick(), Fx::ONE);
+        assert_eq!(Fx::from_bits(hz - 1).per_tick(), Fx::ZERO);
+        assert_eq!(Fx::from_bits(1 - hz).per_tick(), Fx::ZERO);
+        assert_eq!(Fx::from_bits(2 * hz - 1).per_tick().to_bits(), 1);
+        assert_eq!(Fx::from_bits(1 - 2 * hz).per_tick().to_bits(), -1);
+
+        // A second's worth of ticks loses less than one bit per tick
+        let gravity = Fx::from_int(980);
+        let lost = gravity - gravity.per_tick() * hz;
+        assert!(lost >= Fx::ZERO && lost < Fx::from_bits(hz));
+
+        let velocity = FxVec2::new(Fx::from_int(hz), Fx::from_int(-hz)).per_tick();
+        assert_eq!(velocity, FxVec2::new(Fx::ONE, -Fx::ONE));
+    }
+
+    #[test]
+    fn length_uses_an_exact_integer_square_root() {
+        let v = |x, y| FxVec2::new(Fx::from_int(x), Fx::from_int(y));
+        assert_eq!(v(3, 4).length(), Fx::from_int(5));
+        assert_eq!(v(-3, -4).length(), Fx::from_int(5));
+        assert_eq!(FxVec2::ZERO.length(), Fx::ZERO);
+        // sqrt(2) = 1.41421356..., floored to the nearest bit below
+        assert_eq!(v(1, 1).length().to_bits(), 92_681);
+        assert_eq!(FxVec2::new(MAX, MAX).length(), MAX);
+        assert_eq!(FxVec2::new(MIN, MIN).length(), MAX);
+    }
+
+    #[test]
+    fn vectors_saturate_per_component() {
+        let a = FxVec2::new(MAX, Fx::ONE);
+        assert_eq!(a + a, FxVec2::new(MAX, Fx::from_int(2)));
+        assert_eq!(FxVec2::new(MIN, Fx::ZERO) - a, FxVec2::new(MIN, -Fx::ONE));
+        assert_eq!(a * Fx::from_int(2), FxVec2::new(MAX, Fx::from_int(2)));
+        assert_eq!(
+            FxVec2::from_vec2(Vec2::new(1.5, -0.25)).to_vec2(),
+            Vec2::new(1.5, -0.25)
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+/// Number of ticks shown on the scrolling frame meter.
+pub const FRAME_METER_TICKS: usize = 80;
+
+/// What a fighter was doing on one simulated tick, as shown on the frame meter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FrameKind {
+    /// Free to act.
+    #[default]
+    Idle,
+    Startup,
+    Active,
+    Recovery,
+    Hitstun,
+    Blockstun,
+}
+
+/// Measured frame data of one move, in simulated ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MoveFrameData {
+    /// Clip the move started on.
+    pub clip_index: u16,
+    /// Ticks before the first active tick.
+    pub startup: u16,
+    pub active: u16,
+    /// Ticks after the last active tick until the fighter could act again.
+    pub recovery: u16,
+    /// Frame advantage when the move hit (positive means the attacker recovers first).
+    pub on_hit: Option<i32>,
+    /// Frame advantage when the move was blocked.
+    pub on_block: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Connection {
+    Hit,
+    Block,
+}
+
+/// A connected move waiting for both fighters to recover before its advantage is known.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingAdvantage {
+    pub defender: Entity,
+    pub connection: Connection,
+}
+
+/// Per-fighter frame data measured tick by tick from the running simulation.
+#[derive(Component, Debug, Default)]
+pub struct FrameDataTracker {
+    /// Classification of the latest tick.
+    pub current: FrameKind,
+    /// Tick on which the fighter last became actionable, `None` while busy.
+    pub free_since: Option<u32>,
+    /// Most recently completed move.
+    pub last_move: Option<MoveFrameData>,
+    /// Scrolling frame meter, oldest tick first.
+    pub meter: VecDeque<FrameKind>,
+    pub(crate) in_progress: Option<MoveFrameData>,
+    pub(crate) pending: Option<PendingAdvantage>,
+}
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+pub mod components;
+mod systems;
+
+pub use components::{FRAME_METER_TICKS, FrameDataTracker, FrameKind, MoveFrameData};
+
+use crate::{GameMode, gameplay::character::presentation::advance_character_animations};
+
+pub struct FrameDataPlugin;
+
+impl Plugin for FrameDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                systems::attach_frame_data_trackers,
+                systems::track_frame_data,
+            )
+                .chain()
+                .after(advance_character_animations)
+                .run_if(resource_equals(GameMode::Training)),
+        );
+    }
+}
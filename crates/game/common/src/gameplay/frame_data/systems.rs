@@ -0,0 +1,180 @@
+use bevy::{ecs::message::MessageReader, prelude::*};
+
+use super::components::{
+    Connection, FRAME_METER_TICKS, FrameDataTracker, FrameKind, MoveFrameData, PendingAdvantage,
+};
+use crate::gameplay::{
+    SimTick,
+    character::{
+        Character, CharacterHitMessage, CombatState,
+        locomotion::components::{CharacterBlockedMessage, MoveState},
+        presentation::{
+            CharacterAnimationState, CharacterManifestAsset, CharacterManifestHandle, FramePhase,
+        },
+    },
+};
+
+pub fn attach_frame_data_trackers(
+    mut commands: Commands,
+    fighters: Query<Entity, (With<Character>, Without<FrameDataTracker>)>,
+) {
+    for entity in &fighters {
+        commands.entity(entity).insert(FrameDataTracker::default());
+    }
+}
+
+/// Classifies every fighter's tick, accumulates startup/active/recovery counts for the
+/// move in progress, resolves frame advantage once both sides recover, and scrolls
+/// the frame meter while anyone is busy.
+#[allow(clippy::type_complexity)]
+pub fn track_frame_data(
+    tick: Res<SimTick>,
+    manifest_assets: Res<Assets<CharacterManifestAsset>>,
+    mut hits: MessageReader<CharacterHitMessage>,
+    mut blocks: MessageReader<CharacterBlockedMessage>,
+    mut was_recording: Local<bool>,
+    mut fighters: Query<
+        (
+            Entity,
+            &mut FrameDataTracker,
+            &MoveState,
+            &CombatState,
+            &CharacterManifestHandle,
+            &CharacterAnimationState,
+        ),
+        With<Character>,
+    >,
+) {
+    let connections: Vec<(Entity, Entity, Connection)> = hits
+        .read()
+        .map(|msg| (msg.attacker, msg.defender, Connection::Hit))
+        .chain(
+            blocks
+                .read()
+                .map(|msg| (msg.attacker, msg.defender, Connection::Block)),
+        )
+        .collect();
+
+    // 1. Classify this tick and accumulate the move in progress
+    for (entity, mut tracker, state, combat, manifest_handle, anim_state) in &mut fighters {
+        let phase = manifest_assets
+            .get(&manifest_handle.0)
+            .and_then(|manifest| manifest.clips.get(anim_state.clip_index as usize))
+            .and_then(|clip| clip.frames.get(anim_state.frame_index as usize))
+            .map_or(FramePhase::Neutral, |frame| frame.phase);
+        let kind = classify_tick(state, combat, phase);
+
+        match kind {
+            FrameKind::Startup | FrameKind::Active | FrameKind::Recovery => {
+                // Startup after active or recovery means a new move was chained in
+                let chained = kind == FrameKind::Startup
+                    && tracker
+                        .in_progress
+                        .is_some_and(|data| data.active > 0 || data.recovery > 0);
+                if chained {
+                    tracker.last_move = tracker.in_progress.take();
+                }
+
+                if tracker.in_progress.is_none() {
+                    tracker.pending = None;
+                }
+                let data = tracker.in_progress.get_or_insert(MoveFrameData {
+                    clip_index: anim_state.clip_index,
+                    ..default()
+                });
+                match kind {
+                    FrameKind::Startup => data.startup += 1,
+                    FrameKind::Active => data.active += 1,
+                    _ => data.recovery += 1,
+                }
+            }
+            _ => {
+                if let Some(done) = tracker.in_progress.take() {
+                    tracker.last_move = Some(done);
+                }
+            }
+        }
+
+        if kind == FrameKind::Idle {
+            tracker.free_since.get_or_insert(tick.0);
+        } else {
+            tracker.free_since = None;
+        }
+        tracker.current = kind;
+
+        if let Some(&(_, defender, connection)) = connections
+            .iter()
+            .find(|(attacker, _, _)| *attacker == entity)
+        {
+            tracker.pending = Some(PendingAdvantage {
+                defender,
+                connection,
+            });
+        }
+    }
+
+    // 2. Resolve frame advantage once attacker and defender can both act again
+    let free_ticks: Vec<(Entity, Option<u32>)> = fighters
+        .iter()
+        .map(|(entity, tracker, ..)| (entity, tracker.free_since))
+        .collect();
+
+    for (_, mut tracker, ..) in &mut fighters {
+        let (Some(pending), Some(attacker_free)) = (tracker.pending, tracker.free_since) else {
+            continue;
+        };
+        let Some(defender_free) = free_ticks
+            .iter()
+            .find(|(entity, _)| *entity == pending.defender)
+            .and_then(|(_, free)| *free)
+        else {
+            continue;
+        };
+
+        let advantage = defender_free.wrapping_sub(attacker_free) as i32;
+        if let Some(last_move) = tracker.last_move.as_mut() {
+            match pending.connection {
+                Connection::Hit => last_move.on_hit = Some(advantage),
+                Connection::Block => last_move.on_block = Some(advantage),
+            }
+        }
+        tracker.pending = None;
+    }
+
+    // 3. Scroll the frame meter while anyone is busy; a new sequence clears it
+    let any_busy = fighters
+        .iter()
+        .any(|(_, tracker, ..)| tracker.current != FrameKind::Idle);
+
+    if any_busy {
+        for (_, mut tracker, ..) in &mut fighters {
+            if !*was_recording {
+                tracker.meter.clear();
+            }
+            if tracker.meter.len() >= FRAME_METER_TICKS {
+                tracker.meter.pop_front();
+            }
+            let current = tracker.current;
+            tracker.meter.push_back(current);
+        }
+    }
+    *was_recording = any_busy;
+}
+
+fn classify_tick(state: &MoveState, combat: &CombatState, phase: FramePhase) -> FrameKind {
+    if combat.hitstun_ticks > 0 {
+        return FrameKind::Hitstun;
+    }
+    if combat.blockstun_ticks > 0 {
+        return FrameKind::Blockstun;
+    }
+
+    match phase {
+        FramePhase::Startup => FrameKind::Startup,
+        FramePhase::Active => FrameKind::Active,
+        FramePhase::Recovery => FrameKind::Recovery,
+        // Dashes carry no move annotations; their travel counts as active movement
+        FramePhase::Neutral if state.dashing => FrameKind::Active,
+        FramePhase::Neutral => FrameKind::Idle,
+    }
+}
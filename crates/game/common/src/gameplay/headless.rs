@@ -0,0 +1,95 @@
+//! Versus matches simulated without a window, input devices or asset server.
+
+use bevy::{app::FixedMain, prelude::*};
+
+use crate::gameplay::{
+    SimulationPlugin,
+    character::{
+        input::{InputFrame, MockedInput},
+        presentation::CharacterManifestAsset,
+    },
+    versus::{PlayerSlot, VersusRounds, fighter_bundle},
+};
+
+/// A versus match stepped tick by tick from its players' inputs, on the same systems
+/// the clients run. The game server keeps one per online match, so results come from
+/// its own simulation rather than from what the players report.
+#[derive(Debug)]
+pub struct HeadlessMatch {
+    world: World,
+    /// Indexed by `PlayerSlot`.
+    fighters: [Entity; 2],
+}
+
+impl HeadlessMatch {
+    /// A match between `manifests`, indexed by `PlayerSlot`, with both fighters at their
+    /// start positions.
+    pub fn new(manifests: [CharacterManifestAsset; 2]) -> Self {
+        let mut app = App::new();
+        app.add_plugins(SimulationPlugin)
+            .init_resource::<Assets<CharacterManifestAsset>>()
+            .init_resource::<VersusRounds>();
+        // Systems live in the world's schedules, so the world is all that is needed to
+        // run them, and unlike `App` it can move between threads.
+        let mut world = std::mem::take(app.world_mut());
+
+        let handles = {
+            let mut assets = world.resource_mut::<Assets<CharacterManifestAsset>>();
+            manifests.map(|manifest| assets.add(manifest))
+        };
+        let fighters = PlayerSlot::ALL.map(|slot| {
+            world
+                .spawn((
+                    fighter_bundle(slot, slot.start_position(), handles[slot.index()].clone()),
+                    MockedInput::default(),
+                ))
+                .id()
+        });
+
+        Self { world, fighters }
+    }
+
+    /// Simulates the next tick with `frames`, indexed by `PlayerSlot`.
+    pub fn step(&mut self, frames: [InputFrame; 2]) {
+        for (fighter, frame) in self.fighters.into_iter().zip(frames) {
+            if let Some(mut mocked) = self.world.get_mut::<MockedInput>(fighter) {
+                mocked.0 = frame;
+            }
+        }
+        self.world.run_schedule(FixedMain);
+        // Messages are swapped once per frame in `First`; each tick stands in for a frame.
+        self.world.run_schedule(First);
+    }
+
+    pub fn rounds(&self) -> &VersusRounds {
+        self.world.resource::<VersusRounds>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::versus::MAX_MATCH_TICKS;
+
+    fn naruto() -> CharacterManifestAsset {
+        CharacterManifestAsset::from_ron(include_bytes!(
+            "../../../../../assets/game/characters/naruto/naruto.ron"
+        ))
+        .expect("parse naruto.ron")
+    }
+
+    #[test]
+    fn idle_fighters_draw_on_time() {
+        let mut game = HeadlessMatch::new([naruto(), naruto()]);
+        let mut ticks = 0;
+        while !game.rounds().is_over() {
+            assert!(ticks < MAX_MATCH_TICKS, "match never ended");
+            game.step([InputFrame::NONE; 2]);
+            ticks += 1;
+        }
+
+        // Two rounds run out level, which counts for both fighters each time
+        assert_eq!(game.rounds().wins, [2, 2]);
+        assert_eq!(game.rounds().winner(), None);
+    }
+}
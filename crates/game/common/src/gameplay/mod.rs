@@ -1,14 +1,72 @@
-use bevy::app::{App, Plugin};
+use std::time::Duration;
 
-use crate::gameplay::character::CharacterPlugin;
+use bevy::prelude::*;
 
+use crate::gameplay::{
+    ai::AiPlugin,
+    arena::ArenaBounds,
+    character::{CharacterPlugin, input::InputDevicePlugin, presentation::PresentationPlugin},
+    checksum::ChecksumPlugin,
+    frame_data::FrameDataPlugin,
+    replay::ReplayPlugin,
+    training::TrainingPlugin,
+    versus::VersusPlugin,
+};
+
+pub mod ai;
 pub mod arena;
 pub mod character;
+pub mod checksum;
+pub mod fixed;
+pub mod frame_data;
+pub mod headless;
+pub mod replay;
+pub mod training;
+pub mod versus;
+
+/// Rate of the fixed simulation tick, matching the 60Hz animation frame timing.
+pub const SIM_TICK_HZ: u32 = 60;
+
+/// Wall-clock length of `ticks` simulation ticks.
+pub fn ticks_to_duration(ticks: u16) -> Duration {
+    Duration::from_secs_f64(f64::from(ticks) / f64::from(SIM_TICK_HZ))
+}
+
+/// Number of `FixedUpdate` ticks simulated so far.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deref)]
+pub struct SimTick(pub u32);
+
+fn advance_sim_tick(mut tick: ResMut<SimTick>) {
+    tick.0 = tick.0.wrapping_add(1);
+}
+
+/// The deterministic part of the game: everything that runs on the fixed tick and
+/// changes the outcome of a fight. Needs no window, input device or asset server, so
+/// the game server runs it headless (see `headless::HeadlessMatch`).
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimTick>()
+            .add_systems(FixedFirst, advance_sim_tick)
+            .add_plugins((CharacterPlugin, VersusPlugin, AiPlugin));
+    }
+}
 
 pub struct GameplayPlugin;
 
 impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(CharacterPlugin);
+        app.insert_resource(Time::<Fixed>::from_hz(SIM_TICK_HZ as f64))
+            .init_resource::<ArenaBounds>()
+            .add_plugins((
+                SimulationPlugin,
+                InputDevicePlugin,
+                PresentationPlugin,
+                TrainingPlugin,
+                FrameDataPlugin,
+                ReplayPlugin,
+                ChecksumPlugin,
+            ));
     }
 }
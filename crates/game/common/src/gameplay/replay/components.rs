@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use super::format::{Replay, ReplayError, ReplayHeader};
+
+/// Slowest playback speed offered by the replay viewer.
+pub const MIN_REPLAY_SPEED: f32 = 0.25;
+/// Fastest playback speed offered by the replay viewer.
+pub const MAX_REPLAY_SPEED: f32 = 4.0;
+/// Upper bound on ticks re-simulated in a single frame while seeking, so long
+/// seeks stay responsive instead of stalling one frame.
+pub const MAX_SEEK_TICKS_PER_FRAME: u32 = 1200;
+
+/// Match currently being recorded, if any.
+#[derive(Resource, Debug, Default)]
+pub struct ReplayRecorder {
+    replay: Option<Replay>,
+}
+
+impl ReplayRecorder {
+    pub fn start(&mut self, header: ReplayHeader) {
+        self.replay = Some(Replay::new(header));
+    }
+
+    /// Stops recording and hands back what was captured.
+    pub fn finish(&mut self) -> Option<Replay> {
+        self.replay.take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub(crate) fn replay_mut(&mut self) -> Option<&mut Replay> {
+        self.replay.as_mut()
+    }
+}
+
+/// Replay being watched. Present only while `GameMode::Replay` is running.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    /// Next tick to feed into the simulation.
+    pub cursor: u32,
+    pub paused: bool,
+    /// Playback rate relative to real time, within `MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED`.
+    pub speed: f32,
+    /// Tick the viewer is fast-forwarding to.
+    pub(crate) seek_target: Option<u32>,
+    /// Single ticks requested while paused.
+    pub(crate) pending_steps: u32,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            cursor: 0,
+            paused: false,
+            speed: 1.0,
+            seek_target: None,
+            pending_steps: 0,
+        }
+    }
+
+    /// Starts at the last recorded tick, fast-forwarding through everything before it.
+    /// Used to join a match that is still being streamed in.
+    pub fn catch_up(replay: Replay) -> Self {
+        let seek_target = Some(replay.tick_count());
+        Self {
+            seek_target,
+            ..Self::new(replay)
+        }
+    }
+
+    /// Appends more ticks of a match joined with `catch_up`, fast-forwarding through
+    /// them too while the catch-up is still running.
+    pub fn extend_catch_up(&mut self, ticks: &Replay) -> Result<(), ReplayError> {
+        self.replay.extend(ticks)?;
+        if self.seek_target.is_some() {
+            self.seek_target = Some(self.replay.tick_count());
+        }
+        Ok(())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.replay.tick_count()
+    }
+
+    pub fn is_seeking(&self) -> bool {
+        self.seek_target.is_some()
+    }
+}
+
+/// Viewer controls for the replay being played.
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub enum ReplayCommand {
+    TogglePause,
+    /// Advance exactly one tick while paused.
+    Step,
+    /// Jump to a tick, re-simulating from the start when seeking backwards.
+    Seek(u32),
+    /// Rewind to tick zero and re-simulate straight through to the last tick.
+    Rerun,
+    SetSpeed(f32),
+}
+
+/// The replay is rewinding to tick zero; fighters must be respawned from the header
+/// before re-simulation starts.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayRestartMessage;
@@ -0,0 +1,441 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::gameplay::{character::input::InputFrame, checksum::StateHasher};
+
+/// First bytes of every replay file.
+pub const REPLAY_MAGIC: [u8; 4] = *b"NRPL";
+
+/// Binary layout version written after the magic. Bump when the layout changes.
+/// Version 1 stored one byte per input frame, before frames carried stick tilt.
+pub const REPLAY_FORMAT_VERSION: u16 = 2;
+
+/// File extension used when saving replays.
+pub const REPLAY_EXTENSION: &str = "nrpl";
+
+/// Stage name recorded while the game has a single arena.
+pub const DEFAULT_STAGE: &str = "default";
+
+/// Longest run of identical ticks stored in one run-length entry.
+const MAX_RUN: usize = u16::MAX as usize;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("not a replay file")]
+    BadMagic,
+    #[error("unsupported replay format version {0}")]
+    UnsupportedVersion(u16),
+    #[error("replay file ends unexpectedly")]
+    Truncated,
+    #[error("replay contains invalid text")]
+    InvalidText,
+    #[error("replay input runs do not add up to its tick count")]
+    BadRunLength,
+    #[error("replay part has {found} fighters, the match has {expected}")]
+    FighterCountMismatch { expected: usize, found: usize },
+    #[error("replay has {0} fighters, at most {max} are supported", max = u8::MAX)]
+    TooManyFighters(usize),
+}
+
+/// One fighter taking part in the recorded match, in `PlayerSlot` order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayFighter {
+    /// `CharacterManifestAsset::id` of the fighter.
+    pub manifest_id: String,
+    /// `CharacterManifestAsset::content_hash` at recording time, to detect edited manifests.
+    /// Zero when unknown, as in replays recorded by the game server.
+    pub content_hash: u64,
+    /// Where the fighter stood on the first tick.
+    pub spawn: Vec2,
+}
+
+/// Everything needed to rebuild the starting state of a recorded match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayHeader {
+    pub game_version: String,
+    pub stage: String,
+    /// Seed the match's randomness was drawn from: the CPU opponent's in a CPU match,
+    /// the server's match seed online, zero when nothing random took part.
+    pub rng_seed: u64,
+    pub fighters: Vec<ReplayFighter>,
+}
+
+/// A recorded match: the header plus one `InputFrame` per fighter per fixed tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    /// Tick-major input frames, `fighters.len()` entries per tick.
+    frames: Vec<InputFrame>,
+}
+
+impl Replay {
+    pub fn new(header: ReplayHeader) -> Self {
+        Self {
+            header,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn fighter_count(&self) -> usize {
+        self.header.fighters.len()
+    }
+
+    pub fn tick_count(&self) -> u32 {
+        match self.fighter_count() {
+            0 => 0,
+            fighters => (self.frames.len() / fighters) as u32,
+        }
+    }
+
+    /// Frames for every fighter on `tick`, in `PlayerSlot` order.
+    pub fn tick(&self, tick: u32) -> Option<&[InputFrame]> {
+        let fighters = self.fighter_count();
+        let start = tick as usize * fighters;
+        self.frames.get(start..start + fighters)
+    }
+
+    /// Drops every tick from `ticks` on.
+    pub fn truncate(&mut self, ticks: u32) {
+        self.frames.truncate(ticks as usize * self.fighter_count());
+    }
+
+    /// Appends one tick. `frames` must hold one entry per fighter.
+    pub fn push_tick(&mut self, frames: &[InputFrame]) {
+        debug_assert_eq!(frames.len(), self.fighter_count());
+        self.frames.extend_from_slice(frames);
+    }
+
+    /// The same match holding only the ticks in `ticks`, to send a long replay in parts.
+    pub fn slice(&self, ticks: Range<u32>) -> Self {
+        let fighters = self.fighter_count();
+        let start = (ticks.start as usize * fighters).min(self.frames.len());
+        let end = (ticks.end as usize * fighters).clamp(start, self.frames.len());
+        Self {
+            header: self.header.clone(),
+            frames: self.frames[start..end].to_vec(),
+        }
+    }
+
+    /// Appends every tick of `other`, a part of the same match from `slice`. A part with
+    /// another number of fighters is rejected, as its ticks would not line up.
+    pub fn extend(&mut self, other: &Replay) -> Result<(), ReplayError> {
+        if other.fighter_count() != self.fighter_count() {
+            return Err(ReplayError::FighterCountMismatch {
+                expected: self.fighter_count(),
+                found: other.fighter_count(),
+            });
+        }
+        self.frames.extend_from_slice(&other.frames);
+        Ok(())
+    }
+
+    /// Serializes the replay: header fields, then run-length encoded input ticks.
+    pub fn encode(&self) -> Result<Vec<u8>, ReplayError> {
+        let fighters = self.fighter_count();
+        let fighter_count =
+            u8::try_from(fighters).map_err(|_| ReplayError::TooManyFighters(fighters))?;
+
+        let mut out = Vec::with_capacity(64 + self.frames.len() / 4);
+        out.extend_from_slice(&REPLAY_MAGIC);
+        out.extend_from_slice(&REPLAY_FORMAT_VERSION.to_le_bytes());
+        write_str(&mut out, &self.header.game_version);
+        write_str(&mut out, &self.header.stage);
+        out.extend_from_slice(&self.header.rng_seed.to_le_bytes());
+
+        out.push(fighter_count);
+        for fighter in &self.header.fighters {
+            write_str(&mut out, &fighter.manifest_id);
+            out.extend_from_slice(&fighter.content_hash.to_le_bytes());
+            out.extend_from_slice(&fighter.spawn.x.to_le_bytes());
+            out.extend_from_slice(&fighter.spawn.y.to_le_bytes());
+        }
+
+        out.extend_from_slice(&self.tick_count().to_le_bytes());
+        if fighters > 0 {
+            let mut ticks = self.frames.chunks_exact(fighters).peekable();
+            while let Some(row) = ticks.next() {
+                let mut run = 1;
+                while run < MAX_RUN && ticks.next_if(|next| *next == row).is_some() {
+                    run += 1;
+                }
+                out.extend_from_slice(&(run as u16).to_le_bytes());
+                out.extend(row.iter().flat_map(|frame| frame.bits().to_le_bytes()));
+            }
+        }
+
+        Ok(out)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader(bytes);
+
+        if reader.take(REPLAY_MAGIC.len())? != REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = reader.u16()?;
+        let frame_size = match version {
+            1 => 1,
+            REPLAY_FORMAT_VERSION => 2,
+            _ => return Err(ReplayError::UnsupportedVersion(version)),
+        };
+
+        let game_version = reader.string()?;
+        let stage = reader.string()?;
+        let rng_seed = reader.u64()?;
+
+        let fighter_count = reader.u8()? as usize;
+        let mut fighters = Vec::with_capacity(fighter_count);
+        for _ in 0..fighter_count {
+            fighters.push(ReplayFighter {
+                manifest_id: reader.string()?,
+                content_hash: reader.u64()?,
+                spawn: Vec2::new(reader.f32()?, reader.f32()?),
+            });
+        }
+
+        let tick_count = reader.u32()? as usize;
+        if fighter_count == 0 && tick_count > 0 {
+            return Err(ReplayError::BadRunLength);
+        }
+        // Each run-length entry covers at most `MAX_RUN` ticks, which caps what the
+        // rest of the file can hold before anything is allocated for it
+        let row_size = 2 + fighter_count * frame_size;
+        if tick_count > reader.0.len() / row_size * MAX_RUN {
+            return Err(ReplayError::Truncated);
+        }
+
+        let mut frames = Vec::with_capacity(tick_count * fighter_count);
+        let mut decoded = 0;
+        while decoded < tick_count {
+            let run = reader.u16()? as usize;
+            if run == 0 || run > tick_count - decoded {
+                return Err(ReplayError::BadRunLength);
+            }
+            let row: Vec<InputFrame> = reader
+                .take(fighter_count * frame_size)?
+                .chunks_exact(frame_size)
+                .map(|bytes| {
+                    let mut bits = [0; 2];
+                    bits[..frame_size].copy_from_slice(bytes);
+                    InputFrame::from_bits_truncate(u16::from_le_bytes(bits))
+                })
+                .collect();
+            for _ in 0..run {
+                frames.extend_from_slice(&row);
+            }
+            decoded += run;
+        }
+
+        Ok(Self {
+            header: ReplayHeader {
+                game_version,
+                stage,
+                rng_seed,
+                fighters,
+            },
+            frames,
+        })
+    }
+}
+
+/// Stable hash of raw file bytes, see `StateHasher`.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    StateHasher::default().bytes(bytes).finish()
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    let bytes = &value.as_bytes()[..value.len().min(u16::MAX as usize)];
+    out.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReplayError> {
+        if self.0.len() < len {
+            return Err(ReplayError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, ReplayError> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    fn string(&mut self) -> Result<String, ReplayError> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| ReplayError::InvalidText)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Replay {
+        let fighter = |manifest_id: &str, x| ReplayFighter {
+            manifest_id: manifest_id.to_owned(),
+            content_hash: 0xfeed_beef,
+            spawn: Vec2::new(x, -100.0),
+        };
+        let mut replay = Replay::new(ReplayHeader {
+            game_version: "0.1.0".to_owned(),
+            stage: DEFAULT_STAGE.to_owned(),
+            rng_seed: 42,
+            fighters: vec![fighter("naruto", -300.0), fighter("naruto", 300.0)],
+        });
+        let idle = [InputFrame::NONE, InputFrame::NONE];
+        let walk = [InputFrame::RIGHT, InputFrame::LEFT | InputFrame::JUMP];
+        for tick in 0..200 {
+            replay.push_tick(if tick % 50 < 30 { &idle } else { &walk });
+        }
+        replay
+    }
+
+    #[test]
+    fn round_trips() {
+        let replay = sample();
+        let bytes = replay.encode().unwrap();
+        assert_eq!(Replay::decode(&bytes).unwrap(), replay);
+    }
+
+    #[test]
+    fn splits_runs_longer_than_a_run_entry() {
+        let mut replay = sample();
+        replay.truncate(0);
+        for _ in 0..MAX_RUN + 10 {
+            replay.push_tick(&[InputFrame::DOWN, InputFrame::NONE]);
+        }
+        let bytes = replay.encode().unwrap();
+        assert_eq!(Replay::decode(&bytes).unwrap(), replay);
+    }
+
+    #[test]
+    fn round_trips_slices() {
+        let replay = sample();
+        let mut joined = replay.slice(0..0);
+        for start in (0..replay.tick_count()).step_by(64) {
+            let bytes = replay.slice(start..start + 64).encode().unwrap();
+            joined.extend(&Replay::decode(&bytes).unwrap()).unwrap();
+        }
+        assert_eq!(joined, replay);
+    }
+
+    #[test]
+    fn rejects_parts_of_another_match() {
+        let mut replay = sample();
+        let mut solo = replay.slice(0..10);
+        solo.header.fighters.truncate(1);
+        assert!(matches!(
+            replay.extend(&solo),
+            Err(ReplayError::FighterCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert_eq!(replay, sample());
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_version() {
+        let mut bytes = sample().encode().unwrap();
+        bytes[0] = b'X';
+        assert!(matches!(Replay::decode(&bytes), Err(ReplayError::BadMagic)));
+
+        let mut bytes = sample().encode().unwrap();
+        bytes[4..6].copy_from_slice(&99u16.to_le_bytes());
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn rejects_every_truncation() {
+        let bytes = sample().encode().unwrap();
+        for len in 0..bytes.len() {
+            assert!(
+                Replay::decode(&bytes[..len]).is_err(),
+                "{len} bytes decoded"
+            );
+        }
+    }
+
+    /// Offset of the tick count, right after the header of `sample()`.
+    fn tick_count_offset(replay: &Replay) -> usize {
+        let mut header = replay.slice(0..0).encode().unwrap();
+        header.truncate(header.len() - 4);
+        header.len()
+    }
+
+    #[test]
+    fn rejects_tick_counts_the_file_cannot_hold() {
+        let replay = sample();
+        let offset = tick_count_offset(&replay);
+        let mut bytes = replay.encode().unwrap();
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_and_overshooting_runs() {
+        let replay = sample();
+        let runs = tick_count_offset(&replay) + 4;
+
+        let mut bytes = replay.encode().unwrap();
+        bytes[runs..runs + 2].copy_from_slice(&0u16.to_le_bytes());
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::BadRunLength)
+        ));
+
+        let mut bytes = replay.encode().unwrap();
+        bytes[runs..runs + 2].copy_from_slice(&1000u16.to_le_bytes());
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::BadRunLength)
+        ));
+    }
+
+    #[test]
+    fn rejects_ticks_without_fighters() {
+        let mut replay = sample();
+        replay.header.fighters.clear();
+        let mut bytes = replay.encode().unwrap();
+        let offset = bytes.len() - 4;
+        bytes[offset..].copy_from_slice(&5u32.to_le_bytes());
+        assert!(matches!(
+            Replay::decode(&bytes),
+            Err(ReplayError::BadRunLength)
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+pub mod components;
+pub mod format;
+mod systems;
+
+pub use components::{
+    MAX_REPLAY_SPEED, MIN_REPLAY_SPEED, ReplayCommand, ReplayPlayback, ReplayRecorder,
+    ReplayRestartMessage,
+};
+pub use format::{
+    DEFAULT_STAGE, REPLAY_EXTENSION, Replay, ReplayError, ReplayFighter, ReplayHeader, content_hash,
+};
+pub use systems::handle_replay_commands;
+
+use crate::{
+    GameMode, GameState,
+    gameplay::character::{input::sample_character_input, presentation::update_character_sprites},
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_message::<ReplayCommand>()
+            .add_message::<ReplayRestartMessage>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    systems::record_replay_inputs.after(sample_character_input),
+                    systems::feed_replay_inputs
+                        .before(sample_character_input)
+                        .run_if(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(
+                OnEnter(GameState::InGame),
+                systems::begin_playback.run_if(resource_exists::<ReplayPlayback>),
+            )
+            .add_systems(
+                Update,
+                handle_replay_commands.run_if(
+                    resource_equals(GameMode::Replay).and(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                systems::fast_forward_replay
+                    .before(update_character_sprites)
+                    .run_if(resource_exists::<ReplayPlayback>),
+            );
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::{app::FixedMain, prelude::*};
+
+use super::components::{
+    MAX_REPLAY_SPEED, MAX_SEEK_TICKS_PER_FRAME, MIN_REPLAY_SPEED, ReplayCommand, ReplayPlayback,
+    ReplayRecorder, ReplayRestartMessage,
+};
+use crate::gameplay::{
+    SimTick,
+    character::{
+        CharacterHitMessage,
+        input::{InputFrame, InputHistory, MockedInput},
+        locomotion::{
+            CharacterBlockedMessage, CharacterDashedMessage, CharacterGuardStateChangedMessage,
+            CharacterJumpedMessage, CharacterLandedMessage, CharacterPlatformDroppedMessage,
+            CharacterTurnedMessage,
+        },
+    },
+    versus::PlayerSlot,
+};
+
+/// Appends this tick's sampled input of every fighter to the active recording.
+pub fn record_replay_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    fighters: Query<(&PlayerSlot, &InputHistory)>,
+) {
+    let Some(replay) = recorder.replay_mut() else {
+        return;
+    };
+
+    let mut frames = vec![InputFrame::NONE; replay.fighter_count()];
+    let mut sampled = 0;
+    for (slot, history) in &fighters {
+        if let Some(frame) = frames.get_mut(slot.index()) {
+            *frame = history.latest();
+            sampled += 1;
+        }
+    }
+
+    // Fighters spawn a frame after recording starts; don't record ticks without them
+    if sampled == frames.len() {
+        replay.push_tick(&frames);
+    }
+}
+
+/// Starts the simulation clock from zero so replay ticks line up with `SimTick`.
+pub fn begin_playback(mut sim_tick: ResMut<SimTick>) {
+    *sim_tick = SimTick::default();
+}
+
+/// Drives each fighter's `MockedInput` from the replay tick under the cursor. It is
+/// recorded into `InputHistory` on this same tick, so seeks that run many ticks in one
+/// frame still simulate every recorded frame.
+pub fn feed_replay_inputs(
+    mut playback: ResMut<ReplayPlayback>,
+    mut fighters: Query<(&PlayerSlot, &mut MockedInput)>,
+) {
+    let cursor = playback.cursor;
+    let Some(frames) = playback.replay.tick(cursor) else {
+        return;
+    };
+
+    for (slot, mut mocked) in &mut fighters {
+        if let Some(&frame) = frames.get(slot.index()) {
+            mocked.0 = frame;
+        }
+    }
+
+    playback.cursor += 1;
+}
+
+/// Applies viewer commands and keeps virtual time in line with the playback state,
+/// so regular `FixedUpdate` ticks only run while the replay is actually playing.
+pub fn handle_replay_commands(
+    mut commands: MessageReader<ReplayCommand>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time: ResMut<Time<Virtual>>,
+    mut sim_tick: ResMut<SimTick>,
+    mut restart_writer: MessageWriter<ReplayRestartMessage>,
+) {
+    for command in commands.read() {
+        match *command {
+            ReplayCommand::TogglePause => playback.paused = !playback.paused,
+            ReplayCommand::Step => {
+                if playback.paused {
+                    playback.pending_steps += 1;
+                }
+            }
+            ReplayCommand::SetSpeed(speed) => {
+                playback.speed = speed.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+            }
+            ReplayCommand::Seek(tick) => {
+                let target = tick.min(playback.replay.tick_count());
+                if target < playback.cursor {
+                    restart(&mut playback, &mut sim_tick, &mut restart_writer);
+                }
+                playback.seek_target = Some(target);
+                playback.pending_steps = 0;
+            }
+            ReplayCommand::Rerun => {
+                restart(&mut playback, &mut sim_tick, &mut restart_writer);
+                playback.seek_target = Some(playback.replay.tick_count());
+                playback.pending_steps = 0;
+            }
+        }
+    }
+
+    time.set_relative_speed(playback.speed);
+    if playback.paused || playback.is_finished() || playback.is_seeking() {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
+
+/// Moves the cursor back to tick zero and asks for the fighters to be respawned.
+fn restart(
+    playback: &mut ReplayPlayback,
+    sim_tick: &mut SimTick,
+    restart_writer: &mut MessageWriter<ReplayRestartMessage>,
+) {
+    playback.cursor = 0;
+    *sim_tick = SimTick::default();
+    restart_writer.write(ReplayRestartMessage);
+}
+
+/// Runs the fixed simulation by hand for seeks and single steps, outside of the
+/// regular fixed timestep.
+pub fn fast_forward_replay(world: &mut World) {
+    let (ticks, seek_done) = {
+        let mut playback = world.resource_mut::<ReplayPlayback>();
+        let remaining =
+            playback.replay.tick_count() - playback.cursor.min(playback.replay.tick_count());
+
+        let (ticks, seek_done) = match playback.seek_target {
+            Some(target) => {
+                let ticks = target
+                    .saturating_sub(playback.cursor)
+                    .min(MAX_SEEK_TICKS_PER_FRAME);
+                (ticks, playback.cursor + ticks >= target)
+            }
+            None => (std::mem::take(&mut playback.pending_steps), false),
+        };
+        (ticks.min(remaining), seek_done)
+    };
+
+    // The seek stays active until its last tick ran, so frame events stay quiet throughout
+    if ticks == 0 {
+        if seek_done {
+            world.resource_mut::<ReplayPlayback>().seek_target = None;
+        }
+        return;
+    }
+
+    // Same context swap the fixed main loop does, so systems reading `Time` see the fixed clock
+    let fixed_time = world.resource::<Time<Fixed>>().as_generic();
+    *world.resource_mut::<Time>() = fixed_time;
+    for _ in 0..ticks {
+        world.run_schedule(FixedMain);
+    }
+    let virtual_time = world.resource::<Time<Virtual>>().as_generic();
+    *world.resource_mut::<Time>() = virtual_time;
+
+    // Single steps keep their sounds and shakes; the ticks skipped by a seek must not
+    if world.resource::<ReplayPlayback>().is_seeking() {
+        clear_simulation_messages(world);
+    }
+
+    if seek_done {
+        world.resource_mut::<ReplayPlayback>().seek_target = None;
+    }
+}
+
+/// Drops the messages the simulation sent during a seek once every tick has run. The
+/// simulation's own readers live in `FixedMain` and already saw them; presentation
+/// readers in `Update` would otherwise react to a whole seek's worth of action at once.
+fn clear_simulation_messages(world: &mut World) {
+    fn clear<M: Message>(world: &mut World) {
+        world.resource_mut::<Messages<M>>().clear();
+    }
+
+    clear::<CharacterJumpedMessage>(world);
+    clear::<CharacterLandedMessage>(world);
+    clear::<CharacterTurnedMessage>(world);
+    clear::<CharacterDashedMessage>(world);
+    clear::<CharacterGuardStateChangedMessage>(world);
+    clear::<CharacterBlockedMessage>(world);
+    clear::<CharacterPlatformDroppedMessage>(world);
+    clear::<CharacterHitMessage>(world);
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::gameplay::character::input::{InputFrame, MockedInput};
+
+/// Marks the character driven by the training dummy settings.
+#[derive(Component, Debug, Default)]
+#[require(MockedInput)]
+pub struct TrainingDummy;
+
+/// Idle behaviour of the training dummy while no recording is playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DummyMode {
+    #[default]
+    Stand,
+    Jump,
+    /// Holds guard, which is bound to crouching until guard gets its own input.
+    BlockAll,
+    /// Stands until hit, then guards for the rest of the string.
+    BlockAfterFirstHit,
+}
+
+impl DummyMode {
+    pub fn next(self) -> Self {
+        match self {
+            DummyMode::Stand => DummyMode::Jump,
+            DummyMode::Jump => DummyMode::BlockAll,
+            DummyMode::BlockAll => DummyMode::BlockAfterFirstHit,
+            DummyMode::BlockAfterFirstHit => DummyMode::Stand,
+        }
+    }
+}
+
+/// Practice-mode configuration.
+#[derive(Resource, Debug, Clone)]
+pub struct TrainingSettings {
+    pub dummy_mode: DummyMode,
+    /// Length of a dummy recording, in fixed ticks.
+    pub record_ticks: u16,
+    /// Repeat the recording until stopped instead of playing it once.
+    pub loop_playback: bool,
+    /// Where player one and player two are placed on reset, indexed by `PlayerSlot`.
+    pub reset_positions: [Vec2; 2],
+}
+
+impl Default for TrainingSettings {
+    fn default() -> Self {
+        Self {
+            dummy_mode: DummyMode::Stand,
+            record_ticks: 300,
+            loop_playback: false,
+            reset_positions: [Vec2::new(-200.0, 0.0), Vec2::new(200.0, 0.0)],
+        }
+    }
+}
+
+/// Requests issued by the training menu or hotkeys.
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub enum TrainingCommand {
+    SetDummyMode(DummyMode),
+    CycleDummyMode,
+    /// Start recording player one's inputs, or stop if already recording.
+    ToggleRecording,
+    /// Play the recording back on the dummy, or stop if already playing.
+    TogglePlayback,
+    /// Put both fighters back at their reset positions with full meters.
+    ResetPositions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RecorderState {
+    #[default]
+    Idle,
+    Recording,
+    Playing {
+        cursor: usize,
+    },
+}
+
+/// Dummy recording slot.
+///
+/// Frames are sampled from player one's `InputHistory` (the same values its actions
+/// produced) and stored facing-relative, so playback on the dummy facing the other
+/// way performs the same moves.
+#[derive(Resource, Debug, Default)]
+pub struct DummyRecorder {
+    pub state: RecorderState,
+    pub frames: Vec<InputFrame>,
+    /// Ticks left during which `BlockAfterFirstHit` keeps guarding.
+    pub(crate) guard_ticks: u16,
+}
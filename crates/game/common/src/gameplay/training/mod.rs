@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+pub mod components;
+mod systems;
+
+pub use components::{
+    DummyMode, DummyRecorder, RecorderState, TrainingCommand, TrainingDummy, TrainingSettings,
+};
+
+use crate::{GameMode, gameplay::character::input::sample_character_input};
+
+pub struct TrainingPlugin;
+
+impl Plugin for TrainingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrainingSettings>()
+            .init_resource::<DummyRecorder>()
+            .add_message::<TrainingCommand>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        systems::handle_training_commands,
+                        systems::drive_training_dummy,
+                    )
+                        .chain()
+                        .before(sample_character_input),
+                    systems::record_training_inputs.after(sample_character_input),
+                )
+                    .run_if(resource_equals(GameMode::Training)),
+            );
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::{ecs::message::MessageReader, prelude::*};
+
+use super::TrainingSettings;
+use super::components::{DummyMode, DummyRecorder, RecorderState, TrainingCommand, TrainingDummy};
+use crate::gameplay::{
+    character::{
+        Character, CharacterHitMessage, CombatState, Vitals,
+        input::{InputFrame, InputHistory, MockedInput},
+        locomotion::components::{
+            CharacterBlockedMessage, Facing, Locks, MoveState, MoveStats, PushVelocity,
+            SimPosition, Velocity,
+        },
+        presentation::CharacterAnimationState,
+    },
+    fixed::FxVec2,
+    versus::PlayerSlot,
+};
+
+/// Ticks the `BlockAfterFirstHit` dummy keeps guarding once out of the last hit or
+/// blockstun.
+const GUARD_AFTER_HIT_TICKS: u16 = 30;
+
+#[allow(clippy::type_complexity)]
+pub fn handle_training_commands(
+    mut commands_reader: MessageReader<TrainingCommand>,
+    mut settings: ResMut<TrainingSettings>,
+    mut recorder: ResMut<DummyRecorder>,
+    mut fighters: Query<
+        (
+            &PlayerSlot,
+            &mut SimPosition,
+            &mut Facing,
+            &mut Velocity,
+            &mut PushVelocity,
+            &mut MoveState,
+            &MoveStats,
+            &mut CombatState,
+            &mut Locks,
+            &mut Vitals,
+            Option<&mut CharacterAnimationState>,
+        ),
+        With<Character>,
+    >,
+) {
+    for command in commands_reader.read() {
+        match *command {
+            TrainingCommand::SetDummyMode(mode) => {
+                settings.dummy_mode = mode;
+            }
+            TrainingCommand::CycleDummyMode => {
+                settings.dummy_mode = settings.dummy_mode.next();
+                log::info!("Training dummy mode: {:?}", settings.dummy_mode);
+            }
+            TrainingCommand::ToggleRecording => {
+                if recorder.state == RecorderState::Recording {
+                    recorder.state = RecorderState::Idle;
+                } else {
+                    recorder.frames.clear();
+                    recorder.state = RecorderState::Recording;
+                }
+                log::info!("Training recorder: {:?}", recorder.state);
+            }
+            TrainingCommand::TogglePlayback => {
+                recorder.state = match recorder.state {
+                    RecorderState::Playing { .. } => RecorderState::Idle,
+                    _ if recorder.frames.is_empty() => RecorderState::Idle,
+                    _ => RecorderState::Playing { cursor: 0 },
+                };
+                log::info!("Training recorder: {:?}", recorder.state);
+            }
+            TrainingCommand::ResetPositions => {
+                if matches!(recorder.state, RecorderState::Playing { .. }) {
+                    recorder.state = RecorderState::Idle;
+                }
+                recorder.guard_ticks = 0;
+
+                let [p1, p2] = settings.reset_positions;
+                for (
+                    slot,
+                    mut sim_position,
+                    mut facing,
+                    mut velocity,
+                    mut push_velocity,
+                    mut state,
+                    stats,
+                    mut combat,
+                    mut locks,
+                    mut vitals,
+                    anim_state,
+                ) in &mut fighters
+                {
+                    let (position, other) = match slot {
+                        PlayerSlot::One => (p1, p2),
+                        PlayerSlot::Two => (p2, p1),
+                    };
+
+                    sim_position.0 = FxVec2::from_vec2(position);
+                    *facing = if position.x <= other.x {
+                        Facing::Right
+                    } else {
+                        Facing::Left
+                    };
+                    velocity.0 = FxVec2::ZERO;
+                    push_velocity.0 = FxVec2::ZERO;
+                    *state = MoveState {
+                        jumps_remaining: stats.max_jumps,
+                        ..default()
+                    };
+                    *combat = CombatState::default();
+                    *locks = Locks::default();
+                    vitals.refill();
+
+                    if let Some(mut anim_state) = anim_state {
+                        *anim_state = CharacterAnimationState::default();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Captures player one's input while recording. Runs after `sample_character_input`,
+/// so the frame stored is the one simulated this tick.
+pub fn record_training_inputs(
+    settings: Res<TrainingSettings>,
+    mut recorder: ResMut<DummyRecorder>,
+    players: Query<(&PlayerSlot, &InputHistory, &Facing), Without<TrainingDummy>>,
+) {
+    if recorder.state != RecorderState::Recording {
+        return;
+    }
+
+    let sampled = players
+        .iter()
+        .find(|(slot, _, _)| **slot == PlayerSlot::One)
+        .map(|(_, history, facing)| to_facing_relative(history.latest(), *facing));
+
+    if let Some(frame) = sampled {
+        recorder.frames.push(frame);
+    }
+
+    let max_ticks = usize::from(settings.record_ticks);
+    if recorder.frames.len() >= max_ticks {
+        recorder.state = RecorderState::Idle;
+        log::info!(
+            "Training recorder: captured {} ticks",
+            recorder.frames.len()
+        );
+    }
+}
+
+/// Feeds the dummy its next input: recorded playback if active, otherwise the idle
+/// behaviour selected by `DummyMode`.
+pub fn drive_training_dummy(
+    settings: Res<TrainingSettings>,
+    mut recorder: ResMut<DummyRecorder>,
+    mut hits: MessageReader<CharacterHitMessage>,
+    mut blocks: MessageReader<CharacterBlockedMessage>,
+    mut dummies: Query<
+        (Entity, &mut MockedInput, &Facing, &MoveState, &CombatState),
+        With<TrainingDummy>,
+    >,
+) {
+    let dummy_entities: Vec<Entity> = dummies.iter().map(|(entity, ..)| entity).collect();
+    let struck = hits
+        .read()
+        .map(|msg| msg.defender)
+        .chain(blocks.read().map(|msg| msg.defender))
+        .any(|defender| dummy_entities.contains(&defender));
+    if struck {
+        recorder.guard_ticks = GUARD_AFTER_HIT_TICKS;
+    }
+
+    for (_, mut mocked, facing, state, _) in &mut dummies {
+        if let RecorderState::Playing { cursor } = recorder.state {
+            let Some(&frame) = recorder.frames.get(cursor) else {
+                recorder.state = RecorderState::Idle;
+                continue;
+            };
+            mocked.0 = to_facing_relative(frame, *facing);
+
+            let next = cursor + 1;
+            recorder.state = if next < recorder.frames.len() {
+                RecorderState::Playing { cursor: next }
+            } else if settings.loop_playback {
+                RecorderState::Playing { cursor: 0 }
+            } else {
+                RecorderState::Idle
+            };
+            continue;
+        }
+
+        mocked.0 = match settings.dummy_mode {
+            DummyMode::Stand => InputFrame::NONE,
+            DummyMode::BlockAll => InputFrame::DOWN,
+            DummyMode::Jump => {
+                // Tap jump on every grounded tick, releasing in between so it re-triggers
+                if state.grounded && !mocked.0.contains(InputFrame::JUMP) {
+                    InputFrame::JUMP
+                } else {
+                    InputFrame::NONE
+                }
+            }
+            DummyMode::BlockAfterFirstHit => {
+                if recorder.guard_ticks > 0 {
+                    InputFrame::DOWN
+                } else {
+                    InputFrame::NONE
+                }
+            }
+        };
+    }
+
+    let stunned = dummies
+        .iter()
+        .any(|(.., combat)| combat.hitstun_ticks > 0 || combat.blockstun_ticks > 0);
+    if !stunned {
+        recorder.guard_ticks = recorder.guard_ticks.saturating_sub(1);
+    }
+}
+
+/// Converts between screen-relative and facing-relative input (forward is `RIGHT`).
+/// The conversion is its own inverse.
+fn to_facing_relative(frame: InputFrame, facing: Facing) -> InputFrame {
+    match facing {
+        Facing::Right => frame,
+        Facing::Left => frame.mirrored(),
+    }
+}
@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+
+use crate::gameplay::{
+    SIM_TICK_HZ,
+    character::{
+        Character, CharacterInput, CharacterLocomotion,
+        locomotion::{Facing, FacingMode},
+        presentation::{CharacterAnimationState, CharacterManifestAsset, CharacterManifestHandle},
+    },
+};
+
+/// Horizontal distance of each fighter from the arena centre at round start.
+pub const VERSUS_START_OFFSET_X: f32 = 200.0;
+/// Rounds a fighter has to win to take the match.
+pub const ROUNDS_TO_WIN: u8 = 2;
+/// Length of a round (99 seconds). When time runs out, more health left wins the round.
+pub const ROUND_DURATION_TICKS: u32 = 99 * SIM_TICK_HZ;
+/// Pause between a round being decided and the next one starting.
+pub const ROUND_OVER_TICKS: u32 = 2 * SIM_TICK_HZ;
+/// Longest a match can run. Every round scores for at least one fighter, so someone has
+/// won the match after `2 * ROUNDS_TO_WIN - 1` rounds.
+pub const MAX_MATCH_TICKS: u32 =
+    (2 * ROUNDS_TO_WIN as u32 - 1) * (ROUND_DURATION_TICKS + ROUND_OVER_TICKS);
+
+/// Local player slot a character is controlled from.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PlayerSlot {
+    One,
+    Two,
+}
+
+impl PlayerSlot {
+    pub const ALL: [PlayerSlot; 2] = [PlayerSlot::One, PlayerSlot::Two];
+
+    pub fn index(self) -> usize {
+        match self {
+            PlayerSlot::One => 0,
+            PlayerSlot::Two => 1,
+        }
+    }
+
+    /// Where this slot's fighter stands when a versus round starts.
+    pub fn start_position(self) -> Vec2 {
+        match self {
+            PlayerSlot::One => Vec2::new(-VERSUS_START_OFFSET_X, 0.0),
+            PlayerSlot::Two => Vec2::new(VERSUS_START_OFFSET_X, 0.0),
+        }
+    }
+}
+
+/// What the simulation needs of a versus fighter standing at `position`, before its
+/// input source is attached. Clients and the server spawn the same components, so they
+/// simulate the same fight.
+pub fn fighter_bundle(
+    slot: PlayerSlot,
+    position: Vec2,
+    manifest: Handle<CharacterManifestAsset>,
+) -> impl Bundle {
+    let facing = match slot {
+        PlayerSlot::One => Facing::Right,
+        PlayerSlot::Two => Facing::Left,
+    };
+
+    (
+        Transform::from_translation(position.extend(0.0)).with_scale(Vec3::splat(2.0)),
+        CharacterManifestHandle(manifest),
+        Character,
+        CharacterLocomotion::with_facing_mode(FacingMode::Opponent),
+        slot,
+        facing,
+        CharacterInput,
+        CharacterAnimationState::default(),
+    )
+}
+
+/// The character this fighter is currently facing off against.
+/// Linked automatically once both versus slots are occupied.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opponent(pub Entity);
+
+/// Where a versus match is between rounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RoundPhase {
+    #[default]
+    Fighting,
+    /// The round was decided; the fighters stand frozen until the next one.
+    RoundOver,
+    /// A fighter reached `ROUNDS_TO_WIN`. The fighters stay frozen.
+    MatchOver,
+}
+
+/// Score and clock of a versus match, played to `ROUNDS_TO_WIN` rounds. A round ends
+/// when a fighter is knocked out or its time runs out, and goes to whoever has more
+/// health left; a round ending level counts for both fighters.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VersusRounds {
+    /// Rounds won, indexed by `PlayerSlot`.
+    pub wins: [u8; 2],
+    pub phase: RoundPhase,
+    /// Ticks spent in the current phase.
+    pub phase_ticks: u32,
+}
+
+impl VersusRounds {
+    /// Advances the clock by one tick, deciding the round from the fighters' `health`
+    /// (indexed by `PlayerSlot`). Returns `true` when a new round starts this tick and
+    /// the fighters have to be put back in place.
+    pub fn advance(&mut self, health: [u16; 2]) -> bool {
+        self.phase_ticks += 1;
+        match self.phase {
+            RoundPhase::Fighting => {
+                if health.contains(&0) || self.phase_ticks >= ROUND_DURATION_TICKS {
+                    for slot in PlayerSlot::ALL {
+                        if leader(health).is_none_or(|leader| leader == slot) {
+                            self.wins[slot.index()] += 1;
+                        }
+                    }
+                    self.phase = if self.wins.contains(&ROUNDS_TO_WIN) {
+                        RoundPhase::MatchOver
+                    } else {
+                        RoundPhase::RoundOver
+                    };
+                    self.phase_ticks = 0;
+                }
+                false
+            }
+            RoundPhase::RoundOver if self.phase_ticks >= ROUND_OVER_TICKS => {
+                self.phase = RoundPhase::Fighting;
+                self.phase_ticks = 0;
+                true
+            }
+            RoundPhase::RoundOver | RoundPhase::MatchOver => false,
+        }
+    }
+
+    pub fn is_fighting(&self) -> bool {
+        self.phase == RoundPhase::Fighting
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.phase == RoundPhase::MatchOver
+    }
+
+    /// Winner of a finished match. `None` while it runs, or when both fighters won
+    /// their last round together.
+    pub fn winner(&self) -> Option<PlayerSlot> {
+        if self.is_over() {
+            leader(self.wins.map(u16::from))
+        } else {
+            None
+        }
+    }
+
+    /// Whole seconds left on the round clock, rounded up.
+    pub fn seconds_left(&self) -> u32 {
+        match self.phase {
+            RoundPhase::Fighting => (ROUND_DURATION_TICKS
+                - self.phase_ticks.min(ROUND_DURATION_TICKS))
+            .div_ceil(SIM_TICK_HZ),
+            RoundPhase::RoundOver | RoundPhase::MatchOver => 0,
+        }
+    }
+}
+
+/// The slot with the higher of two scores, `None` when level.
+fn leader(scores: [u16; 2]) -> Option<PlayerSlot> {
+    match scores[0].cmp(&scores[1]) {
+        Ordering::Greater => Some(PlayerSlot::One),
+        Ordering::Less => Some(PlayerSlot::Two),
+        Ordering::Equal => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a round to its end with `health` from the first tick.
+    fn play_round(rounds: &mut VersusRounds, health: [u16; 2]) {
+        while rounds.is_fighting() {
+            assert!(!rounds.advance(health));
+        }
+    }
+
+    /// Waits out the round-over pause, returning whether the next round started.
+    fn next_round(rounds: &mut VersusRounds) -> bool {
+        (0..ROUND_OVER_TICKS).any(|_| rounds.advance([1000, 1000]))
+    }
+
+    #[test]
+    fn knockout_ends_the_round_at_once() {
+        let mut rounds = VersusRounds::default();
+        assert!(!rounds.advance([1000, 1000]));
+        assert!(!rounds.advance([1000, 0]));
+        assert_eq!(rounds.phase, RoundPhase::RoundOver);
+        assert_eq!(rounds.wins, [1, 0]);
+        assert_eq!(rounds.seconds_left(), 0);
+
+        // The pause runs its full length before the fighters are reset
+        for _ in 1..ROUND_OVER_TICKS {
+            assert!(!rounds.advance([1000, 0]));
+        }
+        assert!(rounds.advance([1000, 0]));
+        assert!(rounds.is_fighting());
+        assert_eq!(rounds.seconds_left(), 99);
+    }
+
+    #[test]
+    fn time_out_goes_to_more_health() {
+        let mut rounds = VersusRounds::default();
+        for tick in 1..ROUND_DURATION_TICKS {
+            rounds.advance([400, 500]);
+            assert!(rounds.is_fighting(), "decided early on tick {tick}");
+        }
+        assert_eq!(rounds.seconds_left(), 1);
+        rounds.advance([400, 500]);
+        assert_eq!(rounds.wins, [0, 1]);
+    }
+
+    #[test]
+    fn first_to_two_rounds_wins_the_match() {
+        let mut rounds = VersusRounds::default();
+        play_round(&mut rounds, [0, 10]);
+        assert!(next_round(&mut rounds));
+        play_round(&mut rounds, [10, 0]);
+        assert!(next_round(&mut rounds));
+        assert_eq!(rounds.winner(), None);
+        play_round(&mut rounds, [0, 10]);
+
+        assert!(rounds.is_over());
+        assert_eq!(rounds.wins, [1, 2]);
+        assert_eq!(rounds.winner(), Some(PlayerSlot::Two));
+        assert!(!next_round(&mut rounds), "the match never restarts");
+    }
+
+    #[test]
+    fn level_rounds_count_for_both() {
+        let mut rounds = VersusRounds::default();
+        play_round(&mut rounds, [0, 0]);
+        assert_eq!(rounds.wins, [1, 1]);
+        assert!(next_round(&mut rounds));
+
+        // Both reach two rounds together: a drawn match
+        play_round(&mut rounds, [300, 300]);
+        assert!(rounds.is_over());
+        assert_eq!(rounds.wins, [2, 2]);
+        assert_eq!(rounds.winner(), None);
+    }
+}
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+pub mod components;
+mod systems;
+
+pub use components::{
+    MAX_MATCH_TICKS, Opponent, PlayerSlot, ROUND_DURATION_TICKS, ROUND_OVER_TICKS, ROUNDS_TO_WIN,
+    RoundPhase, VersusRounds, fighter_bundle,
+};
+
+use crate::gameplay::character::presentation::sync_character_transforms;
+
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, systems::link_opponents)
+            .add_systems(
+                FixedPostUpdate,
+                systems::update_rounds
+                    .before(sync_character_transforms)
+                    .run_if(resource_exists::<VersusRounds>),
+            );
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use super::components::{Opponent, PlayerSlot, VersusRounds};
+use crate::gameplay::{
+    character::{
+        Character, CombatState, Vitals,
+        locomotion::components::{
+            Facing, Locks, MoveState, MoveStats, PushVelocity, SimPosition, Velocity,
+        },
+        presentation::CharacterAnimationState,
+    },
+    fixed::FxVec2,
+};
+
+/// Pairs the two occupied player slots as each other's opponent, and unlinks
+/// fighters whose opponent has left the match.
+pub fn link_opponents(
+    mut commands: Commands,
+    fighters: Query<(Entity, Option<&Opponent>), (With<Character>, With<PlayerSlot>)>,
+) {
+    let entities: Vec<Entity> = fighters.iter().map(|(entity, _)| entity).collect();
+
+    for (entity, opponent) in &fighters {
+        let target = if entities.len() == 2 {
+            entities.iter().copied().find(|&other| other != entity)
+        } else {
+            None
+        };
+
+        match (target, opponent) {
+            (Some(target), Some(current)) if current.0 == target => {}
+            (Some(target), _) => {
+                commands.entity(entity).insert(Opponent(target));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Opponent>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Runs the round clock of a versus match. Fighters are frozen from the deciding tick
+/// until the next round, then put back at their start positions with full health.
+#[allow(clippy::type_complexity)]
+pub fn update_rounds(
+    mut rounds: ResMut<VersusRounds>,
+    mut fighters: Query<
+        (
+            &PlayerSlot,
+            &mut SimPosition,
+            &mut Facing,
+            &mut Velocity,
+            &mut PushVelocity,
+            &mut MoveState,
+            &MoveStats,
+            &mut CombatState,
+            &mut Locks,
+            &mut Vitals,
+            Option<&mut CharacterAnimationState>,
+        ),
+        With<Character>,
+    >,
+) {
+    let mut health = [None; 2];
+    for (slot, .., vitals, _) in &fighters {
+        health[slot.index()] = Some(vitals.health);
+    }
+    let [Some(one), Some(two)] = health else {
+        return;
+    };
+
+    let new_round = rounds.advance([one, two]);
+    let frozen = !rounds.is_fighting();
+
+    for (
+        slot,
+        mut position,
+        mut facing,
+        mut velocity,
+        mut push_velocity,
+        mut state,
+        stats,
+        mut combat,
+        mut locks,
+        mut vitals,
+        anim_state,
+    ) in &mut fighters
+    {
+        if new_round {
+            position.0 = FxVec2::from_vec2(slot.start_position());
+            *facing = match slot {
+                PlayerSlot::One => Facing::Right,
+                PlayerSlot::Two => Facing::Left,
+            };
+            velocity.0 = FxVec2::ZERO;
+            push_velocity.0 = FxVec2::ZERO;
+            *state = MoveState {
+                jumps_remaining: stats.max_jumps,
+                ..default()
+            };
+            *combat = CombatState::default();
+            *locks = Locks::default();
+            vitals.health = vitals.max_health;
+
+            if let Some(mut anim_state) = anim_state {
+                *anim_state = CharacterAnimationState::default();
+            }
+        }
+
+        locks.move_locked = frozen;
+        locks.jump_locked = frozen;
+    }
+}
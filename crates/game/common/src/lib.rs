@@ -1,10 +1,12 @@
 pub mod gameplay;
+pub mod net;
 
 use bevy::app::App;
 use bevy::ecs::VariantDefaults;
 use bevy::prelude::*;
 
 pub mod prelude {
+    pub use crate::GameMode;
     pub use crate::GamePlugin;
     pub use crate::GameState;
     pub use crate::gameplay::{
@@ -20,9 +22,11 @@ pub mod prelude {
             presentation::{
                 AnimationFrame, AnimationPlaybackFlags, AnimationVideoSettings,
                 CharacterAnimationClip, CharacterAnimationState, CharacterManifestAsset,
-                CharacterManifestHandle, InterpolationMode, LoopMode, PresentationPlugin,
+                CharacterManifestHandle, CharacterPalette, CharacterRosterAsset, InterpolationMode,
+                LoopMode, PresentationPlugin, RosterCharacter,
             },
         },
+        versus::{Opponent, PlayerSlot, VersusPlugin},
     };
 }
 
@@ -36,10 +40,25 @@ pub enum GameState {
     GameEnd,
 }
 
+/// Which kind of session the simulation is currently running.
+#[derive(Resource, Default, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub enum GameMode {
+    /// Single character roaming the page background.
+    #[default]
+    Sandbox,
+    /// Two local players fighting each other.
+    Versus,
+    /// Player one practising against a configurable dummy.
+    Training,
+    /// A recorded match re-simulated from its inputs.
+    Replay,
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(gameplay::GameplayPlugin);
+        app.init_resource::<GameMode>()
+            .add_plugins(gameplay::GameplayPlugin);
     }
 }
@@ -0,0 +1,318 @@
+//! Minimal byte codec for the wire protocol: little-endian fixed-width integers,
+//! LEB128 varints and zigzag-encoded signed values.
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    #[error("message ended early")]
+    Truncated,
+    #[error("varint is longer than 64 bits")]
+    VarintOverflow,
+    #[error("unknown {kind} tag {tag}")]
+    InvalidTag { kind: &'static str, tag: u8 },
+    #[error("text is not valid UTF-8")]
+    InvalidText,
+    #[error("protocol version {found} does not match {expected}")]
+    VersionMismatch { expected: u16, found: u16 },
+    #[error("{0} trailing bytes after message")]
+    TrailingBytes(usize),
+    #[error("length {0} exceeds the message limit")]
+    TooLong(u64),
+}
+
+#[derive(Debug, Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes.push(value);
+        self
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.u8(value as u8)
+    }
+
+    pub fn u16(&mut self, value: u16) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn varint(&mut self, mut value: u64) -> &mut Self {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return self;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    /// Signed varint; small magnitudes of either sign stay small.
+    pub fn zigzag(&mut self, value: i64) -> &mut Self {
+        self.varint(((value << 1) ^ (value >> 63)) as u64)
+    }
+
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value);
+        self
+    }
+
+    pub fn str(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+}
+
+#[derive(Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Fails unless every byte has been consumed.
+    pub fn finish(&self) -> Result<(), DecodeError> {
+        match self.bytes.len() {
+            0 => Ok(()),
+            n => Err(DecodeError::TrailingBytes(n)),
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let (head, tail) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(DecodeError::Truncated)?;
+        self.bytes = tail;
+        Ok(*head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, DecodeError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    pub fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            // The tenth byte holds only the top bit; anything more would be shifted out
+            if shift == 63 && byte > 1 {
+                return Err(DecodeError::VarintOverflow);
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::VarintOverflow)
+    }
+
+    pub fn zigzag(&mut self) -> Result<i64, DecodeError> {
+        let value = self.varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Varint narrowed to `u16`, rejecting larger values.
+    pub fn varint_u16(&mut self) -> Result<u16, DecodeError> {
+        let value = self.varint()?;
+        u16::try_from(value).map_err(|_| DecodeError::TooLong(value))
+    }
+
+    pub fn varint_u32(&mut self) -> Result<u32, DecodeError> {
+        let value = self.varint()?;
+        u32::try_from(value).map_err(|_| DecodeError::TooLong(value))
+    }
+
+    pub fn zigzag_i32(&mut self) -> Result<i32, DecodeError> {
+        let value = self.zigzag()?;
+        i32::try_from(value).map_err(|_| DecodeError::TooLong(value.unsigned_abs()))
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = self.varint()?;
+        let len_usize = usize::try_from(len).map_err(|_| DecodeError::TooLong(len))?;
+        if len_usize > self.bytes.len() {
+            return Err(DecodeError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(len_usize);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn str(&mut self) -> Result<&'a str, DecodeError> {
+        std::str::from_utf8(self.bytes()?).map_err(|_| DecodeError::InvalidText)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(write: impl FnOnce(&mut Writer)) -> Vec<u8> {
+        let mut w = Writer::new();
+        write(&mut w);
+        w.into_bytes()
+    }
+
+    #[test]
+    fn varints_round_trip_at_byte_boundaries() {
+        for (value, len) in [
+            (0, 1),
+            (0x7f, 1),
+            (0x80, 2),
+            (0x3fff, 2),
+            (0x4000, 3),
+            (u64::from(u32::MAX), 5),
+            (u64::MAX, 10),
+        ] {
+            let bytes = encoded(|w| {
+                w.varint(value);
+            });
+            assert_eq!(bytes.len(), len, "{value:#x}");
+            let mut r = Reader::new(&bytes);
+            assert_eq!(r.varint(), Ok(value));
+            assert_eq!(r.finish(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn zigzag_keeps_small_magnitudes_small() {
+        for (value, zigzagged) in [(0, 0), (-1, 1), (1, 2), (-2, 3), (63, 126), (-64, 127)] {
+            assert_eq!(
+                encoded(|w| {
+                    w.zigzag(value);
+                }),
+                [zigzagged]
+            );
+        }
+        for value in [i64::MIN, i64::MIN + 1, i64::MAX] {
+            let bytes = encoded(|w| {
+                w.zigzag(value);
+            });
+            assert_eq!(Reader::new(&bytes).zigzag(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn rejects_overlong_varints() {
+        let mut too_long = vec![0x80; 10];
+        too_long.push(0);
+        assert_eq!(
+            Reader::new(&too_long).varint(),
+            Err(DecodeError::VarintOverflow)
+        );
+
+        // Ten bytes, but the last one carries bits past the 64th
+        let mut overflowing = vec![0xff; 9];
+        overflowing.push(0x02);
+        assert_eq!(
+            Reader::new(&overflowing).varint(),
+            Err(DecodeError::VarintOverflow)
+        );
+    }
+
+    #[test]
+    fn narrowing_rejects_out_of_range_values() {
+        let bytes = encoded(|w| {
+            w.varint(u64::from(u16::MAX) + 1);
+        });
+        assert_eq!(
+            Reader::new(&bytes).varint_u16(),
+            Err(DecodeError::TooLong(0x1_0000))
+        );
+
+        let bytes = encoded(|w| {
+            w.varint(u64::from(u32::MAX) + 1);
+        });
+        assert!(Reader::new(&bytes).varint_u32().is_err());
+
+        for value in [i64::from(i32::MAX) + 1, i64::from(i32::MIN) - 1] {
+            let bytes = encoded(|w| {
+                w.zigzag(value);
+            });
+            assert!(Reader::new(&bytes).zigzag_i32().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn reports_truncated_input() {
+        assert_eq!(Reader::new(&[]).u8(), Err(DecodeError::Truncated));
+        assert_eq!(Reader::new(&[1]).u16(), Err(DecodeError::Truncated));
+        assert_eq!(Reader::new(&[1, 2, 3]).u32(), Err(DecodeError::Truncated));
+        assert_eq!(Reader::new(&[0xff; 7]).u64(), Err(DecodeError::Truncated));
+        assert_eq!(Reader::new(&[0x80]).varint(), Err(DecodeError::Truncated));
+        // Length prefix promises more than is left
+        assert_eq!(
+            Reader::new(&[3, b'a', b'b']).str(),
+            Err(DecodeError::Truncated)
+        );
+
+        let huge = encoded(|w| {
+            w.varint(u64::MAX);
+        });
+        assert!(Reader::new(&huge).bytes().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_text_and_trailing_bytes() {
+        assert_eq!(
+            Reader::new(&[2, 0xc3, 0x28]).str(),
+            Err(DecodeError::InvalidText)
+        );
+
+        let bytes = encoded(|w| {
+            w.str("naruto").bool(true).u16(7);
+        });
+        let mut r = Reader::new(&bytes);
+        assert_eq!(r.str(), Ok("naruto"));
+        assert_eq!(r.finish(), Err(DecodeError::TrailingBytes(3)));
+        assert_eq!(r.bool(), Ok(true));
+        assert_eq!(r.u16(), Ok(7));
+        assert_eq!(r.remaining(), 0);
+    }
+}
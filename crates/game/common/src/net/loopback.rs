@@ -0,0 +1,41 @@
+//! In-memory transport: both ends live in the same process and exchange messages
+//! through channels, with no serialization beyond the protocol itself.
+
+use async_channel::Sender;
+
+use super::transport::{Connection, Frame, Listener, TransportKind};
+
+/// Two connected ends; whatever one sends, the other receives.
+pub fn loopback_pair() -> (Connection, Connection) {
+    let (a_to_b, b_from_a) = async_channel::unbounded();
+    let (b_to_a, a_from_b) = async_channel::unbounded();
+
+    let _ = a_to_b.try_send(Frame::Opened);
+    let _ = b_to_a.try_send(Frame::Opened);
+
+    (
+        Connection::from_channels(TransportKind::Loopback, a_to_b, a_from_b),
+        Connection::from_channels(TransportKind::Loopback, b_to_a, b_from_a),
+    )
+}
+
+/// Client side of an in-memory server, see `loopback_server`.
+#[derive(Debug, Clone)]
+pub struct LoopbackConnector {
+    accepted: Sender<Connection>,
+}
+
+impl LoopbackConnector {
+    /// Opens a new connection whose other end shows up in the paired `Listener`.
+    pub fn connect(&self) -> Connection {
+        let (client, server) = loopback_pair();
+        let _ = self.accepted.try_send(server);
+        client
+    }
+}
+
+/// A listener plus a connector that feeds it, for running a server in-process.
+pub fn loopback_server() -> (Listener, LoopbackConnector) {
+    let (listener, accepted) = Listener::new();
+    (listener, LoopbackConnector { accepted })
+}
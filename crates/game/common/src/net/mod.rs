@@ -0,0 +1,59 @@
+//! Networking shared by the game client and server: a message-oriented `Connection`
+//! over WebTransport, WebSocket or an in-memory loopback, and the versioned binary
+//! protocol spoken over it.
+
+pub mod codec;
+pub mod loopback;
+pub mod protocol;
+mod runtime;
+pub mod transport;
+pub mod websocket;
+pub mod webtransport;
+
+pub use loopback::{LoopbackConnector, loopback_pair, loopback_server};
+pub use protocol::{
+    ClientMessage, LobbyEvent, LobbyRequest, PROTOCOL_VERSION, PlayerKey, ResumeToken, RoomInfo,
+    RoomPhase, SeatInfo, ServerMessage, SessionId,
+};
+pub use transport::{
+    Channel, Connection, ConnectionState, Listener, TransportError, TransportKind,
+};
+
+/// Where a game server can be reached over each transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAddress {
+    /// `https://host:4433` WebTransport endpoint.
+    pub webtransport_url: String,
+    /// `ws(s)://host/...` WebSocket endpoint, used when WebTransport is unavailable.
+    pub websocket_url: String,
+    /// SHA-256 digest pinning a self-signed WebTransport certificate.
+    pub certificate_hash: Option<[u8; 32]>,
+}
+
+impl ServerAddress {
+    /// The server's default ports on `host`. `secure` picks `wss://` for the WebSocket
+    /// fallback, as required by pages served over HTTPS.
+    pub fn from_host(host: &str, secure: bool) -> Self {
+        let scheme = if secure { "wss" } else { "ws" };
+        Self {
+            webtransport_url: format!("https://{host}:{}", webtransport::DEFAULT_PORT),
+            websocket_url: format!("{scheme}://{host}:{}", websocket::DEFAULT_PORT),
+            certificate_hash: None,
+        }
+    }
+
+    /// Connects over WebTransport where the platform supports it, WebSocket otherwise.
+    /// A WebTransport attempt that ends up `failed_to_open` should be retried with
+    /// `connect_fallback`.
+    pub fn connect(&self) -> Connection {
+        if webtransport::is_supported() {
+            webtransport::connect(&self.webtransport_url, self.certificate_hash)
+        } else {
+            self.connect_fallback()
+        }
+    }
+
+    pub fn connect_fallback(&self) -> Connection {
+        websocket::connect(&self.websocket_url)
+    }
+}
@@ -0,0 +1,881 @@
+//! Versioned binary messages exchanged between game clients and the server.
+//!
+//! Every packet starts with the `PROTOCOL_VERSION` (u16) and a one-byte message tag.
+
+use crate::gameplay::{character::input::InputFrame, versus::PlayerSlot};
+
+use super::{
+    codec::{DecodeError, Reader, Writer},
+    transport::Channel,
+};
+
+/// Bumped on any incompatible change to the encoding below.
+pub const PROTOCOL_VERSION: u16 = 5;
+
+/// Inputs for the newest tick plus this many earlier ones ride along in every input
+/// packet, so a lost datagram is covered by the next one.
+pub const INPUT_REDUNDANCY: usize = 8;
+
+/// Identifies one client connection on the server.
+pub type SessionId = u64;
+
+/// Secret handed out in `Welcome` that lets a dropped client reclaim its session.
+pub type ResumeToken = u64;
+
+/// Secret identity handed out in `Welcome`. Sending it back in a later `Hello` keeps the
+/// player's rating and match record; the name is only what others see.
+pub type PlayerKey = u64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    /// First message on every connection. `resume` reclaims a session that dropped
+    /// within the server's reconnection grace period; `player` is the key from an
+    /// earlier `Welcome`, `None` to play under a new identity.
+    Hello {
+        version: u16,
+        name: String,
+        resume: Option<ResumeToken>,
+        player: Option<PlayerKey>,
+    },
+    /// Inputs for `tick` and the ticks just before it, oldest first.
+    Input {
+        tick: u32,
+        frames: Vec<InputFrame>,
+    },
+    Lobby(LobbyRequest),
+    Ping {
+        nonce: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    Welcome {
+        session: SessionId,
+        resume_token: ResumeToken,
+        player: PlayerKey,
+        tick: u32,
+    },
+    /// Confirmed input of every fighter for one match tick. Spectators get these
+    /// behind the players by the server's spectator delay.
+    Inputs {
+        tick: u32,
+        frames: Vec<(PlayerSlot, InputFrame)>,
+    },
+    Lobby(LobbyEvent),
+    Pong {
+        nonce: u32,
+    },
+    /// Starts a spectator on the current match: `Replay::encode` of its first ticks.
+    /// `SpectateChunk`s bring it up to the delayed tick, to re-simulate before following
+    /// the `Inputs` stream.
+    SpectateStart {
+        replay: Vec<u8>,
+    },
+    /// `Replay::encode` of the catch-up ticks that follow the first `start` ones.
+    SpectateChunk {
+        start: u32,
+        replay: Vec<u8>,
+    },
+    /// Sent right before the server closes the connection.
+    Disconnect {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LobbyRequest {
+    /// Opens a private room; its code comes back in `RoomJoined`.
+    CreateRoom,
+    JoinRoom {
+        code: String,
+    },
+    /// Waits for an opponent of similar rating, preferring the given region tag.
+    QuickMatch {
+        region: String,
+    },
+    CancelQuickMatch,
+    LeaveRoom,
+    SelectCharacter {
+        manifest_id: String,
+    },
+    /// Confirms the selected character.
+    LockIn,
+    RematchVote {
+        rematch: bool,
+    },
+    /// Watches a room's matches without taking a seat.
+    Spectate {
+        code: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LobbyEvent {
+    RoomJoined {
+        code: String,
+        slot: PlayerSlot,
+    },
+    RoomLeft,
+    Rejected {
+        reason: String,
+    },
+    Queued {
+        region: String,
+    },
+    QueueCancelled,
+    /// Sent whenever a room's phase or seats change.
+    RoomUpdate(RoomInfo),
+    MatchStarted {
+        seed: u64,
+        fighters: Vec<(PlayerSlot, String)>,
+    },
+    MatchEnded {
+        winner: Option<PlayerSlot>,
+    },
+    /// Now watching the room; `SpectateStart` follows whenever a match is running.
+    Spectating {
+        code: String,
+    },
+}
+
+/// Lifecycle of a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoomPhase {
+    /// Waiting for a second player.
+    Waiting,
+    CharacterSelect,
+    InMatch,
+    /// Match over, waiting for both players to vote on a rematch.
+    PostMatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomInfo {
+    pub code: String,
+    pub phase: RoomPhase,
+    pub seats: Vec<SeatInfo>,
+    pub spectators: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatInfo {
+    pub slot: PlayerSlot,
+    pub name: String,
+    pub character: Option<String>,
+    pub locked_in: bool,
+    /// False while the player is inside the reconnection grace period.
+    pub connected: bool,
+    pub rematch: Option<bool>,
+}
+
+impl ClientMessage {
+    /// Inputs are resent redundantly every tick; everything else must arrive.
+    pub fn channel(&self) -> Channel {
+        match self {
+            ClientMessage::Input { .. } => Channel::Unreliable,
+            _ => Channel::Reliable,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.u16(PROTOCOL_VERSION);
+        match self {
+            ClientMessage::Hello {
+                version,
+                name,
+                resume,
+                player,
+            } => {
+                w.u8(0).u16(*version).str(name);
+                write_option(&mut w, *resume, |w, token| {
+                    w.u64(token);
+                });
+                write_option(&mut w, *player, |w, key| {
+                    w.u64(key);
+                });
+            }
+            ClientMessage::Input { tick, frames } => {
+                w.u8(1).u32(*tick).varint(frames.len() as u64);
+                for frame in frames {
+                    w.u16(frame.bits());
+                }
+            }
+            ClientMessage::Lobby(request) => {
+                w.u8(2);
+                request.write(&mut w);
+            }
+            ClientMessage::Ping { nonce } => {
+                w.u8(3).u32(*nonce);
+            }
+        }
+        w.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let message = match read_header(&mut r)? {
+            0 => ClientMessage::Hello {
+                version: r.u16()?,
+                name: r.str()?.to_owned(),
+                resume: read_option(&mut r, Reader::u64)?,
+                player: read_option(&mut r, Reader::u64)?,
+            },
+            1 => {
+                let tick = r.u32()?;
+                let len = r.varint()?;
+                if len > INPUT_REDUNDANCY as u64 + 1 {
+                    return Err(DecodeError::TooLong(len));
+                }
+                // Unknown bits are rejected rather than masked: no honest client sets them.
+                // They can only sit in the low byte, the high one is all stick tilt.
+                let frames = (0..len)
+                    .map(|_| {
+                        let bits = r.u16()?;
+                        InputFrame::from_bits(bits).ok_or(DecodeError::InvalidTag {
+                            kind: "input frame",
+                            tag: bits as u8,
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                ClientMessage::Input { tick, frames }
+            }
+            2 => ClientMessage::Lobby(LobbyRequest::read(&mut r)?),
+            3 => ClientMessage::Ping { nonce: r.u32()? },
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "client message",
+                    tag,
+                });
+            }
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
+
+impl ServerMessage {
+    /// Everything the server sends must arrive; spectators and lobbies can't skip ahead.
+    pub fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.u16(PROTOCOL_VERSION);
+        match self {
+            ServerMessage::Welcome {
+                session,
+                resume_token,
+                player,
+                tick,
+            } => {
+                w.u8(0)
+                    .u64(*session)
+                    .u64(*resume_token)
+                    .u64(*player)
+                    .u32(*tick);
+            }
+            ServerMessage::Lobby(event) => {
+                w.u8(2);
+                event.write(&mut w);
+            }
+            ServerMessage::Pong { nonce } => {
+                w.u8(3).u32(*nonce);
+            }
+            ServerMessage::Disconnect { reason } => {
+                w.u8(4).str(reason);
+            }
+            ServerMessage::Inputs { tick, frames } => {
+                w.u8(5).u32(*tick).u8(frames.len() as u8);
+                for (slot, frame) in frames {
+                    write_slot(&mut w, *slot);
+                    w.u16(frame.bits());
+                }
+            }
+            ServerMessage::SpectateStart { replay } => {
+                w.u8(6).bytes(replay);
+            }
+            ServerMessage::SpectateChunk { start, replay } => {
+                w.u8(7).u32(*start).bytes(replay);
+            }
+        }
+        w.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let message = match read_header(&mut r)? {
+            0 => ServerMessage::Welcome {
+                session: r.u64()?,
+                resume_token: r.u64()?,
+                player: r.u64()?,
+                tick: r.u32()?,
+            },
+            2 => ServerMessage::Lobby(LobbyEvent::read(&mut r)?),
+            3 => ServerMessage::Pong { nonce: r.u32()? },
+            4 => ServerMessage::Disconnect {
+                reason: r.str()?.to_owned(),
+            },
+            5 => {
+                let tick = r.u32()?;
+                let count = r.u8()?;
+                let frames = (0..count)
+                    .map(|_| Ok((read_slot(&mut r)?, InputFrame::from_bits_truncate(r.u16()?))))
+                    .collect::<Result<_, DecodeError>>()?;
+                ServerMessage::Inputs { tick, frames }
+            }
+            6 => ServerMessage::SpectateStart {
+                replay: r.bytes()?.to_vec(),
+            },
+            7 => ServerMessage::SpectateChunk {
+                start: r.u32()?,
+                replay: r.bytes()?.to_vec(),
+            },
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "server message",
+                    tag,
+                });
+            }
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
+
+impl LobbyRequest {
+    fn write(&self, w: &mut Writer) {
+        match self {
+            LobbyRequest::CreateRoom => {
+                w.u8(0);
+            }
+            LobbyRequest::JoinRoom { code } => {
+                w.u8(1).str(code);
+            }
+            LobbyRequest::LeaveRoom => {
+                w.u8(2);
+            }
+            LobbyRequest::QuickMatch { region } => {
+                w.u8(3).str(region);
+            }
+            LobbyRequest::CancelQuickMatch => {
+                w.u8(4);
+            }
+            LobbyRequest::SelectCharacter { manifest_id } => {
+                w.u8(5).str(manifest_id);
+            }
+            LobbyRequest::LockIn => {
+                w.u8(6);
+            }
+            LobbyRequest::RematchVote { rematch } => {
+                w.u8(7).bool(*rematch);
+            }
+            LobbyRequest::Spectate { code } => {
+                w.u8(8).str(code);
+            }
+        }
+    }
+
+    fn read(r: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(match r.u8()? {
+            0 => LobbyRequest::CreateRoom,
+            1 => LobbyRequest::JoinRoom {
+                code: r.str()?.to_owned(),
+            },
+            2 => LobbyRequest::LeaveRoom,
+            3 => LobbyRequest::QuickMatch {
+                region: r.str()?.to_owned(),
+            },
+            4 => LobbyRequest::CancelQuickMatch,
+            5 => LobbyRequest::SelectCharacter {
+                manifest_id: r.str()?.to_owned(),
+            },
+            6 => LobbyRequest::LockIn,
+            7 => LobbyRequest::RematchVote { rematch: r.bool()? },
+            8 => LobbyRequest::Spectate {
+                code: r.str()?.to_owned(),
+            },
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "lobby request",
+                    tag,
+                });
+            }
+        })
+    }
+}
+
+impl LobbyEvent {
+    fn write(&self, w: &mut Writer) {
+        match self {
+            LobbyEvent::RoomJoined { code, slot } => {
+                w.u8(0).str(code);
+                write_slot(w, *slot);
+            }
+            LobbyEvent::RoomLeft => {
+                w.u8(1);
+            }
+            LobbyEvent::Rejected { reason } => {
+                w.u8(2).str(reason);
+            }
+            LobbyEvent::Queued { region } => {
+                w.u8(3).str(region);
+            }
+            LobbyEvent::QueueCancelled => {
+                w.u8(4);
+            }
+            LobbyEvent::RoomUpdate(room) => {
+                w.u8(5);
+                room.write(w);
+            }
+            LobbyEvent::MatchStarted { seed, fighters } => {
+                w.u8(6).u64(*seed).u8(fighters.len() as u8);
+                for (slot, manifest_id) in fighters {
+                    write_slot(w, *slot);
+                    w.str(manifest_id);
+                }
+            }
+            LobbyEvent::MatchEnded { winner } => {
+                w.u8(7);
+                write_option(w, *winner, write_slot);
+            }
+            LobbyEvent::Spectating { code } => {
+                w.u8(8).str(code);
+            }
+        }
+    }
+
+    fn read(r: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(match r.u8()? {
+            0 => LobbyEvent::RoomJoined {
+                code: r.str()?.to_owned(),
+                slot: read_slot(r)?,
+            },
+            1 => LobbyEvent::RoomLeft,
+            2 => LobbyEvent::Rejected {
+                reason: r.str()?.to_owned(),
+            },
+            3 => LobbyEvent::Queued {
+                region: r.str()?.to_owned(),
+            },
+            4 => LobbyEvent::QueueCancelled,
+            5 => LobbyEvent::RoomUpdate(RoomInfo::read(r)?),
+            6 => {
+                let seed = r.u64()?;
+                let count = r.u8()?;
+                let fighters = (0..count)
+                    .map(|_| Ok((read_slot(r)?, r.str()?.to_owned())))
+                    .collect::<Result<_, DecodeError>>()?;
+                LobbyEvent::MatchStarted { seed, fighters }
+            }
+            7 => LobbyEvent::MatchEnded {
+                winner: read_option(r, read_slot)?,
+            },
+            8 => LobbyEvent::Spectating {
+                code: r.str()?.to_owned(),
+            },
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "lobby event",
+                    tag,
+                });
+            }
+        })
+    }
+}
+
+impl RoomInfo {
+    fn write(&self, w: &mut Writer) {
+        let phase = match self.phase {
+            RoomPhase::Waiting => 0,
+            RoomPhase::CharacterSelect => 1,
+            RoomPhase::InMatch => 2,
+            RoomPhase::PostMatch => 3,
+        };
+        w.str(&self.code).u8(phase).u8(self.seats.len() as u8);
+        for seat in &self.seats {
+            write_slot(w, seat.slot);
+            w.str(&seat.name);
+            write_option(w, seat.character.as_deref(), |w, id| {
+                w.str(id);
+            });
+            w.bool(seat.locked_in).bool(seat.connected);
+            write_option(w, seat.rematch, |w, rematch| {
+                w.bool(rematch);
+            });
+        }
+        w.varint(self.spectators.into());
+    }
+
+    fn read(r: &mut Reader) -> Result<Self, DecodeError> {
+        let code = r.str()?.to_owned();
+        let phase = match r.u8()? {
+            0 => RoomPhase::Waiting,
+            1 => RoomPhase::CharacterSelect,
+            2 => RoomPhase::InMatch,
+            3 => RoomPhase::PostMatch,
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "room phase",
+                    tag,
+                });
+            }
+        };
+        let count = r.u8()?;
+        let seats = (0..count)
+            .map(|_| {
+                Ok(SeatInfo {
+                    slot: read_slot(r)?,
+                    name: r.str()?.to_owned(),
+                    character: read_option(r, |r| r.str().map(str::to_owned))?,
+                    locked_in: r.bool()?,
+                    connected: r.bool()?,
+                    rematch: read_option(r, Reader::bool)?,
+                })
+            })
+            .collect::<Result<_, DecodeError>>()?;
+        Ok(Self {
+            code,
+            phase,
+            seats,
+            spectators: r.varint_u32()?,
+        })
+    }
+}
+
+fn write_option<T>(w: &mut Writer, value: Option<T>, write: impl FnOnce(&mut Writer, T)) {
+    match value {
+        Some(value) => {
+            w.u8(1);
+            write(w, value);
+        }
+        None => {
+            w.u8(0);
+        }
+    }
+}
+
+fn read_option<'a, T>(
+    r: &mut Reader<'a>,
+    read: impl FnOnce(&mut Reader<'a>) -> Result<T, DecodeError>,
+) -> Result<Option<T>, DecodeError> {
+    match r.u8()? {
+        0 => Ok(None),
+        _ => read(r).map(Some),
+    }
+}
+
+fn read_header(r: &mut Reader) -> Result<u8, DecodeError> {
+    let version = r.u16()?;
+    if version != PROTOCOL_VERSION {
+        return Err(DecodeError::VersionMismatch {
+            expected: PROTOCOL_VERSION,
+            found: version,
+        });
+    }
+    r.u8()
+}
+
+fn write_slot(w: &mut Writer, slot: PlayerSlot) {
+    w.u8(slot.index() as u8);
+}
+
+fn read_slot(r: &mut Reader) -> Result<PlayerSlot, DecodeError> {
+    let tag = r.u8()?;
+    PlayerSlot::ALL
+        .get(tag as usize)
+        .copied()
+        .ok_or(DecodeError::InvalidTag {
+            kind: "player slot",
+            tag,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_messages() -> Vec<ClientMessage> {
+        let lobby = [
+            LobbyRequest::CreateRoom,
+            LobbyRequest::JoinRoom {
+                code: "ABCD".to_owned(),
+            },
+            LobbyRequest::QuickMatch {
+                region: "eu".to_owned(),
+            },
+            LobbyRequest::CancelQuickMatch,
+            LobbyRequest::LeaveRoom,
+            LobbyRequest::SelectCharacter {
+                manifest_id: "naruto".to_owned(),
+            },
+            LobbyRequest::LockIn,
+            LobbyRequest::RematchVote { rematch: true },
+            LobbyRequest::Spectate {
+                code: "WXYZ".to_owned(),
+            },
+        ];
+
+        [
+            ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: "ナルト".to_owned(),
+                resume: Some(u64::MAX),
+                player: None,
+            },
+            ClientMessage::Hello {
+                version: 1,
+                name: String::new(),
+                resume: None,
+                player: Some(7),
+            },
+            ClientMessage::Input {
+                tick: u32::MAX,
+                frames: vec![
+                    InputFrame::NONE,
+                    InputFrame::LEFT | InputFrame::JUMP,
+                    InputFrame::RIGHT | InputFrame::DOWN | InputFrame::ATTACK | InputFrame::TILT,
+                ],
+            },
+            ClientMessage::Input {
+                tick: 0,
+                frames: Vec::new(),
+            },
+            ClientMessage::Ping { nonce: 42 },
+        ]
+        .into_iter()
+        .chain(lobby.map(ClientMessage::Lobby))
+        .collect()
+    }
+
+    fn server_messages() -> Vec<ServerMessage> {
+        let seat = SeatInfo {
+            slot: PlayerSlot::Two,
+            name: "bob".to_owned(),
+            character: Some("naruto".to_owned()),
+            locked_in: true,
+            connected: false,
+            rematch: Some(false),
+        };
+        let lobby = [
+            LobbyEvent::RoomJoined {
+                code: "ABCD".to_owned(),
+                slot: PlayerSlot::One,
+            },
+            LobbyEvent::RoomLeft,
+            LobbyEvent::Rejected {
+                reason: "room is full".to_owned(),
+            },
+            LobbyEvent::Queued {
+                region: "eu".to_owned(),
+            },
+            LobbyEvent::QueueCancelled,
+            LobbyEvent::RoomUpdate(RoomInfo {
+                code: "ABCD".to_owned(),
+                phase: RoomPhase::PostMatch,
+                seats: vec![
+                    SeatInfo {
+                        slot: PlayerSlot::One,
+                        name: "alice".to_owned(),
+                        character: None,
+                        locked_in: false,
+                        connected: true,
+                        rematch: None,
+                    },
+                    seat,
+                ],
+                spectators: u32::MAX,
+            }),
+            LobbyEvent::MatchStarted {
+                seed: 0x5eed,
+                fighters: vec![
+                    (PlayerSlot::One, "naruto".to_owned()),
+                    (PlayerSlot::Two, "naruto".to_owned()),
+                ],
+            },
+            LobbyEvent::MatchEnded { winner: None },
+            LobbyEvent::MatchEnded {
+                winner: Some(PlayerSlot::Two),
+            },
+            LobbyEvent::Spectating {
+                code: "ABCD".to_owned(),
+            },
+        ];
+
+        [
+            ServerMessage::Welcome {
+                session: 1,
+                resume_token: u64::MAX,
+                player: 3,
+                tick: 120,
+            },
+            ServerMessage::Inputs {
+                tick: 9,
+                frames: vec![
+                    (PlayerSlot::One, InputFrame::DASH),
+                    (PlayerSlot::Two, InputFrame::UP | InputFrame::LEFT),
+                ],
+            },
+            ServerMessage::Pong { nonce: 42 },
+            ServerMessage::SpectateStart {
+                replay: vec![1, 2, 3],
+            },
+            ServerMessage::SpectateChunk {
+                start: 600,
+                replay: Vec::new(),
+            },
+            ServerMessage::Disconnect {
+                reason: "server shutting down".to_owned(),
+            },
+        ]
+        .into_iter()
+        .chain(lobby.map(ServerMessage::Lobby))
+        .collect()
+    }
+
+    #[test]
+    fn client_messages_round_trip() {
+        for message in client_messages() {
+            assert_eq!(ClientMessage::decode(&message.encode()), Ok(message));
+        }
+    }
+
+    #[test]
+    fn server_messages_round_trip() {
+        for message in server_messages() {
+            assert_eq!(ServerMessage::decode(&message.encode()), Ok(message));
+        }
+    }
+
+    #[test]
+    fn rejects_every_truncation() {
+        for message in client_messages() {
+            let bytes = message.encode();
+            for len in 0..bytes.len() {
+                assert!(ClientMessage::decode(&bytes[..len]).is_err(), "{message:?}");
+            }
+        }
+        for message in server_messages() {
+            let bytes = message.encode();
+            for len in 0..bytes.len() {
+                assert!(ServerMessage::decode(&bytes[..len]).is_err(), "{message:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = ClientMessage::Ping { nonce: 1 }.encode();
+        bytes.push(0);
+        assert_eq!(
+            ClientMessage::decode(&bytes),
+            Err(DecodeError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn rejects_other_protocol_versions() {
+        let mut bytes = ServerMessage::Pong { nonce: 1 }.encode();
+        bytes[..2].copy_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            ServerMessage::decode(&bytes),
+            Err(DecodeError::VersionMismatch {
+                expected: PROTOCOL_VERSION,
+                found: PROTOCOL_VERSION + 1,
+            })
+        );
+    }
+
+    /// `PROTOCOL_VERSION` followed by `body`.
+    fn packet(body: &[u8]) -> Vec<u8> {
+        let mut bytes = PROTOCOL_VERSION.to_le_bytes().to_vec();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn rejects_unknown_tags() {
+        assert_eq!(
+            ClientMessage::decode(&packet(&[9])),
+            Err(DecodeError::InvalidTag {
+                kind: "client message",
+                tag: 9,
+            })
+        );
+        // Tag 1 is retired and must not be reused by accident
+        assert_eq!(
+            ServerMessage::decode(&packet(&[1])),
+            Err(DecodeError::InvalidTag {
+                kind: "server message",
+                tag: 1,
+            })
+        );
+        assert!(matches!(
+            ClientMessage::decode(&packet(&[2, 9])),
+            Err(DecodeError::InvalidTag {
+                kind: "lobby request",
+                ..
+            })
+        ));
+        assert!(matches!(
+            ServerMessage::decode(&packet(&[2, 9])),
+            Err(DecodeError::InvalidTag {
+                kind: "lobby event",
+                ..
+            })
+        ));
+        // `RoomJoined` with an empty code and a third player slot
+        assert!(matches!(
+            ServerMessage::decode(&packet(&[2, 0, 0, 2])),
+            Err(DecodeError::InvalidTag {
+                kind: "player slot",
+                ..
+            })
+        ));
+        // `RoomUpdate` with an empty code and phase 4
+        assert!(matches!(
+            ServerMessage::decode(&packet(&[2, 5, 0, 4])),
+            Err(DecodeError::InvalidTag {
+                kind: "room phase",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        let input = |count: u8, frames: &[u16]| {
+            let mut body = vec![1, 0, 0, 0, 0, count];
+            body.extend(frames.iter().flat_map(|bits| bits.to_le_bytes()));
+            packet(&body)
+        };
+
+        assert!(ClientMessage::decode(&input(1, &[InputFrame::JUMP.bits()])).is_ok());
+        assert!(matches!(
+            ClientMessage::decode(&input(1, &[0x80])),
+            Err(DecodeError::InvalidTag {
+                kind: "input frame",
+                tag: 0x80,
+            })
+        ));
+
+        let too_many = INPUT_REDUNDANCY as u8 + 2;
+        assert_eq!(
+            ClientMessage::decode(&input(too_many, &vec![0; too_many.into()])),
+            Err(DecodeError::TooLong(too_many.into()))
+        );
+    }
+
+    #[test]
+    fn inputs_travel_unreliably() {
+        for message in client_messages() {
+            let expected = match message {
+                ClientMessage::Input { .. } => Channel::Unreliable,
+                _ => Channel::Reliable,
+            };
+            assert_eq!(message.channel(), expected);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Where transport I/O tasks run: the ambient tokio runtime on native (or a shared
+//! background one when called from outside tokio, e.g. a Bevy client), and the
+//! browser's microtask queue on wasm.
+
+use std::future::Future;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    use std::sync::OnceLock;
+
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(future);
+        }
+        Err(_) => {
+            RUNTIME
+                .get_or_init(|| {
+                    tokio::runtime::Builder::new_multi_thread()
+                        .worker_threads(1)
+                        .thread_name("net-io")
+                        .enable_all()
+                        .build()
+                        .expect("failed to start the network runtime")
+                })
+                .spawn(future);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(future);
+}
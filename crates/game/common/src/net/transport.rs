@@ -0,0 +1,209 @@
+use async_channel::{Receiver, Sender, TryRecvError};
+
+/// Delivery guarantee requested for an outgoing message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Ordered and retransmitted. Lobby control and anything that must arrive.
+    Reliable,
+    /// Fire-and-forget datagrams. Inputs, which are resent every tick anyway.
+    /// Transports without datagrams deliver these reliably.
+    Unreliable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportKind {
+    WebTransport,
+    WebSocket,
+    /// In-memory pair inside one process, for tests and local sessions.
+    Loopback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransportError {
+    #[error("connection failed: {0}")]
+    Connect(String),
+    #[error("transport i/o error: {0}")]
+    Io(String),
+    #[error("connection closed")]
+    Closed,
+    #[error("{0} is not supported on this platform")]
+    Unsupported(&'static str),
+    #[error("message of {0} bytes exceeds the frame limit")]
+    TooLarge(usize),
+}
+
+/// Largest message accepted on any transport.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Unit exchanged between a `Connection` handle and its transport driver. A
+/// loopback pair wires two handles' channels directly to each other.
+#[derive(Debug)]
+pub(crate) enum Frame {
+    Opened,
+    Message(Channel, Vec<u8>),
+    Closed(Option<TransportError>),
+}
+
+/// One end of a message-oriented connection. All methods are non-blocking, so it
+/// can be polled from Bevy systems on native and in the browser alike; the actual
+/// I/O runs on a background task owned by the transport.
+#[derive(Debug)]
+pub struct Connection {
+    kind: TransportKind,
+    state: ConnectionState,
+    was_open: bool,
+    error: Option<TransportError>,
+    outgoing: Sender<Frame>,
+    incoming: Receiver<Frame>,
+}
+
+/// The transport side of a `Connection`.
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionDriver {
+    pub outgoing: Receiver<Frame>,
+    incoming: Sender<Frame>,
+}
+
+impl ConnectionDriver {
+    pub fn opened(&self) {
+        let _ = self.incoming.try_send(Frame::Opened);
+    }
+
+    pub fn deliver(&self, bytes: Vec<u8>) {
+        let _ = self
+            .incoming
+            .try_send(Frame::Message(Channel::Reliable, bytes));
+    }
+
+    pub fn closed(&self, error: Option<TransportError>) {
+        let _ = self.incoming.try_send(Frame::Closed(error));
+        self.incoming.close();
+    }
+}
+
+impl Connection {
+    /// Creates a handle in the `Connecting` state and the driver its transport task uses.
+    pub(crate) fn new(kind: TransportKind) -> (Self, ConnectionDriver) {
+        let (outgoing_tx, outgoing_rx) = async_channel::unbounded();
+        let (incoming_tx, incoming_rx) = async_channel::unbounded();
+        (
+            Self::from_channels(kind, outgoing_tx, incoming_rx),
+            ConnectionDriver {
+                outgoing: outgoing_rx,
+                incoming: incoming_tx,
+            },
+        )
+    }
+
+    pub(crate) fn from_channels(
+        kind: TransportKind,
+        outgoing: Sender<Frame>,
+        incoming: Receiver<Frame>,
+    ) -> Self {
+        Self {
+            kind,
+            state: ConnectionState::Connecting,
+            was_open: false,
+            error: None,
+            outgoing,
+            incoming,
+        }
+    }
+
+    pub fn kind(&self) -> TransportKind {
+        self.kind
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state == ConnectionState::Connected
+    }
+
+    /// Why the connection closed, if it closed with an error.
+    pub fn error(&self) -> Option<&TransportError> {
+        self.error.as_ref()
+    }
+
+    /// Closed without ever opening, e.g. a refused WebTransport handshake worth
+    /// retrying over WebSocket.
+    pub fn failed_to_open(&self) -> bool {
+        self.state == ConnectionState::Closed && !self.was_open
+    }
+
+    /// Queues a message. Messages sent while still connecting go out once open.
+    pub fn send(&mut self, channel: Channel, bytes: Vec<u8>) -> Result<(), TransportError> {
+        if bytes.len() > MAX_MESSAGE_SIZE {
+            return Err(TransportError::TooLarge(bytes.len()));
+        }
+        if self.state == ConnectionState::Closed {
+            return Err(TransportError::Closed);
+        }
+        self.outgoing
+            .try_send(Frame::Message(channel, bytes))
+            .map_err(|_| TransportError::Closed)
+    }
+
+    /// Next received message, updating the connection state on the way.
+    pub fn receive(&mut self) -> Option<Vec<u8>> {
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Frame::Opened) => {
+                    self.state = ConnectionState::Connected;
+                    self.was_open = true;
+                }
+                Ok(Frame::Message(_, bytes)) => return Some(bytes),
+                Ok(Frame::Closed(error)) => {
+                    self.state = ConnectionState::Closed;
+                    self.error = error;
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Closed) => {
+                    self.state = ConnectionState::Closed;
+                    return None;
+                }
+            }
+        }
+    }
+
+    pub fn close(&mut self) {
+        if self.state != ConnectionState::Closed {
+            let _ = self.outgoing.try_send(Frame::Closed(None));
+            self.outgoing.close();
+            self.state = ConnectionState::Closed;
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Server-side source of newly accepted connections.
+#[derive(Debug)]
+pub struct Listener {
+    accepted: Receiver<Connection>,
+}
+
+impl Listener {
+    pub(crate) fn new() -> (Self, Sender<Connection>) {
+        let (sender, accepted) = async_channel::unbounded();
+        (Self { accepted }, sender)
+    }
+
+    /// Next connection accepted since the last call, if any.
+    pub fn accept(&mut self) -> Option<Connection> {
+        self.accepted.try_recv().ok()
+    }
+}
@@ -0,0 +1,192 @@
+//! WebSocket transport: the fallback for browsers without WebTransport. Everything
+//! travels over one TCP stream, so `Channel::Unreliable` is delivered reliably.
+
+use super::transport::{Connection, TransportKind};
+
+/// Default TCP port of the game server's WebSocket endpoint.
+pub const DEFAULT_PORT: u16 = 4434;
+
+/// Opens a WebSocket connection to `url` (`ws://` or `wss://`).
+pub fn connect(url: &str) -> Connection {
+    let (connection, driver) = Connection::new(TransportKind::WebSocket);
+    platform::connect(url.to_owned(), driver);
+    connection
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use platform::listen;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::net::SocketAddr;
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{AsyncRead, AsyncWrite};
+    use tokio_tungstenite::{WebSocketStream, tungstenite::Message};
+
+    use super::super::{
+        runtime::spawn,
+        transport::{Connection, ConnectionDriver, Frame, Listener, TransportError, TransportKind},
+    };
+
+    pub(super) fn connect(url: String, driver: ConnectionDriver) {
+        spawn(async move {
+            match tokio_tungstenite::connect_async(url.as_str()).await {
+                Ok((stream, _)) => run(stream, driver).await,
+                Err(err) => driver.closed(Some(TransportError::Connect(err.to_string()))),
+            }
+        });
+    }
+
+    /// Accepts WebSocket connections on `addr`.
+    pub async fn listen(addr: SocketAddr) -> Result<Listener, TransportError> {
+        let socket = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|err| TransportError::Io(err.to_string()))?;
+        let (listener, accepted) = Listener::new();
+
+        spawn(async move {
+            loop {
+                let stream = match socket.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        tracing::warn!("websocket accept failed: {err}");
+                        continue;
+                    }
+                };
+                let accepted = accepted.clone();
+                spawn(async move {
+                    let _ = stream.set_nodelay(true);
+                    match tokio_tungstenite::accept_async(stream).await {
+                        Ok(stream) => {
+                            let (connection, driver) = Connection::new(TransportKind::WebSocket);
+                            if accepted.send(connection).await.is_ok() {
+                                run(stream, driver).await;
+                            }
+                        }
+                        Err(err) => tracing::debug!("websocket handshake failed: {err}"),
+                    }
+                });
+            }
+        });
+
+        Ok(listener)
+    }
+
+    async fn run<S>(stream: WebSocketStream<S>, driver: ConnectionDriver)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        driver.opened();
+        let (mut sink, mut source) = stream.split();
+
+        loop {
+            tokio::select! {
+                frame = driver.outgoing.recv() => match frame {
+                    Ok(Frame::Message(_, bytes)) => {
+                        if let Err(err) = sink.send(Message::Binary(bytes.into())).await {
+                            driver.closed(Some(TransportError::Io(err.to_string())));
+                            return;
+                        }
+                    }
+                    Ok(Frame::Opened) => {}
+                    Ok(Frame::Closed(_)) | Err(_) => {
+                        let _ = sink.close().await;
+                        driver.closed(None);
+                        return;
+                    }
+                },
+                message = source.next() => match message {
+                    Some(Ok(Message::Binary(bytes))) => driver.deliver(bytes.to_vec()),
+                    Some(Ok(Message::Close(_))) | None => {
+                        driver.closed(None);
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => {
+                        driver.closed(Some(TransportError::Io(err.to_string())));
+                        return;
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use wasm_bindgen::{JsCast, closure::Closure};
+    use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+    use super::super::{
+        runtime::spawn,
+        transport::{ConnectionDriver, Frame, TransportError},
+    };
+
+    pub(super) fn connect(url: String, driver: ConnectionDriver) {
+        let socket = match WebSocket::new(&url) {
+            Ok(socket) => socket,
+            Err(err) => {
+                driver.closed(Some(TransportError::Connect(format!("{err:?}"))));
+                return;
+            }
+        };
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        // Sending on a socket that is still connecting throws, so outgoing frames
+        // wait for this signal. Closing it unblocks the sender on failure.
+        let (open_tx, open_rx) = async_channel::bounded::<()>(1);
+
+        let on_open = {
+            let driver = driver.clone();
+            let open_tx = open_tx.clone();
+            Closure::<dyn FnMut(Event)>::new(move |_| {
+                driver.opened();
+                let _ = open_tx.try_send(());
+            })
+        };
+        let on_message = {
+            let driver = driver.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                    driver.deliver(js_sys::Uint8Array::new(&buffer).to_vec());
+                }
+            })
+        };
+        let on_close = {
+            let driver = driver.clone();
+            Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                let error = (!event.was_clean())
+                    .then(|| TransportError::Io(format!("closed with code {}", event.code())));
+                driver.closed(error);
+                open_tx.close();
+            })
+        };
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        // Owns the callbacks for as long as the handle can still send.
+        spawn(async move {
+            if open_rx.recv().await.is_ok() {
+                while let Ok(frame) = driver.outgoing.recv().await {
+                    match frame {
+                        Frame::Message(_, bytes) => {
+                            if socket.send_with_u8_array(&bytes).is_err() {
+                                break;
+                            }
+                        }
+                        Frame::Opened => {}
+                        Frame::Closed(_) => break,
+                    }
+                }
+            }
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            let _ = socket.close();
+            driver.closed(None);
+            drop((on_open, on_message, on_close));
+        });
+    }
+}
@@ -0,0 +1,359 @@
+//! WebTransport (HTTP/3 over QUIC) transport. `Channel::Reliable` messages travel on
+//! one bidirectional stream, length-prefixed; `Channel::Unreliable` ones are sent as
+//! datagrams unless they exceed the datagram size, in which case they fall back to
+//! the stream.
+
+use super::transport::{Connection, TransportKind};
+
+/// Default UDP port of the game server's WebTransport endpoint.
+pub const DEFAULT_PORT: u16 = 4433;
+
+/// Opens a WebTransport session to `url` (`https://host:port`). `certificate_hash` pins
+/// a self-signed server certificate by its SHA-256 digest, as used in development.
+pub fn connect(url: &str, certificate_hash: Option<[u8; 32]>) -> Connection {
+    let (connection, driver) = Connection::new(TransportKind::WebTransport);
+    platform::connect(url.to_owned(), certificate_hash, driver);
+    connection
+}
+
+/// Whether this platform can open WebTransport sessions at all.
+pub fn is_supported() -> bool {
+    platform::is_supported()
+}
+
+fn encode_frame(bytes: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(bytes.len() + 4);
+    framed.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    framed.extend_from_slice(bytes);
+    framed
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use platform::{Identity, listen};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{net::SocketAddr, time::Duration};
+
+    use wtransport::{
+        ClientConfig, Endpoint, RecvStream, SendStream, ServerConfig, VarInt, tls::Sha256Digest,
+    };
+
+    pub use wtransport::Identity;
+
+    use super::super::{
+        runtime::spawn,
+        transport::{
+            Channel, Connection, ConnectionDriver, Frame, Listener, MAX_MESSAGE_SIZE,
+            TransportError, TransportKind,
+        },
+    };
+    use super::encode_frame;
+
+    const KEEP_ALIVE: Duration = Duration::from_secs(3);
+
+    pub(super) fn is_supported() -> bool {
+        true
+    }
+
+    pub(super) fn connect(
+        url: String,
+        certificate_hash: Option<[u8; 32]>,
+        driver: ConnectionDriver,
+    ) {
+        spawn(async move {
+            let builder = ClientConfig::builder().with_bind_default();
+            let config = match certificate_hash {
+                Some(hash) => builder.with_server_certificate_hashes([Sha256Digest::new(hash)]),
+                None => builder.with_native_certs(),
+            }
+            .keep_alive_interval(Some(KEEP_ALIVE))
+            .build();
+
+            let session = async {
+                let endpoint = Endpoint::client(config).map_err(|err| err.to_string())?;
+                let session = endpoint
+                    .connect(url.as_str())
+                    .await
+                    .map_err(|err| err.to_string())?;
+                let (send, recv) = session
+                    .open_bi()
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .await
+                    .map_err(|err| err.to_string())?;
+                Ok::<_, String>((session, send, recv))
+            };
+
+            match session.await {
+                Ok((session, send, recv)) => run(session, send, recv, driver).await,
+                Err(err) => driver.closed(Some(TransportError::Connect(err))),
+            }
+        });
+    }
+
+    /// Accepts WebTransport sessions on `addr` (UDP), presenting `identity` as the
+    /// TLS certificate.
+    pub async fn listen(addr: SocketAddr, identity: Identity) -> Result<Listener, TransportError> {
+        let config = ServerConfig::builder()
+            .with_bind_address(addr)
+            .with_identity(identity)
+            .keep_alive_interval(Some(KEEP_ALIVE))
+            .build();
+        let endpoint =
+            Endpoint::server(config).map_err(|err| TransportError::Io(err.to_string()))?;
+        let (listener, accepted) = Listener::new();
+
+        spawn(async move {
+            loop {
+                let incoming = endpoint.accept().await;
+                let accepted = accepted.clone();
+                spawn(async move {
+                    let session = async {
+                        let request = incoming.await.map_err(|err| err.to_string())?;
+                        let session = request.accept().await.map_err(|err| err.to_string())?;
+                        let (send, recv) =
+                            session.accept_bi().await.map_err(|err| err.to_string())?;
+                        Ok::<_, String>((session, send, recv))
+                    };
+                    match session.await {
+                        Ok((session, send, recv)) => {
+                            let (connection, driver) = Connection::new(TransportKind::WebTransport);
+                            if accepted.send(connection).await.is_ok() {
+                                run(session, send, recv, driver).await;
+                            }
+                        }
+                        Err(err) => tracing::debug!("webtransport handshake failed: {err}"),
+                    }
+                });
+            }
+        });
+
+        Ok(listener)
+    }
+
+    async fn run(
+        session: wtransport::Connection,
+        mut send: SendStream,
+        recv: RecvStream,
+        driver: ConnectionDriver,
+    ) {
+        driver.opened();
+
+        spawn(read_stream(recv, driver.clone()));
+        spawn({
+            let session = session.clone();
+            let driver = driver.clone();
+            async move {
+                while let Ok(datagram) = session.receive_datagram().await {
+                    driver.deliver(datagram.payload().to_vec());
+                }
+            }
+        });
+
+        while let Ok(frame) = driver.outgoing.recv().await {
+            let result = match frame {
+                Frame::Message(Channel::Unreliable, bytes)
+                    if session
+                        .max_datagram_size()
+                        .is_some_and(|max| bytes.len() <= max) =>
+                {
+                    session
+                        .send_datagram(bytes)
+                        .map_err(|err| TransportError::Io(err.to_string()))
+                }
+                Frame::Message(_, bytes) => send
+                    .write_all(&encode_frame(&bytes))
+                    .await
+                    .map_err(|err| TransportError::Io(err.to_string())),
+                Frame::Opened => Ok(()),
+                Frame::Closed(_) => break,
+            };
+            if let Err(err) = result {
+                driver.closed(Some(err));
+                return;
+            }
+        }
+
+        session.close(VarInt::from_u32(0), b"closed");
+        driver.closed(None);
+    }
+
+    async fn read_stream(mut recv: RecvStream, driver: ConnectionDriver) {
+        let mut len = [0u8; 4];
+        loop {
+            if recv.read_exact(&mut len).await.is_err() {
+                break;
+            }
+            let len = u32::from_le_bytes(len) as usize;
+            if len > MAX_MESSAGE_SIZE {
+                driver.closed(Some(TransportError::TooLarge(len)));
+                return;
+            }
+            let mut bytes = vec![0; len];
+            if recv.read_exact(&mut bytes).await.is_err() {
+                break;
+            }
+            driver.deliver(bytes);
+        }
+        driver.closed(None);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use js_sys::{Array, Object, Reflect, Uint8Array};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{
+        ReadableStream, ReadableStreamDefaultReader, WebTransport, WebTransportBidirectionalStream,
+        WebTransportOptions, WritableStreamDefaultWriter,
+    };
+
+    use super::super::{
+        runtime::spawn,
+        transport::{Channel, ConnectionDriver, Frame, MAX_MESSAGE_SIZE, TransportError},
+    };
+    use super::encode_frame;
+
+    pub(super) fn is_supported() -> bool {
+        Reflect::has(&js_sys::global(), &JsValue::from_str("WebTransport")).unwrap_or(false)
+    }
+
+    pub(super) fn connect(
+        url: String,
+        certificate_hash: Option<[u8; 32]>,
+        driver: ConnectionDriver,
+    ) {
+        spawn(async move {
+            match open(&url, certificate_hash).await {
+                Ok((transport, stream)) => run(transport, stream, driver).await,
+                Err(err) => driver.closed(Some(TransportError::Connect(format!("{err:?}")))),
+            }
+        });
+    }
+
+    async fn open(
+        url: &str,
+        certificate_hash: Option<[u8; 32]>,
+    ) -> Result<(WebTransport, WebTransportBidirectionalStream), JsValue> {
+        let transport = match certificate_hash {
+            Some(hash) => {
+                let entry = Object::new();
+                Reflect::set(&entry, &"algorithm".into(), &"sha-256".into())?;
+                Reflect::set(&entry, &"value".into(), &Uint8Array::from(&hash[..]))?;
+                let options = Object::new();
+                Reflect::set(
+                    &options,
+                    &"serverCertificateHashes".into(),
+                    &Array::of1(&entry),
+                )?;
+                WebTransport::new_with_options(url, options.unchecked_ref::<WebTransportOptions>())?
+            }
+            None => WebTransport::new(url)?,
+        };
+        JsFuture::from(transport.ready()).await?;
+        let stream = JsFuture::from(transport.create_bidirectional_stream())
+            .await?
+            .unchecked_into::<WebTransportBidirectionalStream>();
+        Ok((transport, stream))
+    }
+
+    async fn run(
+        transport: WebTransport,
+        stream: WebTransportBidirectionalStream,
+        driver: ConnectionDriver,
+    ) {
+        let (stream_writer, datagram_writer) = match (
+            stream.writable().get_writer(),
+            transport.datagrams().writable().get_writer(),
+        ) {
+            (Ok(stream), Ok(datagrams)) => (stream, datagrams),
+            _ => {
+                driver.closed(Some(TransportError::Connect(
+                    "could not lock the session's writers".into(),
+                )));
+                return;
+            }
+        };
+        driver.opened();
+
+        spawn(read_stream(stream.readable(), driver.clone()));
+        spawn(read_datagrams(
+            transport.datagrams().readable(),
+            driver.clone(),
+        ));
+
+        let max_datagram = transport.datagrams().max_datagram_size() as usize;
+        while let Ok(frame) = driver.outgoing.recv().await {
+            let result = match frame {
+                Frame::Message(Channel::Unreliable, bytes) if bytes.len() <= max_datagram => {
+                    write(&datagram_writer, &bytes).await
+                }
+                Frame::Message(_, bytes) => write(&stream_writer, &encode_frame(&bytes)).await,
+                Frame::Opened => Ok(()),
+                Frame::Closed(_) => break,
+            };
+            if let Err(err) = result {
+                driver.closed(Some(TransportError::Io(format!("{err:?}"))));
+                return;
+            }
+        }
+
+        transport.close();
+        driver.closed(None);
+    }
+
+    async fn write(writer: &WritableStreamDefaultWriter, bytes: &[u8]) -> Result<(), JsValue> {
+        JsFuture::from(writer.write_with_chunk(&Uint8Array::from(bytes)))
+            .await
+            .map(drop)
+    }
+
+    /// Next chunk from `reader`, or `None` once the stream is done or errored.
+    async fn read_chunk(reader: &ReadableStreamDefaultReader) -> Option<Vec<u8>> {
+        let result = JsFuture::from(reader.read()).await.ok()?;
+        let done = Reflect::get(&result, &"done".into()).ok()?.as_bool()?;
+        if done {
+            return None;
+        }
+        let value = Reflect::get(&result, &"value".into()).ok()?;
+        Some(value.dyn_into::<Uint8Array>().ok()?.to_vec())
+    }
+
+    async fn read_stream(readable: ReadableStream, driver: ConnectionDriver) {
+        let reader = readable
+            .get_reader()
+            .unchecked_into::<ReadableStreamDefaultReader>();
+        // Stream chunks do not line up with messages, so reassemble length-prefixed frames.
+        let mut buffer = Vec::new();
+        while let Some(chunk) = read_chunk(&reader).await {
+            buffer.extend_from_slice(&chunk);
+            while let Some(len) = buffer
+                .first_chunk::<4>()
+                .map(|len| u32::from_le_bytes(*len))
+            {
+                let len = len as usize;
+                if len > MAX_MESSAGE_SIZE {
+                    driver.closed(Some(TransportError::TooLarge(len)));
+                    return;
+                }
+                if buffer.len() < len + 4 {
+                    break;
+                }
+                driver.deliver(buffer[4..len + 4].to_vec());
+                buffer.drain(..len + 4);
+            }
+        }
+        driver.closed(None);
+    }
+
+    async fn read_datagrams(readable: ReadableStream, driver: ConnectionDriver) {
+        let reader = readable
+            .get_reader()
+            .unchecked_into::<ReadableStreamDefaultReader>();
+        while let Some(datagram) = read_chunk(&reader).await {
+            driver.deliver(datagram);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{Context, bail};
+
+pub const USAGE: &str = "\
+Usage: game-loadtest [options]
+
+Plays bots against a game server started on this machine.
+
+Options:
+  --bots <n>            connections to open (default 20)
+  --duration <secs>     how long to play once every bot is connecting (default 60)
+  --ramp <secs>         spread the connects over this long (default 5)
+  --websocket           use the WebSocket endpoint instead of WebTransport
+  --cert-hash <hex>     SHA-256 of the server's self-signed certificate, as logged
+                        by the server on startup
+  --admin-port <port>   port serving the server's /metrics (default 9100)
+  --script <file>       play inputs from a script instead of at random
+  -h, --help            print this help";
+
+#[derive(Debug, Clone)]
+pub struct Args {
+    pub bots: usize,
+    pub duration: Duration,
+    pub ramp: Duration,
+    pub websocket: bool,
+    pub certificate_hash: Option<[u8; 32]>,
+    pub admin_port: u16,
+    pub script: Option<PathBuf>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            bots: 20,
+            duration: Duration::from_secs(60),
+            ramp: Duration::from_secs(5),
+            websocket: false,
+            certificate_hash: None,
+            admin_port: 9100,
+            script: None,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the command line; `None` when only help was asked for.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().with_context(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--bots" => parsed.bots = value()?.parse().context("--bots is not a number")?,
+                "--duration" => parsed.duration = seconds(&value()?).context("--duration")?,
+                "--ramp" => parsed.ramp = seconds(&value()?).context("--ramp")?,
+                "--websocket" => parsed.websocket = true,
+                "--cert-hash" => parsed.certificate_hash = Some(parse_hash(&value()?)?),
+                "--admin-port" => {
+                    parsed.admin_port = value()?.parse().context("--admin-port is not a port")?;
+                }
+                "--script" => parsed.script = Some(PathBuf::from(value()?)),
+                "-h" | "--help" => return Ok(None),
+                _ => bail!("unknown option {arg}\n\n{USAGE}"),
+            }
+        }
+        if parsed.bots == 0 {
+            bail!("--bots must be at least 1");
+        }
+        Ok(Some(parsed))
+    }
+}
+
+fn seconds(value: &str) -> anyhow::Result<Duration> {
+    let secs: f64 = value.parse().context("not a number of seconds")?;
+    Duration::try_from_secs_f64(secs).context("not a number of seconds")
+}
+
+/// Hex digest, with or without the `:` separators the server logs it with.
+fn parse_hash(value: &str) -> anyhow::Result<[u8; 32]> {
+    let digits: Vec<u8> = value.bytes().filter(|&b| b != b':').collect();
+    if digits.len() != 64 {
+        bail!("--cert-hash must be 32 hex-encoded bytes");
+    }
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(digits.chunks(2)) {
+        let pair = std::str::from_utf8(pair).context("--cert-hash is not hex")?;
+        *byte = u8::from_str_radix(pair, 16).context("--cert-hash is not hex")?;
+    }
+    Ok(hash)
+}
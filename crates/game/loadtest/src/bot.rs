@@ -0,0 +1,247 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use game_common::{
+    gameplay::character::input::InputFrame,
+    net::{
+        ClientMessage, Connection, ConnectionState, LobbyEvent, LobbyRequest, PROTOCOL_VERSION,
+        RoomPhase, ServerAddress, ServerMessage, TransportKind, protocol::INPUT_REDUNDANCY,
+    },
+};
+
+use crate::{
+    TICK,
+    inputs::{InputSource, Script},
+    report::Measurements,
+};
+
+/// Bots queue under their own region tag, so they prefer each other over real players.
+const REGION: &str = "loadtest";
+const CHARACTER: &str = "naruto";
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+/// Unanswered pings kept around; older ones are given up on.
+const MAX_PINGS_IN_FLIGHT: usize = 8;
+
+/// One simulated player: queues for quick matches, locks in, plays the match out and
+/// votes for a rematch, forever.
+#[derive(Debug)]
+pub struct Bot {
+    index: usize,
+    address: ServerAddress,
+    connection: Connection,
+    greeted: bool,
+    welcomed: bool,
+    closed: bool,
+    locked_in: bool,
+    inputs: InputSource,
+    /// Last match tick we sent inputs for, while playing.
+    tick: Option<u32>,
+    /// Inputs of the latest ticks, oldest first, repeated in every input packet.
+    history: VecDeque<InputFrame>,
+    pings: VecDeque<(u32, Instant)>,
+    next_nonce: u32,
+    next_ping: Instant,
+    last_inputs: Option<Instant>,
+}
+
+impl Bot {
+    pub fn new(
+        index: usize,
+        address: ServerAddress,
+        websocket: bool,
+        script: Option<&Script>,
+        now: Instant,
+    ) -> Self {
+        let connection = if websocket {
+            address.connect_fallback()
+        } else {
+            address.connect()
+        };
+        Self {
+            index,
+            address,
+            connection,
+            greeted: false,
+            welcomed: false,
+            closed: false,
+            locked_in: false,
+            inputs: InputSource::new(script),
+            tick: None,
+            history: VecDeque::with_capacity(INPUT_REDUNDANCY + 1),
+            pings: VecDeque::new(),
+            next_nonce: 0,
+            next_ping: now,
+            last_inputs: None,
+        }
+    }
+
+    fn send(&mut self, message: ClientMessage, measurements: &mut Measurements) {
+        let bytes = message.encode();
+        measurements.messages_sent += 1;
+        measurements.bytes_sent += bytes.len() as u64;
+        let _ = self.connection.send(message.channel(), bytes);
+    }
+
+    fn lobby(&mut self, request: LobbyRequest, measurements: &mut Measurements) {
+        self.send(ClientMessage::Lobby(request), measurements);
+    }
+
+    /// Handles everything the server sent since the last poll.
+    pub fn poll(&mut self, now: Instant, measurements: &mut Measurements) {
+        if self.closed {
+            return;
+        }
+
+        if self.connection.kind() == TransportKind::WebTransport && self.connection.failed_to_open()
+        {
+            measurements.fell_back += 1;
+            self.connection = self.address.connect_fallback();
+        }
+
+        if !self.greeted && self.connection.is_connected() {
+            self.greeted = true;
+            let name = format!("loadtest-{}", self.index);
+            self.send(
+                ClientMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    name,
+                    resume: None,
+                    player: None,
+                },
+                measurements,
+            );
+        }
+
+        while let Some(bytes) = self.connection.receive() {
+            measurements.messages_received += 1;
+            measurements.bytes_received += bytes.len() as u64;
+            match ServerMessage::decode(&bytes) {
+                Ok(message) => self.handle(message, now, measurements),
+                Err(err) => measurements.error(format!("undecodable message: {err}")),
+            }
+        }
+
+        if self.connection.state() == ConnectionState::Closed {
+            self.closed = true;
+            if self.welcomed {
+                measurements.error("connection lost");
+            } else {
+                measurements.failed += 1;
+                let reason = self
+                    .connection
+                    .error()
+                    .map_or("closed before welcome".to_owned(), ToString::to_string);
+                measurements.error(format!("failed to connect: {reason}"));
+            }
+        }
+    }
+
+    fn handle(&mut self, message: ServerMessage, now: Instant, measurements: &mut Measurements) {
+        match message {
+            ServerMessage::Welcome { .. } => {
+                self.welcomed = true;
+                measurements.connected += 1;
+                self.queue(measurements);
+            }
+            ServerMessage::Lobby(LobbyEvent::RoomUpdate(room)) => {
+                if room.phase == RoomPhase::CharacterSelect && !self.locked_in {
+                    self.locked_in = true;
+                    self.lobby(
+                        LobbyRequest::SelectCharacter {
+                            manifest_id: CHARACTER.to_owned(),
+                        },
+                        measurements,
+                    );
+                    self.lobby(LobbyRequest::LockIn, measurements);
+                }
+            }
+            ServerMessage::Lobby(LobbyEvent::MatchStarted { .. }) => {
+                measurements.matches_started += 1;
+                self.tick = Some(0);
+                self.history.clear();
+                self.last_inputs = None;
+            }
+            ServerMessage::Lobby(LobbyEvent::MatchEnded { .. }) => {
+                measurements.matches_finished += 1;
+                self.tick = None;
+                self.locked_in = false;
+                self.lobby(LobbyRequest::RematchVote { rematch: true }, measurements);
+            }
+            // The opponent left or the room timed out: find another one.
+            ServerMessage::Lobby(LobbyEvent::RoomLeft) => {
+                self.tick = None;
+                self.locked_in = false;
+                self.queue(measurements);
+            }
+            ServerMessage::Lobby(LobbyEvent::Rejected { reason }) => {
+                measurements.error(format!("rejected: {reason}"));
+            }
+            ServerMessage::Inputs { .. } => {
+                measurements.ticks_received += 1;
+                if let Some(last) = self.last_inputs {
+                    measurements
+                        .jitter
+                        .push(now.saturating_duration_since(last).abs_diff(TICK));
+                }
+                self.last_inputs = Some(now);
+            }
+            ServerMessage::Pong { nonce } => {
+                if let Some(index) = self.pings.iter().position(|&(sent, _)| sent == nonce) {
+                    let (_, sent_at) = self.pings.remove(index).expect("index is in range");
+                    measurements
+                        .round_trips
+                        .push(now.saturating_duration_since(sent_at));
+                }
+            }
+            ServerMessage::Disconnect { reason } => {
+                measurements.error(format!("disconnected: {reason}"));
+            }
+            _ => {}
+        }
+    }
+
+    fn queue(&mut self, measurements: &mut Measurements) {
+        self.lobby(
+            LobbyRequest::QuickMatch {
+                region: REGION.to_owned(),
+            },
+            measurements,
+        );
+    }
+
+    /// Runs one simulation tick: sends this tick's inputs while playing, and a ping
+    /// now and then.
+    pub fn step(&mut self, now: Instant, measurements: &mut Measurements) {
+        if self.closed || !self.welcomed {
+            return;
+        }
+
+        if let Some(tick) = &mut self.tick {
+            *tick += 1;
+            let tick = *tick;
+            if self.history.len() > INPUT_REDUNDANCY {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.inputs.next_frame());
+            let frames = self.history.iter().copied().collect();
+            self.send(ClientMessage::Input { tick, frames }, measurements);
+        }
+
+        if now >= self.next_ping {
+            self.next_ping = now + PING_INTERVAL;
+            let nonce = self.next_nonce;
+            self.next_nonce = self.next_nonce.wrapping_add(1);
+            if self.pings.len() == MAX_PINGS_IN_FLIGHT {
+                self.pings.pop_front();
+            }
+            self.pings.push_back((nonce, now));
+            self.send(ClientMessage::Ping { nonce }, measurements);
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.connection.close();
+    }
+}
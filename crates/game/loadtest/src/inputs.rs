@@ -0,0 +1,123 @@
+//! Where bots get their inputs: a random walk that looks like someone playing, or a
+//! script looped from a file.
+
+use std::{fs, path::Path, sync::Arc};
+
+use anyhow::{Context, bail};
+use game_common::gameplay::character::input::InputFrame;
+
+/// One script line: hold `frame` for `ticks`.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    ticks: u32,
+    frame: InputFrame,
+}
+
+/// Input script shared by every bot. One step per line, `<ticks> <flags>`, with flags
+/// joined by `+` (`20 RIGHT`, `3 RIGHT+JUMP`, `30 NONE`); `#` starts a comment.
+#[derive(Debug, Clone)]
+pub struct Script {
+    steps: Arc<[Step]>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut steps = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let step = parse_step(line)
+                .with_context(|| format!("{}:{}: invalid step", path.display(), number + 1))?;
+            steps.push(step);
+        }
+        if steps.iter().all(|step| step.ticks == 0) {
+            bail!("{} has no steps", path.display());
+        }
+        Ok(Self {
+            steps: steps.into(),
+        })
+    }
+}
+
+fn parse_step(line: &str) -> anyhow::Result<Step> {
+    let (ticks, flags) = line
+        .split_once(char::is_whitespace)
+        .context("expected `<ticks> <flags>`")?;
+    let ticks = ticks.parse().context("tick count is not a number")?;
+    let mut frame = InputFrame::NONE;
+    for flag in flags.split('+').map(str::trim) {
+        frame |= InputFrame::from_name(&flag.to_ascii_uppercase())
+            .with_context(|| format!("unknown input {flag}"))?;
+    }
+    Ok(Step { ticks, frame })
+}
+
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    /// Holds a random direction for a while, with the odd jump or dash tapped in.
+    Random { held: InputFrame, remaining: u32 },
+    Scripted {
+        script: Script,
+        step: usize,
+        remaining: u32,
+    },
+}
+
+impl InputSource {
+    pub fn new(script: Option<&Script>) -> Self {
+        match script {
+            // Every bot starts at the top; the first call moves on to step zero.
+            Some(script) => InputSource::Scripted {
+                script: script.clone(),
+                step: script.steps.len() - 1,
+                remaining: 0,
+            },
+            None => InputSource::Random {
+                held: InputFrame::NONE,
+                remaining: 0,
+            },
+        }
+    }
+
+    /// Input for the next tick.
+    pub fn next_frame(&mut self) -> InputFrame {
+        match self {
+            InputSource::Random { held, remaining } => {
+                if *remaining == 0 {
+                    *held = match rand::random_range(0..10) {
+                        0..=3 => InputFrame::RIGHT,
+                        4..=7 => InputFrame::LEFT,
+                        8 => InputFrame::DOWN,
+                        _ => InputFrame::NONE,
+                    };
+                    *remaining = rand::random_range(6..45);
+                }
+                *remaining -= 1;
+                let mut frame = *held;
+                if rand::random_bool(0.02) {
+                    frame |= InputFrame::JUMP;
+                }
+                if rand::random_bool(0.01) {
+                    frame |= InputFrame::DASH;
+                }
+                frame
+            }
+            InputSource::Scripted {
+                script,
+                step,
+                remaining,
+            } => {
+                while *remaining == 0 {
+                    *step = (*step + 1) % script.steps.len();
+                    *remaining = script.steps[*step].ticks;
+                }
+                *remaining -= 1;
+                script.steps[*step].frame
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Headless load-test bots for the game server. Opens many connections to a server
+//! running on this machine, plays quick matches with random or scripted inputs at the
+//! simulation rate and prints round-trip times, tick stream jitter and the server's
+//! tick overruns at the end.
+//!
+//! Bots play under a new identity on every run and never touch real players' records,
+//! but they still show up in the stats database, so point the server's `GAME_STATS_DB`
+//! somewhere disposable. The server has no simulation of its own: the
+//! confirmed `Inputs` it sends every tick are its snapshot stream, and their arrival
+//! times are what the jitter is measured on, to the resolution of `POLL_INTERVAL`.
+
+mod args;
+mod bot;
+mod inputs;
+mod report;
+mod scrape;
+
+use std::time::{Duration, Instant};
+
+use game_common::{gameplay::SIM_TICK_HZ, net::ServerAddress};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+use args::{Args, USAGE};
+use bot::Bot;
+use inputs::Script;
+use report::Measurements;
+use scrape::ServerMetrics;
+
+/// One simulation tick.
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / SIM_TICK_HZ as u64);
+/// How often connections are checked for messages, in between ticks.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
+    let Some(args) = Args::parse(std::env::args().skip(1))? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+    let script = args.script.as_deref().map(Script::load).transpose()?;
+    let mut address = ServerAddress::from_host("localhost", false);
+    address.certificate_hash = args.certificate_hash;
+
+    let before = match ServerMetrics::fetch(args.admin_port).await {
+        Ok(metrics) => Some(metrics),
+        Err(err) => {
+            warn!(
+                "cannot read server metrics on port {}: {err}",
+                args.admin_port
+            );
+            None
+        }
+    };
+
+    info!(bots = args.bots, "starting load test");
+    let mut measurements = Measurements::default();
+    let mut bots: Vec<Bot> = Vec::with_capacity(args.bots);
+    let started = Instant::now();
+    let ramp_step = args.ramp.div_f64(args.bots as f64);
+    let end = started + args.ramp + args.duration;
+    let mut next_tick = started;
+
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let interrupted = tokio::signal::ctrl_c();
+    tokio::pin!(interrupted);
+
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {}
+            _ = &mut interrupted => {
+                info!("interrupted, stopping early");
+                break;
+            }
+        }
+        let now = Instant::now();
+        if now >= end {
+            break;
+        }
+
+        while bots.len() < args.bots && now >= started + ramp_step.mul_f64(bots.len() as f64) {
+            let bot = Bot::new(
+                bots.len(),
+                address.clone(),
+                args.websocket,
+                script.as_ref(),
+                now,
+            );
+            bots.push(bot);
+        }
+        for bot in &mut bots {
+            bot.poll(now, &mut measurements);
+        }
+        if now >= next_tick {
+            for bot in &mut bots {
+                bot.step(now, &mut measurements);
+            }
+            next_tick += TICK;
+            // Skip ticks rather than bursting after a stall, like the server does.
+            if next_tick < now {
+                next_tick = now + TICK;
+            }
+        }
+    }
+
+    let elapsed = started.elapsed();
+    for bot in &mut bots {
+        bot.close();
+    }
+    let server = match (before, ServerMetrics::fetch(args.admin_port).await) {
+        (Some(before), Ok(after)) => Some(after.since(&before)),
+        _ => None,
+    };
+    print!(
+        "{}",
+        measurements.render(args.bots, elapsed, server.as_ref())
+    );
+    Ok(())
+}
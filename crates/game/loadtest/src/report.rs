@@ -0,0 +1,123 @@
+//! Measurements collected by the bots and the summary printed at the end.
+
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use crate::scrape::ServerMetrics;
+
+/// Everything the bots observed, summed over all of them.
+#[derive(Debug, Default)]
+pub struct Measurements {
+    /// Ping to pong.
+    pub round_trips: Vec<Duration>,
+    /// How far apart consecutive `Inputs` ticks arrived, minus the tick interval.
+    pub jitter: Vec<Duration>,
+    pub connected: usize,
+    /// Bots that gave up on WebTransport and used WebSocket.
+    pub fell_back: usize,
+    pub failed: usize,
+    pub matches_started: usize,
+    pub matches_finished: usize,
+    pub ticks_received: u64,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Rejections and disconnects by reason.
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl Measurements {
+    pub fn error(&mut self, reason: impl Into<String>) {
+        *self.errors.entry(reason.into()).or_default() += 1;
+    }
+
+    pub fn render(
+        &mut self,
+        bots: usize,
+        elapsed: Duration,
+        server: Option<&ServerMetrics>,
+    ) -> String {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let mut out = String::new();
+        let _ = writeln!(out, "Load test: {bots} bots for {secs:.1}s");
+        let _ = writeln!(
+            out,
+            "  connections   {} connected ({} over WebSocket), {} failed",
+            self.connected, self.fell_back, self.failed
+        );
+        let _ = writeln!(
+            out,
+            "  matches       {} started, {} finished, {} ticks received",
+            self.matches_started, self.matches_finished, self.ticks_received
+        );
+        let _ = writeln!(
+            out,
+            "  traffic       out {:.0} msg/s {}/s, in {:.0} msg/s {}/s",
+            self.messages_sent as f64 / secs,
+            bytes(self.bytes_sent as f64 / secs),
+            self.messages_received as f64 / secs,
+            bytes(self.bytes_received as f64 / secs),
+        );
+        let _ = writeln!(
+            out,
+            "  round trip    {}",
+            distribution(&mut self.round_trips)
+        );
+        let _ = writeln!(out, "  tick jitter   {}", distribution(&mut self.jitter));
+
+        match server {
+            Some(server) => {
+                let _ = writeln!(
+                    out,
+                    "  server ticks  {} run, {} overran ({:.2}%), mean {:.2}ms, p99 <= {}",
+                    server.ticks,
+                    server.overruns,
+                    100.0 * server.overruns as f64 / server.ticks.max(1) as f64,
+                    1000.0 * server.tick_seconds / server.ticks.max(1) as f64,
+                    server
+                        .tick_quantile(0.99)
+                        .map_or("?".to_owned(), |bound| format!("{:.1}ms", bound * 1000.0)),
+                );
+            }
+            None => {
+                let _ = writeln!(out, "  server ticks  unavailable, is the admin port right?");
+            }
+        }
+
+        if !self.errors.is_empty() {
+            let _ = writeln!(out, "  errors");
+            for (reason, count) in &self.errors {
+                let _ = writeln!(out, "    {count:>6}  {reason}");
+            }
+        }
+        out
+    }
+}
+
+/// `p50 / p95 / p99 / max` of `samples`, in milliseconds.
+fn distribution(samples: &mut [Duration]) -> String {
+    if samples.is_empty() {
+        return "no samples".to_owned();
+    }
+    samples.sort_unstable();
+    let at = |q: f64| {
+        let index = ((samples.len() - 1) as f64 * q).round() as usize;
+        samples[index].as_secs_f64() * 1000.0
+    };
+    format!(
+        "p50 {:.2}ms  p95 {:.2}ms  p99 {:.2}ms  max {:.2}ms  ({} samples)",
+        at(0.5),
+        at(0.95),
+        at(0.99),
+        at(1.0),
+        samples.len()
+    )
+}
+
+fn bytes(per_second: f64) -> String {
+    if per_second >= 1_000_000.0 {
+        format!("{:.1} MB", per_second / 1_000_000.0)
+    } else {
+        format!("{:.1} kB", per_second / 1_000.0)
+    }
+}
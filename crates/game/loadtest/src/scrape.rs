@@ -0,0 +1,107 @@
+//! Reads the server's tick counters from its Prometheus endpoint, before and after
+//! the run, so the report can show what the load did to the tick loop.
+
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tick counters of the server, or their growth over the run.
+#[derive(Debug, Clone, Default)]
+pub struct ServerMetrics {
+    pub ticks: u64,
+    pub overruns: u64,
+    pub tick_seconds: f64,
+    /// Cumulative tick duration histogram: upper bound in seconds and count.
+    pub buckets: Vec<(f64, u64)>,
+}
+
+impl ServerMetrics {
+    pub async fn fetch(port: u16) -> anyhow::Result<Self> {
+        let text = tokio::time::timeout(TIMEOUT, get(port, "/metrics"))
+            .await
+            .context("timed out")??;
+        Ok(Self::parse(&text))
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut metrics = Self::default();
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let Some((series, value)) = line.rsplit_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.parse::<f64>() else {
+                continue;
+            };
+            match series {
+                "game_server_tick_duration_seconds_count" => metrics.ticks = value as u64,
+                "game_server_tick_duration_seconds_sum" => metrics.tick_seconds = value,
+                "game_server_tick_overruns_total" => metrics.overruns = value as u64,
+                _ => {
+                    if let Some(bound) = series
+                        .strip_prefix("game_server_tick_duration_seconds_bucket{le=\"")
+                        .and_then(|rest| rest.strip_suffix("\"}"))
+                        .and_then(|bound| bound.parse::<f64>().ok())
+                    {
+                        metrics.buckets.push((bound, value as u64));
+                    }
+                }
+            }
+        }
+        metrics
+    }
+
+    /// What happened between `earlier` and this scrape.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            ticks: self.ticks.saturating_sub(earlier.ticks),
+            overruns: self.overruns.saturating_sub(earlier.overruns),
+            tick_seconds: (self.tick_seconds - earlier.tick_seconds).max(0.0),
+            buckets: self
+                .buckets
+                .iter()
+                .map(|&(bound, count)| {
+                    let before = earlier
+                        .buckets
+                        .iter()
+                        .find(|&&(other, _)| other == bound)
+                        .map_or(0, |&(_, count)| count);
+                    (bound, count.saturating_sub(before))
+                })
+                .collect(),
+        }
+    }
+
+    /// Smallest histogram bound that at least `q` of the ticks stayed under.
+    pub fn tick_quantile(&self, q: f64) -> Option<f64> {
+        let wanted = (self.ticks as f64 * q).ceil() as u64;
+        self.buckets
+            .iter()
+            .find(|&&(_, count)| count >= wanted)
+            .map(|&(bound, _)| bound)
+    }
+}
+
+/// Minimal HTTP/1.1 GET against the server's admin port on localhost.
+async fn get(port: u16, path: &str) -> anyhow::Result<String> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    let request =
+        format!("GET {path} HTTP/1.1\r\nHost: localhost:{port}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .context("malformed HTTP response")?;
+    let status = head.lines().next().unwrap_or_default();
+    if !status.contains(" 200 ") {
+        bail!("{status}");
+    }
+    Ok(body.to_owned())
+}
@@ -0,0 +1,191 @@
+//! HTTP side port: Prometheus metrics on `/metrics` and a JSON admin API under
+//! `/admin`. Handlers never touch server state themselves; they queue an
+//! `AdminRequest` that the tick loop answers between ticks.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use game_common::net::SessionId;
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+use crate::{lobby::Room, metrics::phase_label};
+
+/// Requests waiting for the tick loop; more than this and the API answers 503.
+const QUEUE_SIZE: usize = 64;
+
+#[derive(Debug)]
+pub enum AdminRequest {
+    Metrics(oneshot::Sender<String>),
+    Rooms(oneshot::Sender<Vec<RoomSummary>>),
+    Sessions(oneshot::Sender<Vec<SessionSummary>>),
+    /// Replies whether the session existed.
+    Kick {
+        session: SessionId,
+        reply: oneshot::Sender<bool>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomSummary {
+    pub code: String,
+    pub private: bool,
+    pub phase: &'static str,
+    /// Last confirmed tick of the running match.
+    pub tick: Option<u32>,
+    pub players: Vec<SeatSummary>,
+    pub spectators: Vec<SessionId>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeatSummary {
+    pub session: SessionId,
+    pub name: String,
+    pub character: Option<String>,
+    pub connected: bool,
+}
+
+impl From<&Room> for RoomSummary {
+    fn from(room: &Room) -> Self {
+        Self {
+            code: room.code.clone(),
+            private: room.private,
+            phase: phase_label(room.phase),
+            tick: room.game.as_ref().map(|game| game.tick),
+            players: room
+                .seats
+                .iter()
+                .flatten()
+                .map(|seat| SeatSummary {
+                    session: seat.session,
+                    name: seat.name.clone(),
+                    character: seat.character.clone(),
+                    connected: seat.connected,
+                })
+                .collect(),
+            spectators: room.spectators().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionSummary {
+    pub id: SessionId,
+    pub name: String,
+    pub rating: f64,
+    pub connected: bool,
+    /// Room the session plays in or watches.
+    pub room: Option<String>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub flagged: bool,
+}
+
+#[derive(Clone)]
+struct AdminState {
+    requests: mpsc::Sender<AdminRequest>,
+    /// Bearer token for `/admin`; the admin API is disabled without one.
+    token: Option<Arc<str>>,
+}
+
+pub fn channel() -> (mpsc::Sender<AdminRequest>, mpsc::Receiver<AdminRequest>) {
+    mpsc::channel(QUEUE_SIZE)
+}
+
+/// Binds the side port and serves it in the background.
+pub async fn serve(
+    addr: SocketAddr,
+    token: Option<String>,
+    requests: mpsc::Sender<AdminRequest>,
+) -> anyhow::Result<()> {
+    let state = AdminState {
+        requests,
+        token: token.map(Arc::from),
+    };
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .route("/admin/rooms", get(rooms))
+        .route("/admin/sessions", get(sessions))
+        .route("/admin/sessions/{id}/kick", post(kick))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(%addr, "admin endpoint listening");
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, app).await {
+            error!("admin endpoint failed: {err}");
+        }
+    });
+    Ok(())
+}
+
+async fn metrics(State(state): State<AdminState>) -> Result<impl IntoResponse, StatusCode> {
+    let text = ask(&state, AdminRequest::Metrics).await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text))
+}
+
+async fn rooms(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RoomSummary>>, StatusCode> {
+    authorize(&state, &headers)?;
+    ask(&state, AdminRequest::Rooms).await.map(Json)
+}
+
+async fn sessions(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SessionSummary>>, StatusCode> {
+    authorize(&state, &headers)?;
+    ask(&state, AdminRequest::Sessions).await.map(Json)
+}
+
+async fn kick(
+    State(state): State<AdminState>,
+    Path(session): Path<SessionId>,
+    headers: HeaderMap,
+) -> StatusCode {
+    if let Err(status) = authorize(&state, &headers) {
+        return status;
+    }
+    match ask(&state, |reply| AdminRequest::Kick { session, reply }).await {
+        Ok(true) => StatusCode::NO_CONTENT,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(status) => status,
+    }
+}
+
+fn authorize(state: &AdminState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(token) = &state.token else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if presented == Some(&**token) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Queues a request for the tick loop and waits for its answer.
+async fn ask<T>(
+    state: &AdminState,
+    request: impl FnOnce(oneshot::Sender<T>) -> AdminRequest,
+) -> Result<T, StatusCode> {
+    let (reply, response) = oneshot::channel();
+    state
+        .requests
+        .try_send(request(reply))
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    response.await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)
+}
@@ -0,0 +1,57 @@
+//! Fighters the server simulates matches with, read from the game's asset directory.
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::Context;
+use game_common::gameplay::character::presentation::{
+    CharacterManifestAsset, CharacterRosterAsset,
+};
+
+/// Roster listing every selectable character, relative to the asset directory.
+const ROSTER_PATH: &str = "characters/roster.roster.ron";
+
+/// Manifests of the characters on the roster, in roster order.
+pub struct Characters {
+    manifests: Vec<CharacterManifestAsset>,
+}
+
+impl Characters {
+    /// Reads the roster under `assets_dir` and every manifest it lists.
+    pub fn load(assets_dir: &Path) -> anyhow::Result<Self> {
+        let roster_path = assets_dir.join(ROSTER_PATH);
+        let roster = fs::read(&roster_path)
+            .with_context(|| format!("failed to read roster {}", roster_path.display()))?;
+        let manifests = CharacterRosterAsset::manifest_paths(&roster)
+            .with_context(|| format!("invalid roster {}", roster_path.display()))?
+            .into_iter()
+            .map(|path| {
+                let path = assets_dir.join(path);
+                let bytes = fs::read(&path)
+                    .with_context(|| format!("failed to read manifest {}", path.display()))?;
+                CharacterManifestAsset::from_ron(&bytes)
+                    .with_context(|| format!("invalid manifest {}", path.display()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { manifests })
+    }
+
+    pub fn get(&self, manifest_id: &str) -> Option<&CharacterManifestAsset> {
+        self.manifests
+            .iter()
+            .find(|manifest| manifest.id == manifest_id)
+    }
+
+    /// Character given to players who let the character select timer run out: the
+    /// first on the roster, which is never empty.
+    pub fn default_character(&self) -> &CharacterManifestAsset {
+        &self.manifests[0]
+    }
+}
+
+impl fmt::Debug for Characters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.manifests.iter().map(|manifest| &manifest.id))
+            .finish()
+    }
+}
@@ -0,0 +1,117 @@
+use std::{env, net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use game_common::{
+    gameplay::SIM_TICK_HZ,
+    net::{websocket, webtransport},
+};
+
+/// Runtime settings, read from `GAME_SERVER_*` environment variables.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// UDP address of the WebTransport endpoint.
+    pub webtransport_addr: SocketAddr,
+    /// TCP address of the WebSocket fallback endpoint.
+    pub websocket_addr: SocketAddr,
+    /// PEM certificate chain and key for WebTransport. A self-signed certificate for
+    /// `localhost` is generated when unset.
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    /// How long a dropped player keeps their seat before forfeiting it.
+    pub reconnect_grace: Duration,
+    /// How long a new connection may take to send `Hello`.
+    pub handshake_timeout: Duration,
+    /// How far spectators trail the live match, so they can't relay it to a player.
+    pub spectator_delay: Duration,
+    /// Ratings and match history, shared with the web site through `GAME_STATS_DB`.
+    pub database: PathBuf,
+    /// Where finished matches are saved as replays.
+    pub replay_dir: PathBuf,
+    /// The game's asset directory, whose character roster and manifests the server
+    /// simulates matches with.
+    pub assets_dir: PathBuf,
+    /// TCP address serving `/metrics` and the admin API.
+    pub admin_addr: SocketAddr,
+    /// Bearer token for the admin API, which stays off without one.
+    pub admin_token: Option<String>,
+    /// How long a shutdown waits for running matches to finish; a match lasts at most
+    /// `MAX_MATCH_TICKS`, a little over five minutes.
+    pub drain_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            webtransport_addr: SocketAddr::from(([0, 0, 0, 0], webtransport::DEFAULT_PORT)),
+            websocket_addr: SocketAddr::from(([0, 0, 0, 0], websocket::DEFAULT_PORT)),
+            tls_cert: None,
+            tls_key: None,
+            reconnect_grace: Duration::from_secs(20),
+            handshake_timeout: Duration::from_secs(5),
+            spectator_delay: Duration::from_secs(3),
+            database: game_stats::database_path(),
+            replay_dir: PathBuf::from("replays"),
+            assets_dir: PathBuf::from("assets/game"),
+            admin_addr: SocketAddr::from(([0, 0, 0, 0], 9100)),
+            admin_token: None,
+            drain_timeout: Duration::from_secs(360),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn spectator_delay_ticks(&self) -> u32 {
+        (self.spectator_delay.as_secs_f64() * f64::from(SIM_TICK_HZ)).round() as u32
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        if let Some(addr) = var("GAME_SERVER_WEBTRANSPORT_ADDR") {
+            config.webtransport_addr = addr
+                .parse()
+                .context("GAME_SERVER_WEBTRANSPORT_ADDR is not a socket address")?;
+        }
+        if let Some(addr) = var("GAME_SERVER_WEBSOCKET_ADDR") {
+            config.websocket_addr = addr
+                .parse()
+                .context("GAME_SERVER_WEBSOCKET_ADDR is not a socket address")?;
+        }
+        config.tls_cert = var("GAME_SERVER_TLS_CERT").map(PathBuf::from);
+        config.tls_key = var("GAME_SERVER_TLS_KEY").map(PathBuf::from);
+        if let Some(secs) = var("GAME_SERVER_RECONNECT_GRACE_SECS") {
+            config.reconnect_grace = Duration::from_secs(
+                secs.parse()
+                    .context("GAME_SERVER_RECONNECT_GRACE_SECS is not a number")?,
+            );
+        }
+        if let Some(secs) = var("GAME_SERVER_SPECTATOR_DELAY_SECS") {
+            config.spectator_delay = Duration::from_secs_f64(
+                secs.parse()
+                    .context("GAME_SERVER_SPECTATOR_DELAY_SECS is not a number")?,
+            );
+        }
+        if let Some(dir) = var("GAME_SERVER_REPLAY_DIR") {
+            config.replay_dir = PathBuf::from(dir);
+        }
+        if let Some(dir) = var("GAME_SERVER_ASSETS_DIR") {
+            config.assets_dir = PathBuf::from(dir);
+        }
+        if let Some(addr) = var("GAME_SERVER_ADMIN_ADDR") {
+            config.admin_addr = addr
+                .parse()
+                .context("GAME_SERVER_ADMIN_ADDR is not a socket address")?;
+        }
+        config.admin_token = var("GAME_SERVER_ADMIN_TOKEN");
+        if let Some(secs) = var("GAME_SERVER_DRAIN_SECS") {
+            config.drain_timeout = Duration::from_secs(
+                secs.parse()
+                    .context("GAME_SERVER_DRAIN_SECS is not a number")?,
+            );
+        }
+        Ok(config)
+    }
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
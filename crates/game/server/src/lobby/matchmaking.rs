@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use game_common::net::{PlayerKey, SessionId};
+
+use super::Player;
+
+/// Rating difference accepted as soon as a player enters the queue.
+const BASE_RATING_WINDOW: f64 = 100.0;
+/// How fast the accepted rating difference widens while waiting.
+const RATING_WINDOW_GROWTH_PER_SEC: f64 = 25.0;
+const MAX_RATING_WINDOW: f64 = 800.0;
+/// After waiting this long, players are also paired across regions.
+const CROSS_REGION_AFTER: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone)]
+pub struct QueueEntry {
+    pub session: SessionId,
+    pub player: PlayerKey,
+    pub name: String,
+    pub region: String,
+    pub rating: f64,
+    pub since: Instant,
+}
+
+impl QueueEntry {
+    pub fn player(&self) -> Player<'_> {
+        Player {
+            session: self.session,
+            key: self.player,
+            name: &self.name,
+            rating: self.rating,
+        }
+    }
+
+    fn rating_window(&self, now: Instant) -> f64 {
+        let waited = now.saturating_duration_since(self.since).as_secs_f64();
+        (BASE_RATING_WINDOW + waited * RATING_WINDOW_GROWTH_PER_SEC).min(MAX_RATING_WINDOW)
+    }
+
+    /// Whether both players are willing to be paired with each other right now.
+    fn accepts(&self, other: &QueueEntry, now: Instant) -> bool {
+        let difference = (self.rating - other.rating).abs();
+        let same_region = self.region.eq_ignore_ascii_case(&other.region);
+        let both_waited_long = [self, other]
+            .iter()
+            .all(|entry| now.saturating_duration_since(entry.since) >= CROSS_REGION_AFTER);
+
+        difference <= self.rating_window(now)
+            && difference <= other.rating_window(now)
+            && (same_region || both_waited_long)
+    }
+}
+
+/// Players waiting for a quick match, oldest first.
+#[derive(Debug, Default)]
+pub struct MatchQueue {
+    entries: Vec<QueueEntry>,
+}
+
+impl MatchQueue {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains(&self, session: SessionId) -> bool {
+        self.entries.iter().any(|entry| entry.session == session)
+    }
+
+    /// Adds a player, replacing an earlier entry of the same session.
+    pub fn push(&mut self, entry: QueueEntry) {
+        self.remove(entry.session);
+        self.entries.push(entry);
+    }
+
+    pub fn take_all(&mut self) -> Vec<QueueEntry> {
+        std::mem::take(&mut self.entries)
+    }
+
+    pub fn remove(&mut self, session: SessionId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.session != session);
+        self.entries.len() != len
+    }
+
+    /// Takes every pair that can be matched now. The longest-waiting player is served
+    /// first and gets the closest-rated acceptable opponent.
+    pub fn take_pairs(&mut self, now: Instant) -> Vec<(QueueEntry, QueueEntry)> {
+        let mut matched = vec![false; self.entries.len()];
+        let mut pairs = Vec::new();
+
+        for i in 0..self.entries.len() {
+            if matched[i] {
+                continue;
+            }
+            let entry = &self.entries[i];
+            let best = (i + 1..self.entries.len())
+                .filter(|&j| !matched[j] && entry.accepts(&self.entries[j], now))
+                .min_by(|&a, &b| {
+                    let da = (self.entries[a].rating - entry.rating).abs();
+                    let db = (self.entries[b].rating - entry.rating).abs();
+                    da.total_cmp(&db)
+                });
+            if let Some(j) = best {
+                matched[i] = true;
+                matched[j] = true;
+                pairs.push((i, j));
+            }
+        }
+
+        let taken: Vec<_> = pairs
+            .iter()
+            .map(|&(i, j)| (self.entries[i].clone(), self.entries[j].clone()))
+            .collect();
+        let mut index = 0;
+        self.entries.retain(|_| {
+            index += 1;
+            !matched[index - 1]
+        });
+        taken
+    }
+}
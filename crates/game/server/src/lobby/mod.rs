@@ -0,0 +1,404 @@
+//! Rooms, room codes and the quick-match queue. The lobby never touches connections:
+//! it reacts to requests and queues its replies in an `Outbox` for the server to send.
+
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use game_common::{
+    gameplay::character::input::InputFrame,
+    net::{LobbyEvent, LobbyRequest, PlayerKey, RoomPhase, ServerMessage, SessionId},
+};
+
+use crate::characters::Characters;
+
+mod matchmaking;
+mod room;
+
+use matchmaking::{MatchQueue, QueueEntry};
+pub use room::{FinishedFighter, FinishedMatch, Room};
+
+/// Characters used in room codes; no 0/O or 1/I, so codes survive being read aloud.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LEN: usize = 5;
+
+/// Messages produced while handling lobby traffic, addressed by session, and the
+/// matches that ended meanwhile.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    messages: Vec<(SessionId, ServerMessage)>,
+    finished: Vec<FinishedMatch>,
+}
+
+impl Outbox {
+    pub fn send(&mut self, session: SessionId, message: ServerMessage) {
+        self.messages.push((session, message));
+    }
+
+    pub fn lobby(&mut self, session: SessionId, event: LobbyEvent) {
+        self.send(session, ServerMessage::Lobby(event));
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = (SessionId, ServerMessage)> + '_ {
+        self.messages.drain(..)
+    }
+
+    pub fn finished(&mut self, game: FinishedMatch) {
+        self.finished.push(game);
+    }
+
+    pub fn take_finished(&mut self) -> Vec<FinishedMatch> {
+        std::mem::take(&mut self.finished)
+    }
+}
+
+/// What the lobby needs to know about the player behind a request.
+#[derive(Debug, Clone, Copy)]
+pub struct Player<'a> {
+    pub session: SessionId,
+    pub key: PlayerKey,
+    pub name: &'a str,
+    pub rating: f64,
+}
+
+#[derive(Debug)]
+pub struct Lobby {
+    rooms: HashMap<String, Room>,
+    /// Room code of every seated session.
+    members: HashMap<SessionId, String>,
+    /// Room code of every spectating session.
+    spectators: HashMap<SessionId, String>,
+    queue: MatchQueue,
+    /// Ticks spectators trail the players by.
+    spectator_delay: u32,
+    /// Inputs the server had to repeat for connected players, over all matches.
+    predicted_inputs: u64,
+    /// Set on shutdown: running matches finish, nothing new starts.
+    draining: bool,
+}
+
+impl Lobby {
+    pub fn new(spectator_delay: u32, characters: Arc<Characters>) -> Self {
+        Self {
+            rooms: HashMap::new(),
+            members: HashMap::new(),
+            spectators: HashMap::new(),
+            queue: MatchQueue::default(),
+            spectator_delay,
+            characters,
+            predicted_inputs: 0,
+            draining: false,
+        }
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = &Room> {
+        self.rooms.values()
+    }
+
+    /// Players waiting for a quick match.
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Inputs repeated for connected players whose own input arrived too late. The
+    /// repeat is confirmed for both sides, so a late press is lost rather than replayed.
+    pub fn predicted_inputs(&self) -> u64 {
+        self.predicted_inputs
+    }
+
+    /// Code of the room the session plays in or watches.
+    pub fn room_code_of(&self, session: SessionId) -> Option<&str> {
+        self.members
+            .get(&session)
+            .or_else(|| self.spectators.get(&session))
+            .map(String::as_str)
+    }
+
+    pub fn has_running_matches(&self) -> bool {
+        self.rooms
+            .values()
+            .any(|room| room.phase == RoomPhase::InMatch)
+    }
+
+    /// Stops taking new players: the queue is emptied, rooms without a running match
+    /// close, and the remaining ones close as soon as their match ends.
+    pub fn drain(&mut self, out: &mut Outbox) {
+        self.draining = true;
+        for entry in self.queue.take_all() {
+            out.lobby(entry.session, LobbyEvent::QueueCancelled);
+        }
+        for room in self.rooms.values_mut() {
+            if room.phase != RoomPhase::InMatch {
+                room.close(out);
+            }
+        }
+    }
+
+    fn room_of_mut(&mut self, session: SessionId) -> Option<&mut Room> {
+        self.rooms.get_mut(self.members.get(&session)?)
+    }
+
+    /// Whether the session is seated somewhere or waiting in the queue.
+    pub fn is_active(&self, session: SessionId) -> bool {
+        self.members.contains_key(&session) || self.queue.contains(session)
+    }
+
+    pub fn handle(
+        &mut self,
+        player: Player,
+        request: LobbyRequest,
+        now: Instant,
+        out: &mut Outbox,
+    ) {
+        let result = match request {
+            LobbyRequest::CreateRoom
+            | LobbyRequest::JoinRoom { .. }
+            | LobbyRequest::QuickMatch { .. }
+            | LobbyRequest::Spectate { .. }
+                if self.draining =>
+            {
+                Err("server is shutting down")
+            }
+            LobbyRequest::CreateRoom => self.create_room(player, now, out),
+            LobbyRequest::JoinRoom { code } => self.join_room(player, &code, now, out),
+            LobbyRequest::QuickMatch { region } => self.enqueue(player, region, now, out),
+            LobbyRequest::CancelQuickMatch => {
+                if self.queue.remove(player.session) {
+                    out.lobby(player.session, LobbyEvent::QueueCancelled);
+                }
+                Ok(())
+            }
+            LobbyRequest::LeaveRoom => {
+                self.leave(player.session, now, out);
+                Ok(())
+            }
+            LobbyRequest::SelectCharacter { manifest_id } => self
+                .room_of_mut(player.session)
+                .ok_or("not in a room")
+                .and_then(|room| room.select_character(player.session, manifest_id, out)),
+            LobbyRequest::LockIn => self
+                .room_of_mut(player.session)
+                .ok_or("not in a room")
+                .and_then(|room| room.lock_in(player.session, now, out)),
+            LobbyRequest::RematchVote { rematch } => self
+                .room_of_mut(player.session)
+                .ok_or("not in a room")
+                .and_then(|room| room.vote_rematch(player.session, rematch, now, out)),
+            LobbyRequest::Spectate { code } => self.spectate(player.session, &code, out),
+        };
+
+        if let Err(reason) = result {
+            out.lobby(
+                player.session,
+                LobbyEvent::Rejected {
+                    reason: reason.to_owned(),
+                },
+            );
+        }
+    }
+
+    fn create_room(
+        &mut self,
+        player: Player,
+        now: Instant,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.is_active(player.session) {
+            return Err("already in a room or queue");
+        }
+        self.stop_spectating(player.session, out);
+        let code = self.unused_code();
+        let mut room = Room::new(
+            code.clone(),
+            true,
+            self.spectator_delay,
+            self.characters.clone(),
+            now,
+        );
+        room.join(player, now, out);
+        self.members.insert(player.session, code.clone());
+        self.rooms.insert(code, room);
+        Ok(())
+    }
+
+    fn join_room(
+        &mut self,
+        player: Player,
+        code: &str,
+        now: Instant,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.is_active(player.session) {
+            return Err("already in a room or queue");
+        }
+        let code = code.trim().to_ascii_uppercase();
+        let room = self
+            .rooms
+            .get_mut(&code)
+            .filter(|room| room.private)
+            .ok_or("no room with that code")?;
+        room.join(player, now, out).ok_or("room is full")?;
+        self.members.insert(player.session, code);
+        self.stop_spectating(player.session, out);
+        Ok(())
+    }
+
+    /// Watches any room by code, public or private.
+    fn spectate(
+        &mut self,
+        session: SessionId,
+        code: &str,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.is_active(session) {
+            return Err("already in a room or queue");
+        }
+        let code = code.trim().to_ascii_uppercase();
+        if self.spectators.get(&session) == Some(&code) {
+            return Ok(());
+        }
+        if !self.rooms.contains_key(&code) {
+            return Err("no room with that code");
+        }
+        self.stop_spectating(session, out);
+        let room = self.rooms.get_mut(&code).expect("room exists");
+        room.spectate(session, out)?;
+        self.spectators.insert(session, code);
+        Ok(())
+    }
+
+    fn stop_spectating(&mut self, session: SessionId, out: &mut Outbox) {
+        if let Some(code) = self.spectators.remove(&session)
+            && let Some(room) = self.rooms.get_mut(&code)
+        {
+            room.stop_spectating(session, out);
+        }
+    }
+
+    fn enqueue(
+        &mut self,
+        player: Player,
+        region: String,
+        now: Instant,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.members.contains_key(&player.session) {
+            return Err("already in a room");
+        }
+        self.stop_spectating(player.session, out);
+        let region = region.trim().to_ascii_lowercase();
+        out.lobby(
+            player.session,
+            LobbyEvent::Queued {
+                region: region.clone(),
+            },
+        );
+        self.queue.push(QueueEntry {
+            session: player.session,
+            player: player.key,
+            name: player.name.to_owned(),
+            region,
+            rating: player.rating,
+            since: now,
+        });
+        Ok(())
+    }
+
+    /// Removes a session from its room, the queue or the room it watches.
+    pub fn leave(&mut self, session: SessionId, now: Instant, out: &mut Outbox) {
+        self.queue.remove(session);
+        self.stop_spectating(session, out);
+        if let Some(code) = self.members.remove(&session)
+            && let Some(room) = self.rooms.get_mut(&code)
+        {
+            room.leave(session, now, out);
+        }
+    }
+
+    /// Seed and last confirmed tick of the match the session plays in, if any.
+    pub fn running_match(&self, session: SessionId) -> Option<(u64, u32)> {
+        self.rooms
+            .get(self.members.get(&session)?)?
+            .running_match(session)
+    }
+
+    pub fn submit_inputs(&mut self, session: SessionId, tick: u32, frames: &[InputFrame]) {
+        if let Some(room) = self.room_of_mut(session) {
+            room.submit_inputs(session, tick, frames);
+        }
+    }
+
+    /// The player's connection dropped; their seat is held for the grace period.
+    /// Spectators simply stop watching.
+    pub fn disconnected(&mut self, session: SessionId, out: &mut Outbox) {
+        self.queue.remove(session);
+        self.stop_spectating(session, out);
+        if let Some(room) = self.room_of_mut(session) {
+            room.set_connected(session, false, out);
+        }
+    }
+
+    pub fn reconnected(&mut self, session: SessionId, out: &mut Outbox) {
+        if let Some(room) = self.room_of_mut(session) {
+            out.lobby(
+                session,
+                LobbyEvent::RoomJoined {
+                    code: room.code.clone(),
+                    slot: room.slot_of(session).expect("members are seated"),
+                },
+            );
+            room.set_connected(session, true, out);
+        }
+    }
+
+    /// Pairs queued players, runs every room for one tick and drops closed rooms.
+    pub fn update(&mut self, now: Instant, out: &mut Outbox) {
+        for (a, b) in self.queue.take_pairs(now) {
+            let code = self.unused_code();
+            let mut room = Room::new(
+                code.clone(),
+                false,
+                self.spectator_delay,
+                self.characters.clone(),
+                now,
+            );
+            for entry in [a, b] {
+                room.join(entry.player(), now, out);
+                self.members.insert(entry.session, code.clone());
+            }
+            self.rooms.insert(code, room);
+        }
+
+        for room in self.rooms.values_mut() {
+            room.update(now, out);
+            self.predicted_inputs += room.take_predicted();
+            if self.draining && room.phase != RoomPhase::InMatch && !room.is_closed() {
+                room.close(out);
+            }
+        }
+
+        let (members, spectators) = (&mut self.members, &mut self.spectators);
+        self.rooms.retain(|_, room| {
+            if room.is_closed() {
+                for session in room.sessions() {
+                    members.remove(&session);
+                }
+                for session in room.spectators() {
+                    spectators.remove(&session);
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    fn unused_code(&self) -> String {
+        loop {
+            let code: String = (0..CODE_LEN)
+                .map(|_| CODE_ALPHABET[rand::random_range(0..CODE_ALPHABET.len())] as char)
+                .collect();
+            if !self.rooms.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+}
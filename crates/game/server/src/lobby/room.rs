@@ -0,0 +1,645 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use game_common::{
+    gameplay::{
+        character::{input::InputFrame, presentation::CharacterManifestAsset},
+        headless::HeadlessMatch,
+        replay::{DEFAULT_STAGE, Replay, ReplayFighter, ReplayHeader},
+        versus::PlayerSlot,
+    },
+    net::{LobbyEvent, PlayerKey, RoomInfo, RoomPhase, SeatInfo, ServerMessage, SessionId},
+};
+use tracing::warn;
+
+use super::{Outbox, Player};
+use crate::characters::Characters;
+
+/// Most spectators one room accepts.
+const MAX_SPECTATORS: usize = 32;
+/// Catch-up ticks per spectator message. Even ticks of constantly changing inputs
+/// encode to a few bytes each, far below `MAX_MESSAGE_SIZE`.
+const SPECTATE_CHUNK_TICKS: u32 = 1024;
+
+const CHARACTER_SELECT_TIMEOUT: Duration = Duration::from_secs(30);
+const REMATCH_VOTE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone)]
+pub struct Seat {
+    pub session: SessionId,
+    pub player: PlayerKey,
+    pub name: String,
+    pub character: Option<String>,
+    pub locked_in: bool,
+    pub connected: bool,
+    pub rematch: Option<bool>,
+}
+
+impl Seat {
+    fn new(player: Player) -> Self {
+        Self {
+            session: player.session,
+            player: player.key,
+            name: player.name.to_owned(),
+            character: None,
+            locked_in: false,
+            connected: true,
+            rematch: None,
+        }
+    }
+}
+
+/// A fighter of a match that just ended.
+#[derive(Debug, Clone)]
+pub struct FinishedFighter {
+    pub session: SessionId,
+    pub player: PlayerKey,
+    pub name: String,
+    pub character: String,
+}
+
+/// Result of a match, handed to the server to rate the players and store the replay.
+#[derive(Debug)]
+pub struct FinishedMatch {
+    pub fighters: [FinishedFighter; 2],
+    /// Who won the most rounds in the server's own simulation, or the opponent of a
+    /// player who left. `None` for a draw.
+    pub winner: Option<PlayerSlot>,
+    /// Rounds each fighter won, indexed by `PlayerSlot`.
+    pub rounds_won: [u8; 2],
+    /// Confirmed inputs of every tick, replayable by the client.
+    pub replay: Replay,
+}
+
+/// Inputs of a running match. The server is the authority on which input each fighter
+/// had on each tick: late inputs are dropped and the previous frame repeats. It runs
+/// the confirmed inputs through its own simulation, which decides the rounds.
+#[derive(Debug)]
+pub struct MatchState {
+    pub seed: u64,
+    /// Last confirmed tick.
+    pub tick: u32,
+    pending: [BTreeMap<u32, InputFrame>; 2],
+    last: [InputFrame; 2],
+    sim: HeadlessMatch,
+    replay: Replay,
+    /// Ticks already streamed to spectators.
+    spectated: u32,
+}
+
+impl MatchState {
+    fn new(seed: u64, manifests: [&CharacterManifestAsset; 2]) -> Self {
+        let fighters = PlayerSlot::ALL
+            .into_iter()
+            .zip(manifests)
+            .map(|(slot, manifest)| ReplayFighter {
+                manifest_id: manifest.id.clone(),
+                content_hash: manifest.content_hash,
+                spawn: slot.start_position(),
+            })
+            .collect();
+        Self {
+            seed,
+            tick: 0,
+            pending: Default::default(),
+            last: Default::default(),
+            sim: HeadlessMatch::new(manifests.map(Clone::clone)),
+            replay: Replay::new(ReplayHeader {
+                game_version: env!("CARGO_PKG_VERSION").to_owned(),
+                stage: DEFAULT_STAGE.to_owned(),
+                rng_seed: seed,
+                fighters,
+            }),
+            spectated: 0,
+        }
+    }
+
+    /// What a new spectator starts from: the match up to the last streamed tick, as a
+    /// `SpectateStart` followed by as many `SpectateChunk`s as it takes.
+    ///
+    /// Spectators re-simulate these inputs from tick zero instead of starting from a
+    /// snapshot. The simulation is deterministic, so the inputs rebuild exactly the state
+    /// the players see, at a few bytes per tick; a snapshot would have to capture every
+    /// gameplay component and be kept in step with each one added. The viewer catches up
+    /// at `MAX_SEEK_TICKS_PER_FRAME` ticks per frame, about a second per 20 minutes played.
+    fn spectate_start(&self) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + SPECTATE_CHUNK_TICKS).min(self.spectated);
+            let replay = match self.replay.slice(start..end).encode() {
+                Ok(replay) => replay,
+                Err(err) => {
+                    warn!(
+                        seed = self.seed,
+                        "cannot stream the match to spectators: {err}"
+                    );
+                    return Vec::new();
+                }
+            };
+            messages.push(match start {
+                0 => ServerMessage::SpectateStart { replay },
+                _ => ServerMessage::SpectateChunk { start, replay },
+            });
+            if end == self.spectated {
+                return messages;
+            }
+            start = end;
+        }
+    }
+
+    /// Stores inputs for `tick` and the ticks before it (oldest first), skipping any
+    /// already confirmed.
+    pub fn submit(&mut self, slot: PlayerSlot, tick: u32, frames: &[InputFrame]) {
+        let pending = &mut self.pending[slot.index()];
+        for (age, frame) in frames.iter().rev().enumerate() {
+            let Some(frame_tick) = tick.checked_sub(age as u32) else {
+                break;
+            };
+            if frame_tick <= self.tick {
+                break;
+            }
+            pending.insert(frame_tick, *frame);
+        }
+    }
+
+    /// Confirms the next tick. Fighters without an input for it repeat their last one,
+    /// or stand still when `connected` says their player is gone. Also returns how
+    /// many inputs were repeated for connected players.
+    fn advance(&mut self, connected: [bool; 2]) -> (Vec<(PlayerSlot, InputFrame)>, u64) {
+        self.tick += 1;
+        let tick = self.tick;
+        let mut predicted = 0;
+        let frames: Vec<_> = PlayerSlot::ALL
+            .into_iter()
+            .map(|slot| {
+                let i = slot.index();
+                let pending = &mut self.pending[i];
+                let frame = match pending.remove(&tick) {
+                    Some(frame) => frame,
+                    None if connected[i] => {
+                        predicted += 1;
+                        self.last[i]
+                    }
+                    None => InputFrame::NONE,
+                };
+                pending.retain(|&t, _| t > tick);
+                self.last[i] = frame;
+                (slot, frame)
+            })
+            .collect();
+        self.sim.step(self.last);
+        self.replay.push_tick(&self.last);
+        (frames, predicted)
+    }
+}
+
+#[derive(Debug)]
+pub struct Room {
+    pub code: String,
+    /// Created by code rather than by the quick-match queue.
+    pub private: bool,
+    pub phase: RoomPhase,
+    pub seats: [Option<Seat>; 2],
+    pub game: Option<MatchState>,
+    spectators: Vec<SessionId>,
+    /// How many ticks spectators trail the players by.
+    spectator_delay: u32,
+    characters: Arc<Characters>,
+    /// Inputs repeated for connected players since the last `take_predicted`.
+    predicted: u64,
+    phase_since: Instant,
+    closed: bool,
+}
+
+impl Room {
+    pub fn new(
+        code: String,
+        private: bool,
+        spectator_delay: u32,
+        characters: Arc<Characters>,
+        now: Instant,
+    ) -> Self {
+        Self {
+            code,
+            private,
+            phase: RoomPhase::Waiting,
+            seats: Default::default(),
+            game: None,
+            spectators: Vec::new(),
+            spectator_delay,
+            characters,
+            predicted: 0,
+            phase_since: now,
+            closed: false,
+        }
+    }
+
+    /// A room is discarded once closed or empty.
+    pub fn is_closed(&self) -> bool {
+        self.closed || self.seats.iter().all(Option::is_none)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.seats.iter().all(Option::is_some)
+    }
+
+    pub fn sessions(&self) -> impl Iterator<Item = SessionId> + '_ {
+        self.seats.iter().flatten().map(|seat| seat.session)
+    }
+
+    pub fn spectators(&self) -> impl Iterator<Item = SessionId> + '_ {
+        self.spectators.iter().copied()
+    }
+
+    pub fn slot_of(&self, session: SessionId) -> Option<PlayerSlot> {
+        PlayerSlot::ALL.into_iter().find(|slot| {
+            self.seats[slot.index()]
+                .as_ref()
+                .is_some_and(|seat| seat.session == session)
+        })
+    }
+
+    fn seat_mut(&mut self, session: SessionId) -> Option<&mut Seat> {
+        self.seats
+            .iter_mut()
+            .flatten()
+            .find(|seat| seat.session == session)
+    }
+
+    fn set_phase(&mut self, phase: RoomPhase, now: Instant) {
+        self.phase = phase;
+        self.phase_since = now;
+    }
+
+    pub fn info(&self) -> RoomInfo {
+        RoomInfo {
+            code: self.code.clone(),
+            phase: self.phase,
+            seats: PlayerSlot::ALL
+                .into_iter()
+                .filter_map(|slot| {
+                    self.seats[slot.index()].as_ref().map(|seat| SeatInfo {
+                        slot,
+                        name: seat.name.clone(),
+                        character: seat.character.clone(),
+                        locked_in: seat.locked_in,
+                        connected: seat.connected,
+                        rematch: seat.rematch,
+                    })
+                })
+                .collect(),
+            spectators: self.spectators.len() as u32,
+        }
+    }
+
+    /// Sends to the seated players.
+    pub fn broadcast(&self, message: ServerMessage, out: &mut Outbox) {
+        for session in self.sessions() {
+            out.send(session, message.clone());
+        }
+    }
+
+    /// Sends to the seated players and the spectators.
+    fn broadcast_all(&self, message: ServerMessage, out: &mut Outbox) {
+        for session in self.sessions().chain(self.spectators()) {
+            out.send(session, message.clone());
+        }
+    }
+
+    pub fn broadcast_info(&self, out: &mut Outbox) {
+        self.broadcast_all(
+            ServerMessage::Lobby(LobbyEvent::RoomUpdate(self.info())),
+            out,
+        );
+    }
+
+    /// Seats a player in the first free slot, moving to character select once full.
+    pub fn join(&mut self, player: Player, now: Instant, out: &mut Outbox) -> Option<PlayerSlot> {
+        if self.phase != RoomPhase::Waiting {
+            return None;
+        }
+        let slot = PlayerSlot::ALL
+            .into_iter()
+            .find(|slot| self.seats[slot.index()].is_none())?;
+        self.seats[slot.index()] = Some(Seat::new(player));
+        out.lobby(
+            player.session,
+            LobbyEvent::RoomJoined {
+                code: self.code.clone(),
+                slot,
+            },
+        );
+
+        if self.is_full() {
+            self.set_phase(RoomPhase::CharacterSelect, now);
+        }
+        self.broadcast_info(out);
+        Some(slot)
+    }
+
+    /// Frees a player's seat. Leaving mid-match forfeits it to the opponent.
+    pub fn leave(&mut self, session: SessionId, now: Instant, out: &mut Outbox) {
+        let Some(slot) = self.slot_of(session) else {
+            return;
+        };
+        if self.phase == RoomPhase::InMatch {
+            let winner = PlayerSlot::ALL.into_iter().find(|&other| other != slot);
+            self.end_match(winner, now, out);
+        }
+        self.seats[slot.index()] = None;
+        out.lobby(session, LobbyEvent::RoomLeft);
+
+        // Quick-match rooms only exist for the pairing that created them.
+        if !self.private || self.seats.iter().all(Option::is_none) {
+            self.close(out);
+            return;
+        }
+        for seat in self.seats.iter_mut().flatten() {
+            seat.locked_in = false;
+            seat.rematch = None;
+        }
+        self.game = None;
+        self.set_phase(RoomPhase::Waiting, now);
+        self.broadcast_info(out);
+    }
+
+    pub fn spectate(&mut self, session: SessionId, out: &mut Outbox) -> Result<(), &'static str> {
+        if self.spectators.len() >= MAX_SPECTATORS {
+            return Err("too many spectators in this room");
+        }
+        self.spectators.push(session);
+        out.lobby(
+            session,
+            LobbyEvent::Spectating {
+                code: self.code.clone(),
+            },
+        );
+        if let Some(game) = &self.game {
+            for message in game.spectate_start() {
+                out.send(session, message);
+            }
+        }
+        self.broadcast_info(out);
+        Ok(())
+    }
+
+    pub fn stop_spectating(&mut self, session: SessionId, out: &mut Outbox) {
+        self.spectators.retain(|&spectator| spectator != session);
+        out.lobby(session, LobbyEvent::RoomLeft);
+        self.broadcast_info(out);
+    }
+
+    pub fn set_connected(&mut self, session: SessionId, connected: bool, out: &mut Outbox) {
+        let Some(seat) = self.seat_mut(session) else {
+            return;
+        };
+        seat.connected = connected;
+        self.broadcast_info(out);
+
+        // A returning player needs the match setup again to rejoin the simulation.
+        if connected
+            && self.phase == RoomPhase::InMatch
+            && let Some(game) = &self.game
+        {
+            out.lobby(session, self.match_started(game.seed));
+        }
+    }
+
+    pub fn select_character(
+        &mut self,
+        session: SessionId,
+        manifest_id: String,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.phase != RoomPhase::CharacterSelect {
+            return Err("characters can only be picked during character select");
+        }
+        if self.characters.get(&manifest_id).is_none() {
+            return Err("unknown character");
+        }
+        let seat = self.seat_mut(session).ok_or("not seated in this room")?;
+        if seat.locked_in {
+            return Err("character is already locked in");
+        }
+        seat.character = Some(manifest_id);
+        self.broadcast_info(out);
+        Ok(())
+    }
+
+    pub fn lock_in(
+        &mut self,
+        session: SessionId,
+        now: Instant,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.phase != RoomPhase::CharacterSelect {
+            return Err("nothing to lock in outside character select");
+        }
+        let seat = self.seat_mut(session).ok_or("not seated in this room")?;
+        if seat.character.is_none() {
+            return Err("pick a character first");
+        }
+        seat.locked_in = true;
+
+        if self.seats.iter().flatten().all(|seat| seat.locked_in) {
+            self.start_match(now, out);
+        } else {
+            self.broadcast_info(out);
+        }
+        Ok(())
+    }
+
+    pub fn vote_rematch(
+        &mut self,
+        session: SessionId,
+        rematch: bool,
+        now: Instant,
+        out: &mut Outbox,
+    ) -> Result<(), &'static str> {
+        if self.phase != RoomPhase::PostMatch {
+            return Err("no match to vote on");
+        }
+        let seat = self.seat_mut(session).ok_or("not seated in this room")?;
+        seat.rematch = Some(rematch);
+
+        if !rematch {
+            self.close(out);
+        } else if self
+            .seats
+            .iter()
+            .flatten()
+            .all(|seat| seat.rematch == Some(true))
+        {
+            // Characters carry over, but have to be confirmed again.
+            for seat in self.seats.iter_mut().flatten() {
+                seat.locked_in = false;
+                seat.rematch = None;
+            }
+            self.game = None;
+            self.set_phase(RoomPhase::CharacterSelect, now);
+            self.broadcast_info(out);
+        } else {
+            self.broadcast_info(out);
+        }
+        Ok(())
+    }
+
+    /// Seed and last confirmed tick of the match `session` plays in, while it runs.
+    pub fn running_match(&self, session: SessionId) -> Option<(u64, u32)> {
+        let game = self.game.as_ref()?;
+        (self.phase == RoomPhase::InMatch && self.slot_of(session).is_some())
+            .then_some((game.seed, game.tick))
+    }
+
+    pub fn submit_inputs(&mut self, session: SessionId, tick: u32, frames: &[InputFrame]) {
+        if self.phase != RoomPhase::InMatch {
+            return;
+        }
+        if let (Some(slot), Some(game)) = (self.slot_of(session), &mut self.game) {
+            game.submit(slot, tick, frames);
+        }
+    }
+
+    /// Runs one server tick: advances the match or expires phase timers.
+    pub fn update(&mut self, now: Instant, out: &mut Outbox) {
+        let elapsed = now.saturating_duration_since(self.phase_since);
+        match self.phase {
+            RoomPhase::Waiting => {}
+            RoomPhase::CharacterSelect if elapsed >= CHARACTER_SELECT_TIMEOUT => {
+                for seat in self.seats.iter_mut().flatten() {
+                    seat.character
+                        .get_or_insert_with(|| self.characters.default_character().id.clone());
+                    seat.locked_in = true;
+                }
+                self.start_match(now, out);
+            }
+            RoomPhase::CharacterSelect => {}
+            RoomPhase::InMatch => {
+                let connected = PlayerSlot::ALL.map(|slot| {
+                    self.seats[slot.index()]
+                        .as_ref()
+                        .is_some_and(|seat| seat.connected)
+                });
+                let Some(game) = &mut self.game else {
+                    return;
+                };
+                let (frames, predicted) = game.advance(connected);
+                self.predicted += predicted;
+                let tick = game.tick;
+                let rounds = game.sim.rounds();
+                let result = rounds.is_over().then(|| rounds.winner());
+                self.broadcast(ServerMessage::Inputs { tick, frames }, out);
+                if let Some(winner) = result {
+                    self.end_match(winner, now, out);
+                }
+            }
+            RoomPhase::PostMatch if elapsed >= REMATCH_VOTE_TIMEOUT => self.close(out),
+            RoomPhase::PostMatch => {}
+        }
+        self.stream_to_spectators(out);
+    }
+
+    /// Sends spectators the next confirmed tick once it is `spectator_delay` old. After
+    /// the match ends they catch up with its last ticks at normal speed.
+    fn stream_to_spectators(&mut self, out: &mut Outbox) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        let available = match self.phase {
+            RoomPhase::InMatch => game.tick.saturating_sub(self.spectator_delay),
+            _ => game.tick,
+        };
+        if game.spectated >= available {
+            return;
+        }
+        let Some(frames) = game.replay.tick(game.spectated) else {
+            return;
+        };
+        let frames = PlayerSlot::ALL
+            .into_iter()
+            .zip(frames.iter().copied())
+            .collect();
+        game.spectated += 1;
+        let message = ServerMessage::Inputs {
+            tick: game.spectated,
+            frames,
+        };
+        for &session in &self.spectators {
+            out.send(session, message.clone());
+        }
+    }
+
+    fn match_started(&self, seed: u64) -> LobbyEvent {
+        LobbyEvent::MatchStarted {
+            seed,
+            fighters: PlayerSlot::ALL
+                .into_iter()
+                .filter_map(|slot| {
+                    let seat = self.seats[slot.index()].as_ref()?;
+                    Some((slot, seat.character.clone()?))
+                })
+                .collect(),
+        }
+    }
+
+    fn start_match(&mut self, now: Instant, out: &mut Outbox) {
+        let seed = rand::random();
+        let characters = &self.characters;
+        let manifests = self.seats.each_ref().map(|seat| {
+            seat.as_ref()
+                .and_then(|seat| characters.get(seat.character.as_deref()?))
+                .unwrap_or_else(|| characters.default_character())
+        });
+        let game = MatchState::new(seed, manifests);
+        let start = game.spectate_start();
+        for &session in &self.spectators {
+            for message in &start {
+                out.send(session, message.clone());
+            }
+        }
+        self.game = Some(game);
+        self.set_phase(RoomPhase::InMatch, now);
+        self.broadcast(ServerMessage::Lobby(self.match_started(seed)), out);
+        self.broadcast_info(out);
+    }
+
+    fn end_match(&mut self, winner: Option<PlayerSlot>, now: Instant, out: &mut Outbox) {
+        // The match state stays around until spectators have seen the end of it.
+        if let Some(game) = &self.game
+            && let [Some(one), Some(two)] = &self.seats
+        {
+            let seats = [one, two];
+            let fighters = PlayerSlot::ALL.map(|slot| FinishedFighter {
+                session: seats[slot.index()].session,
+                player: seats[slot.index()].player,
+                name: seats[slot.index()].name.clone(),
+                character: game.replay.header.fighters[slot.index()]
+                    .manifest_id
+                    .clone(),
+            });
+            out.finished(FinishedMatch {
+                fighters,
+                winner,
+                rounds_won: game.sim.rounds().wins,
+                replay: game.replay.clone(),
+            });
+        }
+        self.set_phase(RoomPhase::PostMatch, now);
+        self.broadcast(ServerMessage::Lobby(LobbyEvent::MatchEnded { winner }), out);
+        self.broadcast_info(out);
+    }
+
+    pub fn take_predicted(&mut self) -> u64 {
+        std::mem::take(&mut self.predicted)
+    }
+
+    /// Sends everyone home; the lobby drops the room on its next update.
+    pub fn close(&mut self, out: &mut Outbox) {
+        self.closed = true;
+        self.broadcast_all(ServerMessage::Lobby(LobbyEvent::RoomLeft), out);
+    }
+}
@@ -1 +1,34 @@
-fn main() {}
+mod admin;
+mod characters;
+mod config;
+mod lobby;
+mod metrics;
+mod server;
+mod session;
+mod validation;
+
+use config::ServerConfig;
+use server::Server;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    init_logging();
+    let config = ServerConfig::from_env()?;
+    let (admin_tx, admin_rx) = admin::channel();
+    admin::serve(config.admin_addr, config.admin_token.clone(), admin_tx).await?;
+    let mut server = Server::bind(config).await?;
+    server.run(admin_rx).await
+}
+
+/// Logs filtered by `RUST_LOG` (`info` by default), as JSON lines when
+/// `GAME_SERVER_LOG_FORMAT=json`.
+fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    if std::env::var("GAME_SERVER_LOG_FORMAT").is_ok_and(|format| format == "json") {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
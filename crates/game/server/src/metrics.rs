@@ -0,0 +1,194 @@
+//! Server-wide counters and the Prometheus text format they are scraped in.
+
+use std::{fmt::Display, fmt::Write, time::Duration};
+
+use game_common::net::RoomPhase;
+
+use crate::validation::Violation;
+
+/// Upper bounds of the tick duration histogram, in seconds. One tick at 60 Hz is
+/// 16.7 ms.
+const TICK_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.002, 0.004, 0.008, 0.0167, 0.033, 0.1];
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    violations: [u64; Violation::ALL.len()],
+    flagged_sessions: u64,
+    kicked_sessions: u64,
+    matches_finished: u64,
+    tick_buckets: [u64; TICK_BUCKETS.len()],
+    tick_count: u64,
+    tick_seconds: f64,
+    /// Ticks that took longer than the tick interval.
+    tick_overruns: u64,
+}
+
+impl Metrics {
+    pub fn violation(&mut self, violation: Violation) {
+        self.violations[violation.index()] += 1;
+    }
+
+    pub fn session_flagged(&mut self) {
+        self.flagged_sessions += 1;
+    }
+
+    pub fn session_kicked(&mut self) {
+        self.kicked_sessions += 1;
+    }
+
+    pub fn match_finished(&mut self) {
+        self.matches_finished += 1;
+    }
+
+    pub fn tick(&mut self, duration: Duration, interval: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.tick_buckets.iter_mut().zip(TICK_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.tick_count += 1;
+        self.tick_seconds += seconds;
+        if duration > interval {
+            self.tick_overruns += 1;
+        }
+    }
+
+    pub fn write(&self, out: &mut Exposition) {
+        out.family(
+            "game_server_tick_duration_seconds",
+            "histogram",
+            "Time spent running one server tick.",
+        );
+        for (count, bound) in self.tick_buckets.iter().zip(TICK_BUCKETS) {
+            out.sample(
+                "game_server_tick_duration_seconds_bucket",
+                &[("le", &bound.to_string())],
+                count,
+            );
+        }
+        out.sample(
+            "game_server_tick_duration_seconds_bucket",
+            &[("le", "+Inf")],
+            self.tick_count,
+        )
+        .sample(
+            "game_server_tick_duration_seconds_sum",
+            &[],
+            self.tick_seconds,
+        )
+        .sample(
+            "game_server_tick_duration_seconds_count",
+            &[],
+            self.tick_count,
+        );
+
+        out.family(
+            "game_server_tick_overruns_total",
+            "counter",
+            "Ticks that took longer than the tick interval.",
+        )
+        .sample("game_server_tick_overruns_total", &[], self.tick_overruns);
+
+        out.family(
+            "game_server_matches_finished_total",
+            "counter",
+            "Matches played to the end or forfeited.",
+        )
+        .sample(
+            "game_server_matches_finished_total",
+            &[],
+            self.matches_finished,
+        );
+
+        out.family(
+            "game_server_violations_total",
+            "counter",
+            "Messages rejected by input validation, by kind.",
+        );
+        for violation in Violation::ALL {
+            out.sample(
+                "game_server_violations_total",
+                &[("kind", violation.as_str())],
+                self.violations[violation.index()],
+            );
+        }
+        out.family(
+            "game_server_flagged_sessions_total",
+            "counter",
+            "Sessions flagged for repeated violations.",
+        )
+        .sample(
+            "game_server_flagged_sessions_total",
+            &[],
+            self.flagged_sessions,
+        )
+        .family(
+            "game_server_kicked_sessions_total",
+            "counter",
+            "Sessions disconnected for repeated violations.",
+        )
+        .sample(
+            "game_server_kicked_sessions_total",
+            &[],
+            self.kicked_sessions,
+        );
+    }
+}
+
+pub fn phase_label(phase: RoomPhase) -> &'static str {
+    match phase {
+        RoomPhase::Waiting => "waiting",
+        RoomPhase::CharacterSelect => "character_select",
+        RoomPhase::InMatch => "in_match",
+        RoomPhase::PostMatch => "post_match",
+    }
+}
+
+/// Builds a scrape response in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+        self
+    }
+
+    pub fn sample(
+        &mut self,
+        name: &str,
+        labels: &[(&str, &str)],
+        value: impl Display,
+    ) -> &mut Self {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            self.text.push('{');
+            for (i, (label, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.text.push(',');
+                }
+                let _ = write!(self.text, "{label}=\"");
+                for c in value.chars() {
+                    match c {
+                        '\\' => self.text.push_str("\\\\"),
+                        '"' => self.text.push_str("\\\""),
+                        '\n' => self.text.push_str("\\n"),
+                        c => self.text.push(c),
+                    }
+                }
+                self.text.push('"');
+            }
+            self.text.push('}');
+        }
+        let _ = writeln!(self.text, " {value}");
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
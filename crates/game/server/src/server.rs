@@ -0,0 +1,675 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Context;
+use game_common::{
+    gameplay::{SIM_TICK_HZ, replay::REPLAY_EXTENSION},
+    net::{
+        ClientMessage, Connection, ConnectionState, Listener, PROTOCOL_VERSION, RoomPhase,
+        ServerMessage, SessionId, websocket, webtransport,
+    },
+};
+use game_stats::{MatchParticipant, MatchRecord, StatsDb, StatsError};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+use wtransport::tls::Sha256DigestFmt;
+
+use crate::{
+    admin::{AdminRequest, RoomSummary, SessionSummary},
+    characters::Characters,
+    config::ServerConfig,
+    lobby::{FinishedFighter, FinishedMatch, Lobby, Outbox, Player},
+    metrics::{Exposition, Metrics, phase_label},
+    session::Session,
+    validation::{Verdict, Violation},
+};
+
+/// Time given to the transports to deliver the goodbye before the process exits.
+const SHUTDOWN_FLUSH: Duration = Duration::from_millis(200);
+
+/// A connection that has not said `Hello` yet.
+struct PendingConnection {
+    connection: Connection,
+    since: Instant,
+}
+
+pub struct Server {
+    config: ServerConfig,
+    listeners: Vec<Listener>,
+    pending: Vec<PendingConnection>,
+    sessions: HashMap<SessionId, Session>,
+    next_session: SessionId,
+    lobby: Lobby,
+    outbox: Outbox,
+    stats: StatsDb,
+    metrics: Metrics,
+    tick: u32,
+}
+
+impl Server {
+    pub fn new(
+        config: ServerConfig,
+        listeners: Vec<Listener>,
+        stats: StatsDb,
+        characters: Characters,
+    ) -> Self {
+        let lobby = Lobby::new(config.spectator_delay_ticks(), Arc::new(characters));
+        Self {
+            config,
+            listeners,
+            pending: Vec::new(),
+            sessions: HashMap::new(),
+            next_session: 1,
+            lobby,
+            outbox: Outbox::default(),
+            stats,
+            metrics: Metrics::default(),
+            tick: 0,
+        }
+    }
+
+    /// Loads the characters, then opens the stats database and the WebTransport and
+    /// WebSocket endpoints from `config`.
+    pub async fn bind(config: ServerConfig) -> anyhow::Result<Self> {
+        let characters = Characters::load(&config.assets_dir)?;
+        info!(?characters, "characters loaded");
+        let stats = StatsDb::open(&config.database).with_context(|| {
+            format!(
+                "failed to open stats database {}",
+                config.database.display()
+            )
+        })?;
+        fs::create_dir_all(&config.replay_dir).with_context(|| {
+            format!(
+                "failed to create replay directory {}",
+                config.replay_dir.display()
+            )
+        })?;
+
+        let identity = match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => webtransport::Identity::load_pemfiles(cert, key).await?,
+            _ => {
+                let identity =
+                    webtransport::Identity::self_signed(["localhost", "127.0.0.1", "::1"])?;
+                let hash = identity.certificate_chain().as_slice()[0]
+                    .hash()
+                    .fmt(Sha256DigestFmt::DottedHex);
+                warn!(
+                    certificate_hash = %hash,
+                    "no TLS certificate configured, using a self-signed one for localhost"
+                );
+                identity
+            }
+        };
+        let webtransport = webtransport::listen(config.webtransport_addr, identity).await?;
+        let websocket = websocket::listen(config.websocket_addr).await?;
+        info!(
+            webtransport = %config.webtransport_addr,
+            websocket = %config.websocket_addr,
+            "game server listening"
+        );
+        Ok(Self::new(
+            config,
+            vec![webtransport, websocket],
+            stats,
+            characters,
+        ))
+    }
+
+    /// Ticks the server at the simulation rate, answering `admin` between ticks. On
+    /// SIGTERM or Ctrl-C the server drains: it stops accepting players, lets running
+    /// matches finish for up to the drain timeout, then says goodbye and returns.
+    pub async fn run(&mut self, mut admin: mpsc::Receiver<AdminRequest>) -> anyhow::Result<()> {
+        let period = Duration::from_secs_f64(1.0 / f64::from(SIM_TICK_HZ));
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+        let mut drain_deadline = None;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                () = &mut shutdown, if drain_deadline.is_none() => {
+                    info!(
+                        matches = self.lobby.has_running_matches(),
+                        "shutting down, draining running matches"
+                    );
+                    drain_deadline = Some(Instant::now() + self.config.drain_timeout);
+                    self.listeners.clear();
+                    self.lobby.drain(&mut self.outbox);
+                    continue;
+                }
+            }
+
+            let now = Instant::now();
+            while let Ok(request) = admin.try_recv() {
+                self.answer(request, now);
+            }
+            self.tick(now);
+            self.metrics.tick(now.elapsed(), period);
+
+            if let Some(deadline) = drain_deadline
+                && (!self.lobby.has_running_matches() || Instant::now() >= deadline)
+            {
+                break;
+            }
+        }
+
+        info!(
+            sessions = self.sessions.len(),
+            "disconnecting remaining sessions"
+        );
+        for session in self.sessions.values_mut() {
+            session.kick("server is shutting down");
+        }
+        tokio::time::sleep(SHUTDOWN_FLUSH).await;
+        Ok(())
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        self.tick = self.tick.wrapping_add(1);
+        self.accept(now);
+        self.handshake(now);
+        self.receive(now);
+        self.lobby.update(now, &mut self.outbox);
+        self.flush();
+        self.expire(now);
+        for game in self.outbox.take_finished() {
+            self.metrics.match_finished();
+            self.record(game);
+        }
+    }
+
+    fn answer(&mut self, request: AdminRequest, now: Instant) {
+        match request {
+            AdminRequest::Metrics(reply) => {
+                let _ = reply.send(self.render_metrics());
+            }
+            AdminRequest::Rooms(reply) => {
+                let mut rooms: Vec<RoomSummary> =
+                    self.lobby.rooms().map(RoomSummary::from).collect();
+                rooms.sort_by(|a, b| a.code.cmp(&b.code));
+                let _ = reply.send(rooms);
+            }
+            AdminRequest::Sessions(reply) => {
+                let mut sessions: Vec<SessionSummary> = self
+                    .sessions
+                    .values()
+                    .map(|session| SessionSummary {
+                        id: session.id,
+                        name: session.name.clone(),
+                        rating: session.rating,
+                        connected: session.disconnected_at.is_none(),
+                        room: self.lobby.room_code_of(session.id).map(str::to_owned),
+                        bytes_sent: session.bytes_sent,
+                        bytes_received: session.bytes_received,
+                        flagged: session.guard.is_flagged(),
+                    })
+                    .collect();
+                sessions.sort_by_key(|session| session.id);
+                let _ = reply.send(sessions);
+            }
+            AdminRequest::Kick { session, reply } => {
+                let found = match self.sessions.get_mut(&session) {
+                    Some(target) => {
+                        warn!(session, name = %target.name, "session kicked by an administrator");
+                        target.kick("kicked by an administrator");
+                        self.end_session(session, now);
+                        true
+                    }
+                    None => false,
+                };
+                let _ = reply.send(found);
+            }
+        }
+    }
+
+    fn render_metrics(&self) -> String {
+        let mut out = Exposition::default();
+        self.metrics.write(&mut out);
+
+        out.family("game_server_rooms", "gauge", "Open rooms, by phase.");
+        for phase in [
+            RoomPhase::Waiting,
+            RoomPhase::CharacterSelect,
+            RoomPhase::InMatch,
+            RoomPhase::PostMatch,
+        ] {
+            let rooms = self
+                .lobby
+                .rooms()
+                .filter(|room| room.phase == phase)
+                .count();
+            out.sample("game_server_rooms", &[("phase", phase_label(phase))], rooms);
+        }
+
+        let connected = self
+            .sessions
+            .values()
+            .filter(|session| session.disconnected_at.is_none())
+            .count();
+        let spectators: usize = self
+            .lobby
+            .rooms()
+            .map(|room| room.spectators().count())
+            .sum();
+        out.family(
+            "game_server_sessions",
+            "gauge",
+            "Sessions, by whether their connection is up or held for reconnection.",
+        )
+        .sample("game_server_sessions", &[("state", "connected")], connected)
+        .sample(
+            "game_server_sessions",
+            &[("state", "disconnected")],
+            self.sessions.len() - connected,
+        )
+        .family(
+            "game_server_spectators",
+            "gauge",
+            "Sessions watching a room.",
+        )
+        .sample("game_server_spectators", &[], spectators)
+        .family(
+            "game_server_queued_players",
+            "gauge",
+            "Players waiting for a quick match.",
+        )
+        .sample("game_server_queued_players", &[], self.lobby.queue_len())
+        .family(
+            "game_server_predicted_inputs_total",
+            "counter",
+            "Ticks confirmed with a connected player's previous input repeated because \
+             theirs had not arrived in time.",
+        )
+        .sample(
+            "game_server_predicted_inputs_total",
+            &[],
+            self.lobby.predicted_inputs(),
+        );
+
+        let mut sessions: Vec<&Session> = self.sessions.values().collect();
+        sessions.sort_by_key(|session| session.id);
+        out.family(
+            "game_server_session_sent_bytes_total",
+            "counter",
+            "Message bytes sent to each session.",
+        );
+        for session in &sessions {
+            out.sample(
+                "game_server_session_sent_bytes_total",
+                &[("session", &session.id.to_string())],
+                session.bytes_sent,
+            );
+        }
+        out.family(
+            "game_server_session_received_bytes_total",
+            "counter",
+            "Message bytes received from each session.",
+        );
+        for session in &sessions {
+            out.sample(
+                "game_server_session_received_bytes_total",
+                &[("session", &session.id.to_string())],
+                session.bytes_received,
+            );
+        }
+        out.finish()
+    }
+
+    fn accept(&mut self, now: Instant) {
+        for listener in &mut self.listeners {
+            while let Some(connection) = listener.accept() {
+                debug!(transport = ?connection.kind(), "connection accepted");
+                self.pending.push(PendingConnection {
+                    connection,
+                    since: now,
+                });
+            }
+        }
+    }
+
+    /// Turns connections that said `Hello` into sessions, resuming dropped ones.
+    fn handshake(&mut self, now: Instant) {
+        let pending = std::mem::take(&mut self.pending);
+        for mut pending in pending {
+            let Some(bytes) = pending.connection.receive() else {
+                let alive = pending.connection.state() != ConnectionState::Closed
+                    && now.saturating_duration_since(pending.since) < self.config.handshake_timeout;
+                if alive {
+                    self.pending.push(pending);
+                }
+                continue;
+            };
+
+            let (name, resume, player) = match ClientMessage::decode(&bytes) {
+                Ok(ClientMessage::Hello {
+                    version,
+                    name,
+                    resume,
+                    player,
+                }) if version == PROTOCOL_VERSION => (name, resume, player),
+                Ok(ClientMessage::Hello { version, .. }) => {
+                    refuse(
+                        pending.connection,
+                        &format!(
+                            "protocol version {version} is not supported, expected {PROTOCOL_VERSION}"
+                        ),
+                    );
+                    continue;
+                }
+                _ => {
+                    refuse(pending.connection, "expected hello");
+                    continue;
+                }
+            };
+
+            let resumed = resume.and_then(|token| {
+                self.sessions
+                    .values()
+                    .find(|session| session.resume_token == token)
+                    .map(|session| session.id)
+            });
+            let id = match resumed {
+                Some(id) => {
+                    info!(session = id, "session resumed");
+                    id
+                }
+                None => {
+                    let id = self.next_session;
+                    self.next_session += 1;
+                    info!(session = id, %name, "session started");
+                    id
+                }
+            };
+            let session = match self.sessions.entry(id) {
+                Entry::Occupied(entry) => {
+                    let session = entry.into_mut();
+                    session.attach(pending.connection);
+                    session
+                }
+                Entry::Vacant(entry) => {
+                    let mut session = Session::new(id, &name, pending.connection, now);
+                    // Keys the stats database has never seen are replaced by the fresh one,
+                    // so a client cannot pick its own identity.
+                    let known = player.and_then(|key| match self.stats.player_by_key(key) {
+                        Ok(record) => record.map(|record| (key, record)),
+                        Err(err) => {
+                            warn!(session = id, "failed to load player: {err}");
+                            None
+                        }
+                    });
+                    if let Some((key, record)) = known {
+                        session.player = key;
+                        session.rating = record.rating.rating;
+                    }
+                    entry.insert(session)
+                }
+            };
+
+            session.send(&ServerMessage::Welcome {
+                session: session.id,
+                resume_token: session.resume_token,
+                player: session.player,
+                tick: self.tick,
+            });
+            if resumed.is_some() {
+                self.lobby.reconnected(id, &mut self.outbox);
+            }
+        }
+    }
+
+    fn receive(&mut self, now: Instant) {
+        let mut kicked = Vec::new();
+        for session in self.sessions.values_mut() {
+            while let Some(message) = session.receive() {
+                let checked = session.guard.allow_message(now).and_then(|()| {
+                    let message = message.map_err(|err| {
+                        debug!(session = session.id, "undecodable message: {err}");
+                        Violation::Undecodable
+                    })?;
+                    if let ClientMessage::Input { tick, .. } = &message {
+                        let game = self
+                            .lobby
+                            .running_match(session.id)
+                            .ok_or(Violation::UnexpectedInput)?;
+                        session.guard.check_input(*tick, game, now)?;
+                    }
+                    Ok(message)
+                });
+                let message = match checked {
+                    Ok(message) => message,
+                    Err(violation) => {
+                        if report(session, violation, now, &mut self.metrics) {
+                            kicked.push(session.id);
+                            break;
+                        }
+                        continue;
+                    }
+                };
+
+                match message {
+                    ClientMessage::Lobby(request) => {
+                        let player = Player {
+                            session: session.id,
+                            key: session.player,
+                            name: &session.name,
+                            rating: session.rating,
+                        };
+                        self.lobby.handle(player, request, now, &mut self.outbox);
+                    }
+                    ClientMessage::Input { tick, frames } => {
+                        self.lobby.submit_inputs(session.id, tick, &frames);
+                    }
+                    ClientMessage::Ping { nonce } => {
+                        session.send(&ServerMessage::Pong { nonce });
+                    }
+                    ClientMessage::Hello { .. } => {
+                        debug!(session = session.id, "ignoring repeated hello");
+                    }
+                }
+            }
+
+            if session.detach_if_closed(now) {
+                info!(session = session.id, "connection lost, holding session");
+                self.lobby.disconnected(session.id, &mut self.outbox);
+            }
+        }
+
+        // Kicked sessions forfeit right away instead of waiting out the grace period.
+        for id in kicked {
+            self.end_session(id, now);
+        }
+    }
+
+    /// Forgets a session, forfeiting its seat.
+    fn end_session(&mut self, id: SessionId, now: Instant) {
+        self.lobby.leave(id, now, &mut self.outbox);
+        self.sessions.remove(&id);
+    }
+
+    fn flush(&mut self) {
+        for (session, message) in self.outbox.drain() {
+            if let Some(session) = self.sessions.get_mut(&session) {
+                session.send(&message);
+            }
+        }
+    }
+
+    /// Forgets sessions whose grace period ran out, forfeiting their seats.
+    fn expire(&mut self, now: Instant) {
+        let grace = self.config.reconnect_grace;
+        let expired: Vec<SessionId> = self
+            .sessions
+            .values()
+            .filter(|session| {
+                session.disconnected_at.is_some_and(|since| {
+                    !self.lobby.is_active(session.id)
+                        || now.saturating_duration_since(since) >= grace
+                })
+            })
+            .map(|session| session.id)
+            .collect();
+
+        for id in expired {
+            info!(session = id, "session ended");
+            self.end_session(id, now);
+        }
+    }
+
+    /// Saves the replay of a finished match and updates both players' ratings.
+    fn record(&mut self, game: FinishedMatch) {
+        let file_name = format!("{:016x}.{REPLAY_EXTENSION}", game.replay.header.rng_seed);
+        let replay = match game.replay.encode() {
+            Ok(bytes) => match fs::write(self.config.replay_dir.join(&file_name), bytes) {
+                Ok(()) => Some(file_name),
+                Err(err) => {
+                    warn!("failed to save replay {file_name}: {err}");
+                    None
+                }
+            },
+            Err(err) => {
+                warn!("failed to encode replay: {err}");
+                None
+            }
+        };
+
+        match self.record_match(&game, replay) {
+            Ok(ratings) => {
+                for (fighter, rating) in game.fighters.iter().zip(ratings) {
+                    if let Some(session) = self.sessions.get_mut(&fighter.session) {
+                        session.rating = rating;
+                    }
+                }
+            }
+            Err(err) => error!("failed to record match: {err}"),
+        }
+    }
+
+    fn record_match(
+        &mut self,
+        game: &FinishedMatch,
+        replay: Option<String>,
+    ) -> Result<[f64; 2], StatsError> {
+        let [one, two] = &game.fighters;
+        let participant = |fighter: &FinishedFighter| {
+            Ok::<_, StatsError>(MatchParticipant {
+                player: self
+                    .stats
+                    .register_player(fighter.player, &fighter.name)?
+                    .id,
+                character: fighter.character.clone(),
+            })
+        };
+        let participants = [participant(one)?, participant(two)?];
+        let (id, ratings) = self.stats.record_match(&MatchRecord {
+            participants,
+            winner: game.winner.map(|slot| slot.index()),
+            round_scores: game.rounds_won,
+            duration_ticks: game.replay.tick_count(),
+            replay,
+            played_at: SystemTime::now(),
+        })?;
+        info!(
+            match_id = id,
+            "match recorded: {} {:.0} vs {} {:.0}, rounds {}-{}",
+            one.name,
+            ratings[0].rating,
+            two.name,
+            ratings[1].rating,
+            game.rounds_won[0],
+            game.rounds_won[1]
+        );
+        Ok(ratings.map(|rating| rating.rating))
+    }
+}
+
+#[cfg(test)]
+impl Server {
+    pub fn session(&self, id: SessionId) -> Option<&Session> {
+        self.sessions.get(&id)
+    }
+}
+
+/// Logs and counts a violation, returning whether the session has to go.
+fn report(
+    session: &mut Session,
+    violation: Violation,
+    now: Instant,
+    metrics: &mut Metrics,
+) -> bool {
+    metrics.violation(violation);
+    match session.guard.record(violation, now) {
+        Verdict::Tolerate => {
+            debug!(
+                session = session.id,
+                violation = violation.as_str(),
+                "violation"
+            );
+            false
+        }
+        Verdict::Flag => {
+            metrics.session_flagged();
+            warn!(
+                session = session.id,
+                name = %session.name,
+                violation = violation.as_str(),
+                "session flagged for misbehaving"
+            );
+            false
+        }
+        Verdict::Kick => {
+            metrics.session_kicked();
+            let counts: Vec<String> = session
+                .guard
+                .counts()
+                .map(|(violation, count)| format!("{}={count}", violation.as_str()))
+                .collect();
+            warn!(
+                session = session.id,
+                name = %session.name,
+                violations = %counts.join(","),
+                "kicking misbehaving session"
+            );
+            session.kick("too many invalid messages");
+            true
+        }
+    }
+}
+
+/// Resolves on SIGTERM, as sent by container runtimes, or Ctrl-C.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+                return;
+            }
+            Err(err) => warn!("cannot listen for SIGTERM: {err}"),
+        }
+    }
+    if let Err(err) = tokio::signal::ctrl_c().await {
+        warn!("cannot listen for Ctrl-C: {err}");
+        std::future::pending::<()>().await;
+    }
+}
+
+fn refuse(mut connection: Connection, reason: &str) {
+    debug!("refusing connection: {reason}");
+    let message = ServerMessage::Disconnect {
+        reason: reason.to_owned(),
+    };
+    let _ = connection.send(message.channel(), message.encode());
+    connection.close();
+}
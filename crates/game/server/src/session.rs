@@ -0,0 +1,113 @@
+use std::time::Instant;
+
+use game_common::net::{
+    ClientMessage, Connection, ConnectionState, PlayerKey, ResumeToken, ServerMessage, SessionId,
+    TransportError, codec::DecodeError,
+};
+use game_stats::Rating;
+use tracing::{debug, warn};
+
+use crate::validation::Guard;
+
+const MAX_NAME_LEN: usize = 24;
+
+/// A player known to the server. Outlives its connection for the reconnection grace
+/// period, so a client that drops can resume with its `resume_token`.
+#[derive(Debug)]
+pub struct Session {
+    pub id: SessionId,
+    pub resume_token: ResumeToken,
+    /// Identity the player's stats are kept under; the name is display only.
+    pub player: PlayerKey,
+    pub name: String,
+    /// Glicko-2 rating used for matchmaking, loaded from the stats database.
+    pub rating: f64,
+    connection: Option<Connection>,
+    /// When the connection was lost, while waiting for a reconnect.
+    pub disconnected_at: Option<Instant>,
+    pub guard: Guard,
+    /// Encoded message bytes, over every connection of the session.
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl Session {
+    pub fn new(id: SessionId, name: &str, connection: Connection, now: Instant) -> Self {
+        Self {
+            id,
+            resume_token: rand::random(),
+            player: rand::random(),
+            name: sanitize_name(name),
+            rating: Rating::default().rating,
+            connection: Some(connection),
+            disconnected_at: None,
+            guard: Guard::new(now),
+            bytes_sent: 0,
+            bytes_received: 0,
+        }
+    }
+
+    /// Replaces the connection, e.g. on reconnect.
+    pub fn attach(&mut self, connection: Connection) {
+        self.connection = Some(connection);
+        self.disconnected_at = None;
+    }
+
+    /// Drops a closed connection, returning whether it just went away.
+    pub fn detach_if_closed(&mut self, now: Instant) -> bool {
+        let closed = self
+            .connection
+            .as_ref()
+            .is_some_and(|connection| connection.state() == ConnectionState::Closed);
+        if closed {
+            self.connection = None;
+            self.disconnected_at = Some(now);
+        }
+        closed
+    }
+
+    pub fn send(&mut self, message: &ServerMessage) {
+        if let Some(connection) = &mut self.connection {
+            let bytes = message.encode();
+            let len = bytes.len() as u64;
+            match connection.send(message.channel(), bytes) {
+                Ok(()) => self.bytes_sent += len,
+                // Noticed and handled on the next receive pass.
+                Err(TransportError::Closed) => {
+                    debug!(session = self.id, "dropping message to a closed connection");
+                }
+                Err(err) => warn!(session = self.id, "failed to send message: {err}"),
+            }
+        }
+    }
+
+    /// Drops the connection after telling the client why.
+    pub fn kick(&mut self, reason: &str) {
+        self.send(&ServerMessage::Disconnect {
+            reason: reason.to_owned(),
+        });
+        if let Some(connection) = &mut self.connection {
+            connection.close();
+        }
+    }
+
+    pub fn receive(&mut self) -> Option<Result<ClientMessage, DecodeError>> {
+        let bytes = self.connection.as_mut()?.receive()?;
+        self.bytes_received += bytes.len() as u64;
+        Some(ClientMessage::decode(&bytes))
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LEN)
+        .collect();
+    if name.is_empty() {
+        "Player".to_owned()
+    } else {
+        name
+    }
+}
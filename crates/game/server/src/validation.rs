@@ -0,0 +1,478 @@
+//! Defences against crafted clients. Clients only ever send inputs and lobby
+//! requests; anything else fails to decode. On top of that every session is rate
+//! limited, and its input ticks must follow the match clock at a plausible rate.
+//! Violations add to a decaying strike score, and sessions that keep misbehaving are
+//! disconnected.
+
+use std::time::Instant;
+
+use game_common::gameplay::SIM_TICK_HZ;
+
+/// How far a client's input tick may run ahead of the server's match tick.
+pub const MAX_INPUT_LEAD: u32 = SIM_TICK_HZ / 2;
+/// Messages per second a session may send: inputs every tick plus lobby traffic.
+const MESSAGE_RATE: f64 = SIM_TICK_HZ as f64 * 2.0;
+const MESSAGE_BURST: f64 = MESSAGE_RATE;
+/// Input ticks a client may advance per second; a little over the tick rate covers
+/// clock drift.
+const TICK_RATE: f64 = SIM_TICK_HZ as f64 * 1.1;
+/// Ticks a client may skip ahead at once, e.g. when catching up after a hitch.
+const TICK_BURST: f64 = (SIM_TICK_HZ / 4) as f64;
+/// Strike score at which a session is flagged, and at which it gets disconnected.
+/// One strike decays per second.
+const FLAG_STRIKES: f64 = 5.0;
+const KICK_STRIKES: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// A message that is not a valid `ClientMessage`, including any attempt to
+    /// send state.
+    Undecodable,
+    /// More messages than `MESSAGE_RATE` allows; the excess is dropped.
+    RateLimited,
+    /// Inputs while not playing in a running match.
+    UnexpectedInput,
+    /// Inputs for a tick more than `MAX_INPUT_LEAD` ahead of the match.
+    FutureInput,
+    /// Input ticks advancing faster than real time.
+    TickRate,
+}
+
+impl Violation {
+    pub const ALL: [Violation; 5] = [
+        Violation::Undecodable,
+        Violation::RateLimited,
+        Violation::UnexpectedInput,
+        Violation::FutureInput,
+        Violation::TickRate,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Violation::Undecodable => "undecodable",
+            Violation::RateLimited => "rate_limited",
+            Violation::UnexpectedInput => "unexpected_input",
+            Violation::FutureInput => "future_input",
+            Violation::TickRate => "tick_rate",
+        }
+    }
+
+    /// Strikes added per occurrence. Dropped messages are cheap, since a burst of
+    /// them is usually one hiccup; malformed ones are not.
+    fn strikes(self) -> f64 {
+        match self {
+            Violation::RateLimited => 0.1,
+            Violation::UnexpectedInput => 0.5,
+            Violation::Undecodable | Violation::FutureInput | Violation::TickRate => 1.0,
+        }
+    }
+}
+
+/// What to do with a session after a violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Tolerate,
+    /// The session just crossed the flagging threshold.
+    Flag,
+    Kick,
+}
+
+/// Token bucket refilling at `rate` per second up to `burst`.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated = now;
+    }
+
+    fn take(&mut self, amount: f64, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens < amount {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+}
+
+/// Input ticks seen from one session in one match.
+#[derive(Debug, Clone)]
+struct InputClock {
+    seed: u64,
+    newest: u32,
+    budget: TokenBucket,
+}
+
+/// Per-session validation state.
+#[derive(Debug, Clone)]
+pub struct Guard {
+    messages: TokenBucket,
+    inputs: Option<InputClock>,
+    strikes: f64,
+    struck_at: Instant,
+    flagged: bool,
+    counts: [u32; Violation::ALL.len()],
+}
+
+impl Guard {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            messages: TokenBucket::new(MESSAGE_RATE, MESSAGE_BURST, now),
+            inputs: None,
+            strikes: 0.0,
+            struck_at: now,
+            flagged: false,
+            counts: [0; Violation::ALL.len()],
+        }
+    }
+
+    /// Spends one message from the rate limit.
+    pub fn allow_message(&mut self, now: Instant) -> Result<(), Violation> {
+        if self.messages.take(1.0, now) {
+            Ok(())
+        } else {
+            Err(Violation::RateLimited)
+        }
+    }
+
+    /// Checks an input packet for `tick` against the running match, identified by its
+    /// seed, whose last confirmed tick is `match_tick`.
+    pub fn check_input(
+        &mut self,
+        tick: u32,
+        (seed, match_tick): (u64, u32),
+        now: Instant,
+    ) -> Result<(), Violation> {
+        if tick > match_tick.saturating_add(MAX_INPUT_LEAD) {
+            return Err(Violation::FutureInput);
+        }
+
+        let clock = match &mut self.inputs {
+            Some(clock) if clock.seed == seed => clock,
+            // First input of this match; the lead check above bounds where it starts.
+            _ => {
+                self.inputs = Some(InputClock {
+                    seed,
+                    newest: tick,
+                    budget: TokenBucket::new(TICK_RATE, TICK_BURST, now),
+                });
+                return Ok(());
+            }
+        };
+        // Older ticks are the redundant tail of the packet or reordered datagrams.
+        if tick <= clock.newest {
+            return Ok(());
+        }
+        if !clock.budget.take(f64::from(tick - clock.newest), now) {
+            return Err(Violation::TickRate);
+        }
+        clock.newest = tick;
+        Ok(())
+    }
+
+    /// Counts a violation and decides the session's fate.
+    pub fn record(&mut self, violation: Violation, now: Instant) -> Verdict {
+        self.counts[violation.index()] += 1;
+
+        let elapsed = now.saturating_duration_since(self.struck_at).as_secs_f64();
+        self.strikes = (self.strikes - elapsed).max(0.0) + violation.strikes();
+        self.struck_at = now;
+
+        if self.strikes >= KICK_STRIKES {
+            Verdict::Kick
+        } else if self.strikes >= FLAG_STRIKES && !self.flagged {
+            self.flagged = true;
+            Verdict::Flag
+        } else {
+            Verdict::Tolerate
+        }
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    /// Violations recorded so far, by kind.
+    pub fn counts(&self) -> impl Iterator<Item = (Violation, u32)> + '_ {
+        Violation::ALL
+            .into_iter()
+            .map(|violation| (violation, self.counts[violation.index()]))
+            .filter(|&(_, count)| count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use game_common::{
+        gameplay::character::input::InputFrame,
+        net::{
+            Channel, ClientMessage, Connection, ConnectionState, LobbyEvent, LobbyRequest,
+            LoopbackConnector, PROTOCOL_VERSION, ServerMessage, SessionId, loopback_server,
+        },
+    };
+    use game_stats::StatsDb;
+
+    use super::*;
+    use crate::{characters::Characters, config::ServerConfig, server::Server};
+
+    /// A client that speaks the protocol over loopback but sends whatever it likes.
+    struct FakeClient {
+        connection: Connection,
+        session: SessionId,
+    }
+
+    impl FakeClient {
+        fn connect(server: &mut Server, connector: &LoopbackConnector, now: Instant) -> Self {
+            let mut client = Self {
+                connection: connector.connect(),
+                session: 0,
+            };
+            client.send(ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: "mallory".to_owned(),
+                resume: None,
+                player: None,
+            });
+            server.tick(now);
+            client.session = client
+                .receive()
+                .into_iter()
+                .find_map(|message| match message {
+                    ServerMessage::Welcome { session, .. } => Some(session),
+                    _ => None,
+                })
+                .expect("welcome");
+            client
+        }
+
+        fn send(&mut self, message: ClientMessage) {
+            self.send_raw(message.encode());
+        }
+
+        fn send_raw(&mut self, bytes: Vec<u8>) {
+            self.connection
+                .send(Channel::Reliable, bytes)
+                .expect("loopback send");
+        }
+
+        fn input(&mut self, tick: u32) {
+            self.send(ClientMessage::Input {
+                tick,
+                frames: vec![InputFrame::NONE],
+            });
+        }
+
+        fn receive(&mut self) -> Vec<ServerMessage> {
+            std::iter::from_fn(|| self.connection.receive())
+                .map(|bytes| ServerMessage::decode(&bytes).expect("server message"))
+                .collect()
+        }
+    }
+
+    fn server() -> (Server, LoopbackConnector) {
+        let (listener, connector) = loopback_server();
+        let stats = StatsDb::open(":memory:").expect("in-memory stats database");
+        let characters = Characters::load(Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../assets/game"
+        )))
+        .expect("game characters");
+        let server = Server::new(ServerConfig::default(), vec![listener], stats, characters);
+        (server, connector)
+    }
+
+    /// Two clients seated in a private room with a match running.
+    fn start_match(
+        server: &mut Server,
+        connector: &LoopbackConnector,
+        now: Instant,
+    ) -> [FakeClient; 2] {
+        let mut host = FakeClient::connect(server, connector, now);
+        let mut guest = FakeClient::connect(server, connector, now);
+        host.send(ClientMessage::Lobby(LobbyRequest::CreateRoom));
+        server.tick(now);
+        let code = host
+            .receive()
+            .into_iter()
+            .find_map(|message| match message {
+                ServerMessage::Lobby(LobbyEvent::RoomJoined { code, .. }) => Some(code),
+                _ => None,
+            })
+            .expect("room code");
+
+        guest.send(ClientMessage::Lobby(LobbyRequest::JoinRoom { code }));
+        server.tick(now);
+        for client in [&mut host, &mut guest] {
+            client.send(ClientMessage::Lobby(LobbyRequest::SelectCharacter {
+                manifest_id: "naruto".to_owned(),
+            }));
+            client.send(ClientMessage::Lobby(LobbyRequest::LockIn));
+        }
+        server.tick(now);
+        for client in [&mut host, &mut guest] {
+            let started = client.receive().into_iter().any(|message| {
+                matches!(
+                    message,
+                    ServerMessage::Lobby(LobbyEvent::MatchStarted { .. })
+                )
+            });
+            assert!(started, "match started for session {}", client.session);
+        }
+        [host, guest]
+    }
+
+    fn count(server: &Server, client: &FakeClient, violation: Violation) -> u32 {
+        let session = server.session(client.session).expect("session");
+        session
+            .guard
+            .counts()
+            .find_map(|(counted, count)| (counted == violation).then_some(count))
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn undecodable_packet_is_counted() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let mut client = FakeClient::connect(&mut server, &connector, now);
+
+        client.send_raw(vec![0xff; 8]);
+        server.tick(now);
+
+        assert_eq!(count(&server, &client, Violation::Undecodable), 1);
+        assert!(!server.session(client.session).unwrap().guard.is_flagged());
+    }
+
+    #[test]
+    fn inputs_outside_a_match_are_unexpected() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let mut client = FakeClient::connect(&mut server, &connector, now);
+
+        client.input(1);
+        server.tick(now);
+
+        assert_eq!(count(&server, &client, Violation::UnexpectedInput), 1);
+    }
+
+    #[test]
+    fn input_too_far_ahead_of_the_match_is_rejected() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let [mut player, _guest] = start_match(&mut server, &connector, now);
+
+        player.input(MAX_INPUT_LEAD + 10);
+        server.tick(now);
+
+        assert_eq!(count(&server, &player, Violation::FutureInput), 1);
+    }
+
+    #[test]
+    fn input_ticks_faster_than_real_time_are_rejected() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let [mut player, _guest] = start_match(&mut server, &connector, now);
+
+        // Within the lead of the match, but a jump the tick budget cannot cover
+        // without any time passing.
+        player.input(1);
+        player.input(1 + TICK_BURST as u32 + 5);
+        server.tick(now);
+
+        assert_eq!(count(&server, &player, Violation::FutureInput), 0);
+        assert_eq!(count(&server, &player, Violation::TickRate), 1);
+    }
+
+    #[test]
+    fn message_flood_is_rate_limited_and_flagged() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let mut client = FakeClient::connect(&mut server, &connector, now);
+
+        let flood = MESSAGE_BURST as u32 + 80;
+        for nonce in 0..flood {
+            client.send(ClientMessage::Ping { nonce });
+        }
+        server.tick(now);
+
+        assert_eq!(count(&server, &client, Violation::RateLimited), 80);
+        let session = server.session(client.session).expect("flagged, not kicked");
+        assert!(session.guard.is_flagged());
+        let pongs = client
+            .receive()
+            .into_iter()
+            .filter(|message| matches!(message, ServerMessage::Pong { .. }))
+            .count();
+        assert_eq!(pongs, MESSAGE_BURST as usize);
+    }
+
+    #[test]
+    fn repeated_garbage_gets_the_session_kicked() {
+        let now = Instant::now();
+        let (mut server, connector) = server();
+        let mut client = FakeClient::connect(&mut server, &connector, now);
+
+        for _ in 0..KICK_STRIKES as usize + 5 {
+            client.send_raw(vec![0xff; 8]);
+        }
+        server.tick(now);
+
+        assert!(server.session(client.session).is_none());
+        let disconnected = client
+            .receive()
+            .into_iter()
+            .any(|message| matches!(message, ServerMessage::Disconnect { .. }));
+        assert!(disconnected);
+        assert_eq!(client.connection.state(), ConnectionState::Closed);
+    }
+
+    #[test]
+    fn strikes_flag_once_then_kick() {
+        let now = Instant::now();
+        let mut guard = Guard::new(now);
+        let verdicts: Vec<Verdict> = (0..KICK_STRIKES as usize)
+            .map(|_| guard.record(Violation::Undecodable, now))
+            .collect();
+
+        let flagged_at = FLAG_STRIKES as usize - 1;
+        assert!(
+            verdicts[..flagged_at]
+                .iter()
+                .all(|&v| v == Verdict::Tolerate)
+        );
+        assert_eq!(verdicts[flagged_at], Verdict::Flag);
+        assert!(
+            verdicts[flagged_at + 1..verdicts.len() - 1]
+                .iter()
+                .all(|&v| v == Verdict::Tolerate)
+        );
+        assert_eq!(verdicts.last(), Some(&Verdict::Kick));
+        assert_eq!(
+            guard.counts().collect::<Vec<_>>(),
+            [(Violation::Undecodable, KICK_STRIKES as u32)]
+        );
+    }
+}
@@ -0,0 +1,660 @@
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, params};
+
+use crate::glicko2::{Outcome, Rating};
+
+pub type PlayerId = i64;
+pub type MatchId = i64;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("database schema version {0} is newer than this build supports")]
+    UnsupportedSchema(i64),
+    #[error("unknown player {0}")]
+    UnknownPlayer(PlayerId),
+}
+
+/// Schema migrations, applied in order; `PRAGMA user_version` counts the applied ones.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE players (
+        id          INTEGER PRIMARY KEY,
+        key         INTEGER NOT NULL UNIQUE,
+        name        TEXT NOT NULL,
+        rating      REAL NOT NULL,
+        deviation   REAL NOT NULL,
+        volatility  REAL NOT NULL,
+        wins        INTEGER NOT NULL DEFAULT 0,
+        losses      INTEGER NOT NULL DEFAULT 0,
+        draws       INTEGER NOT NULL DEFAULT 0,
+        created_at  INTEGER NOT NULL
+    );
+    CREATE TABLE matches (
+        id              INTEGER PRIMARY KEY,
+        played_at       INTEGER NOT NULL,
+        duration_ticks  INTEGER NOT NULL,
+        replay          TEXT
+    );
+    CREATE TABLE match_players (
+        match_id        INTEGER NOT NULL REFERENCES matches(id),
+        slot            INTEGER NOT NULL,
+        player_id       INTEGER NOT NULL REFERENCES players(id),
+        character       TEXT NOT NULL,
+        outcome         INTEGER,
+        rating_before   REAL NOT NULL,
+        rating_after    REAL NOT NULL,
+        PRIMARY KEY (match_id, slot)
+    );
+    CREATE INDEX match_players_by_player ON match_players(player_id, match_id);
+"#,
+    r#"
+    ALTER TABLE matches ADD COLUMN round_scores TEXT;
+"#,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerRecord {
+    pub id: PlayerId,
+    pub name: String,
+    pub rating: Rating,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl PlayerRecord {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+/// One side of a finished match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchParticipant {
+    pub player: PlayerId,
+    /// `CharacterManifestAsset::id` the player fought with.
+    pub character: String,
+}
+
+/// A finished two-player match, in `PlayerSlot` order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchRecord {
+    pub participants: [MatchParticipant; 2],
+    /// Index into `participants` of the winner, `None` for a draw.
+    pub winner: Option<usize>,
+    /// Rounds each participant won.
+    pub round_scores: [u8; 2],
+    pub duration_ticks: u32,
+    /// Where the match replay was stored, if it was.
+    pub replay: Option<String>,
+    pub played_at: SystemTime,
+}
+
+/// A match seen from one player's side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub match_id: MatchId,
+    pub played_at: SystemTime,
+    pub duration_ticks: u32,
+    pub replay: Option<String>,
+    /// `None` for a match recorded unrated, before draws were rated.
+    pub outcome: Option<Outcome>,
+    /// Rounds won by this player and by the opponent. `None` for matches recorded
+    /// before rounds were.
+    pub round_scores: Option<(u8, u8)>,
+    pub character: String,
+    pub rating_before: f64,
+    pub rating_after: f64,
+    pub opponent: PlayerId,
+    pub opponent_name: String,
+    pub opponent_character: String,
+}
+
+/// Handle to the stats database. Not `Sync`; open one per thread or request.
+#[derive(Debug)]
+pub struct StatsDb {
+    conn: Connection,
+}
+
+impl StatsDb {
+    /// Opens or creates the database at `path`, migrating it to the current schema.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StatsError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // The server writes while the site reads; wait out short write locks.
+        conn.busy_timeout(std::time::Duration::from_secs(2))?;
+        let mut db = Self { conn };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    /// Opens the database without write access, for the web site. `None` until the game
+    /// server has created and migrated it.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Option<Self>, StatsError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(std::time::Duration::from_secs(2))?;
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version == 0 {
+            return Ok(None);
+        }
+        if version > MIGRATIONS.len() as i64 {
+            return Err(StatsError::UnsupportedSchema(version));
+        }
+        Ok(Some(Self { conn }))
+    }
+
+    fn migrate(&mut self) -> Result<(), StatsError> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let latest = MIGRATIONS.len() as i64;
+        if version > latest {
+            return Err(StatsError::UnsupportedSchema(version));
+        }
+
+        let tx = self.conn.transaction()?;
+        for migration in &MIGRATIONS[version as usize..] {
+            tx.execute_batch(migration)?;
+        }
+        tx.pragma_update(None, "user_version", latest)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The player the game server issued `key` to, if they have played before.
+    pub fn player_by_key(&self, key: u64) -> Result<Option<PlayerRecord>, StatsError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, name, rating, deviation, volatility, wins, losses, draws
+                 FROM players WHERE key = ?1",
+                params![key_param(key)],
+                player_from_row,
+            )
+            .optional()?)
+    }
+
+    /// The player holding `key` under their latest display name, created with a
+    /// default rating if new.
+    pub fn register_player(&self, key: u64, name: &str) -> Result<PlayerRecord, StatsError> {
+        let default = Rating::default();
+        self.conn.execute(
+            "INSERT INTO players (key, name, rating, deviation, volatility, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(key) DO UPDATE SET name = excluded.name",
+            params![
+                key_param(key),
+                name,
+                default.rating,
+                default.deviation,
+                default.volatility,
+                unix_seconds(SystemTime::now()),
+            ],
+        )?;
+        Ok(self.conn.query_row(
+            "SELECT id, name, rating, deviation, volatility, wins, losses, draws
+             FROM players WHERE key = ?1",
+            params![key_param(key)],
+            player_from_row,
+        )?)
+    }
+
+    pub fn player(&self, id: PlayerId) -> Result<Option<PlayerRecord>, StatsError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, name, rating, deviation, volatility, wins, losses, draws
+                 FROM players WHERE id = ?1",
+                params![id],
+                player_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Players who finished at least one match, best conservative rating first.
+    pub fn leaderboard(&self, limit: u32) -> Result<Vec<PlayerRecord>, StatsError> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, name, rating, deviation, volatility, wins, losses, draws
+             FROM players
+             WHERE wins + losses + draws > 0
+             ORDER BY rating - 2 * deviation DESC, rating DESC
+             LIMIT ?1",
+        )?;
+        let players = statement
+            .query_map(params![limit], player_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(players)
+    }
+
+    /// Most recent matches of a player, newest first.
+    pub fn history(&self, player: PlayerId, limit: u32) -> Result<Vec<HistoryEntry>, StatsError> {
+        let mut statement = self.conn.prepare(
+            "SELECT m.id, m.played_at, m.duration_ticks, m.replay,
+                    me.outcome, me.character, me.rating_before, me.rating_after,
+                    them.player_id, p.name, them.character, m.round_scores, me.slot
+             FROM match_players me
+             JOIN matches m ON m.id = me.match_id
+             JOIN match_players them ON them.match_id = me.match_id AND them.slot != me.slot
+             JOIN players p ON p.id = them.player_id
+             WHERE me.player_id = ?1
+             ORDER BY m.played_at DESC, m.id DESC
+             LIMIT ?2",
+        )?;
+        let entries = statement
+            .query_map(params![player, limit], |row| {
+                let slot: i64 = row.get(12)?;
+                Ok(HistoryEntry {
+                    match_id: row.get(0)?,
+                    played_at: from_unix_seconds(row.get(1)?),
+                    duration_ticks: row.get(2)?,
+                    replay: row.get(3)?,
+                    outcome: row.get::<_, Option<i64>>(4)?.map(outcome_from_code),
+                    round_scores: row
+                        .get::<_, Option<String>>(11)?
+                        .as_deref()
+                        .and_then(parse_round_scores)
+                        .map(|[first, second]| match slot {
+                            0 => (first, second),
+                            _ => (second, first),
+                        }),
+                    character: row.get(5)?,
+                    rating_before: row.get(6)?,
+                    rating_after: row.get(7)?,
+                    opponent: row.get(8)?,
+                    opponent_name: row.get(9)?,
+                    opponent_character: row.get(10)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    /// Stores a finished match and applies its result to both players' ratings and
+    /// records, returning the match id and the new ratings in participant order.
+    pub fn record_match(
+        &mut self,
+        record: &MatchRecord,
+    ) -> Result<(MatchId, [Rating; 2]), StatsError> {
+        let tx = self.conn.transaction()?;
+
+        let load = |id: PlayerId| {
+            tx.query_row(
+                "SELECT id, name, rating, deviation, volatility, wins, losses, draws
+                 FROM players WHERE id = ?1",
+                params![id],
+                player_from_row,
+            )
+            .optional()?
+            .ok_or(StatsError::UnknownPlayer(id))
+        };
+        let players = [
+            load(record.participants[0].player)?,
+            load(record.participants[1].player)?,
+        ];
+
+        let outcomes = match record.winner {
+            Some(0) => [Outcome::Win, Outcome::Loss],
+            Some(_) => [Outcome::Loss, Outcome::Win],
+            None => [Outcome::Draw, Outcome::Draw],
+        };
+        let rate = |player: usize, opponent: usize| {
+            players[player]
+                .rating
+                .updated(&[(players[opponent].rating, outcomes[player])])
+        };
+        let ratings = [rate(0, 1), rate(1, 0)];
+
+        let [first, second] = record.round_scores;
+        tx.execute(
+            "INSERT INTO matches (played_at, duration_ticks, replay, round_scores)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                unix_seconds(record.played_at),
+                record.duration_ticks,
+                record.replay,
+                format!("{first}-{second}"),
+            ],
+        )?;
+        let match_id = tx.last_insert_rowid();
+
+        for (slot, participant) in record.participants.iter().enumerate() {
+            let (before, after, outcome) = (players[slot].rating, ratings[slot], outcomes[slot]);
+            tx.execute(
+                "INSERT INTO match_players
+                    (match_id, slot, player_id, character, outcome, rating_before, rating_after)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    match_id,
+                    slot as i64,
+                    participant.player,
+                    participant.character,
+                    outcome_code(outcome),
+                    before.rating,
+                    after.rating,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE players
+                 SET rating = ?2, deviation = ?3, volatility = ?4,
+                     wins = wins + ?5, losses = losses + ?6, draws = draws + ?7
+                 WHERE id = ?1",
+                params![
+                    participant.player,
+                    after.rating,
+                    after.deviation,
+                    after.volatility,
+                    (outcome == Outcome::Win) as i64,
+                    (outcome == Outcome::Loss) as i64,
+                    (outcome == Outcome::Draw) as i64,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok((match_id, ratings))
+    }
+}
+
+fn player_from_row(row: &Row) -> rusqlite::Result<PlayerRecord> {
+    Ok(PlayerRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        rating: Rating {
+            rating: row.get(2)?,
+            deviation: row.get(3)?,
+            volatility: row.get(4)?,
+        },
+        wins: row.get(5)?,
+        losses: row.get(6)?,
+        draws: row.get(7)?,
+    })
+}
+
+fn outcome_code(outcome: Outcome) -> i64 {
+    match outcome {
+        Outcome::Loss => 0,
+        Outcome::Win => 1,
+        Outcome::Draw => 2,
+    }
+}
+
+fn outcome_from_code(code: i64) -> Outcome {
+    match code {
+        0 => Outcome::Loss,
+        1 => Outcome::Win,
+        _ => Outcome::Draw,
+    }
+}
+
+/// Round scores stored as `"<first>-<second>"`, in participant order.
+fn parse_round_scores(scores: &str) -> Option<[u8; 2]> {
+    let (first, second) = scores.split_once('-')?;
+    Some([first.parse().ok()?, second.parse().ok()?])
+}
+
+/// SQLite integers are signed; keys are stored bit for bit.
+fn key_param(key: u64) -> i64 {
+    key as i64
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+fn from_unix_seconds(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + std::time::Duration::from_secs(seconds.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+
+    /// A database file of its own under the system temp directory, deleted on drop.
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new() -> Self {
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let name = format!(
+                "game-stats-test-{}-{}.db",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            );
+            Self(std::env::temp_dir().join(name))
+        }
+
+        fn open(&self) -> StatsDb {
+            StatsDb::open(&self.0).expect("open stats database")
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let mut path = self.0.clone().into_os_string();
+                path.push(suffix);
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn participant(player: PlayerId, character: &str) -> MatchParticipant {
+        MatchParticipant {
+            player,
+            character: character.to_owned(),
+        }
+    }
+
+    fn record(one: PlayerId, two: PlayerId, winner: Option<usize>) -> MatchRecord {
+        MatchRecord {
+            participants: [participant(one, "naruto"), participant(two, "sasuke")],
+            winner,
+            round_scores: match winner {
+                Some(0) => [2, 1],
+                Some(_) => [1, 2],
+                None => [2, 2],
+            },
+            duration_ticks: 600,
+            replay: Some("0000000000000001.nrpl".to_owned()),
+            played_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn migrates_new_and_existing_databases() {
+        let file = TempDb::new();
+        let id = {
+            let db = file.open();
+            let version: i64 = db
+                .conn
+                .pragma_query_value(None, "user_version", |row| row.get(0))
+                .unwrap();
+            assert_eq!(version, MIGRATIONS.len() as i64);
+            db.register_player(7, "ada").unwrap().id
+        };
+
+        // Reopening leaves migrated data alone.
+        let db = file.open();
+        assert_eq!(
+            db.player(id).unwrap().map(|player| player.name),
+            Some("ada".to_owned())
+        );
+
+        db.conn
+            .pragma_update(None, "user_version", MIGRATIONS.len() as i64 + 1)
+            .unwrap();
+        drop(db);
+        assert!(matches!(
+            StatsDb::open(&file.0),
+            Err(StatsError::UnsupportedSchema(_))
+        ));
+    }
+
+    #[test]
+    fn read_only_waits_for_the_server() {
+        let file = TempDb::new();
+        assert!(StatsDb::open_read_only(&file.0).unwrap().is_none());
+
+        // A file the server has not migrated yet reads as no stats at all.
+        drop(Connection::open(&file.0).unwrap());
+        assert!(StatsDb::open_read_only(&file.0).unwrap().is_none());
+
+        let id = file.open().register_player(1, "ada").unwrap().id;
+        let db = StatsDb::open_read_only(&file.0).unwrap().expect("migrated");
+        assert_eq!(
+            db.player(id).unwrap().map(|player| player.name),
+            Some("ada".to_owned())
+        );
+        assert!(db.register_player(2, "grace").is_err());
+    }
+
+    #[test]
+    fn players_are_keyed_on_their_key() {
+        let file = TempDb::new();
+        let db = file.open();
+        assert_eq!(db.player_by_key(u64::MAX).unwrap(), None);
+
+        let first = db.register_player(u64::MAX, "ada").unwrap();
+        let renamed = db.register_player(u64::MAX, "grace").unwrap();
+        let other = db.register_player(1, "ada").unwrap();
+
+        assert_eq!(renamed.id, first.id);
+        assert_eq!(renamed.name, "grace");
+        assert_ne!(other.id, first.id);
+        assert_eq!(db.player_by_key(u64::MAX).unwrap(), Some(renamed));
+    }
+
+    #[test]
+    fn rated_match_updates_players_and_history() {
+        let file = TempDb::new();
+        let mut db = file.open();
+        let one = db.register_player(1, "ada").unwrap();
+        let two = db.register_player(2, "grace").unwrap();
+
+        let (match_id, ratings) = db.record_match(&record(one.id, two.id, Some(1))).unwrap();
+        assert!(ratings[0].rating < one.rating.rating);
+        assert!(ratings[1].rating > two.rating.rating);
+
+        let loser = db.player(one.id).unwrap().unwrap();
+        let winner = db.player(two.id).unwrap().unwrap();
+        assert_eq!((loser.wins, loser.losses, loser.draws), (0, 1, 0));
+        assert_eq!((winner.wins, winner.losses, winner.draws), (1, 0, 0));
+        assert_eq!(loser.rating, ratings[0]);
+        assert_eq!(winner.rating, ratings[1]);
+
+        let history = db.history(one.id, 10).unwrap();
+        assert_eq!(
+            history,
+            [HistoryEntry {
+                match_id,
+                played_at: record(one.id, two.id, Some(1)).played_at,
+                duration_ticks: 600,
+                replay: Some("0000000000000001.nrpl".to_owned()),
+                outcome: Some(Outcome::Loss),
+                round_scores: Some((1, 2)),
+                character: "naruto".to_owned(),
+                rating_before: one.rating.rating,
+                rating_after: ratings[0].rating,
+                opponent: two.id,
+                opponent_name: "grace".to_owned(),
+                opponent_character: "sasuke".to_owned(),
+            }]
+        );
+        let history = db.history(two.id, 10).unwrap();
+        assert_eq!(history[0].outcome, Some(Outcome::Win));
+        assert_eq!(history[0].round_scores, Some((2, 1)));
+
+        let leaderboard = db.leaderboard(10).unwrap();
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard[0].id, two.id);
+    }
+
+    #[test]
+    fn draw_is_rated_for_both_players() {
+        let file = TempDb::new();
+        let mut db = file.open();
+        let one = db.register_player(1, "ada").unwrap();
+        let two = db.register_player(2, "grace").unwrap();
+
+        let (_, ratings) = db.record_match(&record(one.id, two.id, None)).unwrap();
+        // Even players stay even, but are less uncertain about it
+        assert_eq!(ratings[0].rating, ratings[1].rating);
+        assert!(ratings[0].deviation < one.rating.deviation);
+
+        for (id, rating) in [(one.id, ratings[0]), (two.id, ratings[1])] {
+            let player = db.player(id).unwrap().unwrap();
+            assert_eq!((player.wins, player.losses, player.draws), (0, 0, 1));
+            assert_eq!(player.rating, rating);
+            let history = db.history(id, 10).unwrap();
+            assert_eq!(history[0].outcome, Some(Outcome::Draw));
+            assert_eq!(history[0].round_scores, Some((2, 2)));
+        }
+        assert_eq!(db.leaderboard(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn matches_recorded_before_rounds_have_no_scores() {
+        let file = TempDb::new();
+        let mut db = file.open();
+        let one = db.register_player(1, "ada").unwrap();
+        let two = db.register_player(2, "grace").unwrap();
+        let (match_id, _) = db.record_match(&record(one.id, two.id, Some(0))).unwrap();
+        db.conn
+            .execute(
+                "UPDATE matches SET round_scores = NULL WHERE id = ?1",
+                params![match_id],
+            )
+            .unwrap();
+
+        assert_eq!(db.history(one.id, 10).unwrap()[0].round_scores, None);
+    }
+
+    #[test]
+    fn history_is_newest_first_and_limited() {
+        let file = TempDb::new();
+        let mut db = file.open();
+        let one = db.register_player(1, "ada").unwrap();
+        let two = db.register_player(2, "grace").unwrap();
+
+        let ids: Vec<MatchId> = (0..3)
+            .map(|_| db.record_match(&record(one.id, two.id, Some(0))).unwrap().0)
+            .collect();
+
+        let history = db.history(one.id, 2).unwrap();
+        let listed: Vec<MatchId> = history.iter().map(|entry| entry.match_id).collect();
+        assert_eq!(listed, [ids[2], ids[1]]);
+    }
+
+    #[test]
+    fn unknown_players_cannot_be_recorded() {
+        let file = TempDb::new();
+        let mut db = file.open();
+        let one = db.register_player(1, "ada").unwrap();
+
+        assert!(matches!(
+            db.record_match(&record(one.id, one.id + 1, Some(0))),
+            Err(StatsError::UnknownPlayer(_))
+        ));
+        assert!(db.history(one.id, 10).unwrap().is_empty());
+    }
+}
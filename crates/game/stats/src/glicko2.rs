@@ -0,0 +1,187 @@
+//! Glicko-2 rating system (Glickman, "Example of the Glicko-2 system"), applied after
+//! every match as a rating period of one game.
+
+use std::f64::consts::PI;
+
+/// Converts between the Glicko and Glicko-2 scales.
+const SCALE: f64 = 173.7178;
+/// Constrains how much volatility can change per period.
+const TAU: f64 = 0.5;
+const CONVERGENCE: f64 = 1e-6;
+
+/// Rating deviation never grows past that of a new player.
+const MAX_DEVIATION: f64 = 350.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            deviation: MAX_DEVIATION,
+            volatility: 0.06,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl Outcome {
+    pub fn score(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Loss => 0.0,
+            Outcome::Draw => 0.5,
+        }
+    }
+
+    /// The same game seen from the opponent's side.
+    pub fn reversed(self) -> Self {
+        match self {
+            Outcome::Win => Outcome::Loss,
+            Outcome::Loss => Outcome::Win,
+            Outcome::Draw => Outcome::Draw,
+        }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64 {
+    1.0 / (1.0 + (-g(opponent_phi) * (mu - opponent_mu)).exp())
+}
+
+impl Rating {
+    /// Lower bound of the 95% confidence interval; ranks established players above
+    /// new ones with a lucky first win.
+    pub fn conservative(&self) -> f64 {
+        self.rating - 2.0 * self.deviation
+    }
+
+    /// Rating after a period with the given games against `(opponent, outcome)`.
+    pub fn updated(&self, games: &[(Rating, Outcome)]) -> Rating {
+        let mu = (self.rating - 1500.0) / SCALE;
+        let phi = self.deviation / SCALE;
+        let sigma = self.volatility;
+
+        if games.is_empty() {
+            let phi = (phi * phi + sigma * sigma).sqrt();
+            return Rating {
+                deviation: (phi * SCALE).min(MAX_DEVIATION),
+                ..*self
+            };
+        }
+
+        let mut inverse_v = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, outcome) in games {
+            let opponent_mu = (opponent.rating - 1500.0) / SCALE;
+            let opponent_phi = opponent.deviation / SCALE;
+            let g = g(opponent_phi);
+            let e = expected(mu, opponent_mu, opponent_phi);
+            inverse_v += g * g * e * (1.0 - e);
+            improvement += g * (outcome.score() - e);
+        }
+        let v = 1.0 / inverse_v;
+        let delta = v * improvement;
+
+        let sigma = new_volatility(phi, sigma, v, delta);
+        let phi_star = (phi * phi + sigma * sigma).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+
+        Rating {
+            rating: mu * SCALE + 1500.0,
+            deviation: (phi * SCALE).min(MAX_DEVIATION),
+            volatility: sigma,
+        }
+    }
+}
+
+/// Step 5 of the paper: solves for the new volatility with the Illinois algorithm.
+fn new_volatility(phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denominator = phi * phi + v + ex;
+        ex * (delta * delta - phi * phi - v - ex) / (2.0 * denominator * denominator)
+            - (x - a) / (TAU * TAU)
+    };
+
+    let mut lower = a;
+    let mut upper = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let mut f_lower = f(lower);
+    let mut f_upper = f(upper);
+    while (upper - lower).abs() > CONVERGENCE {
+        let c = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+        let f_c = f(c);
+        if f_c * f_upper <= 0.0 {
+            lower = upper;
+            f_lower = f_upper;
+        } else {
+            f_lower /= 2.0;
+        }
+        upper = c;
+        f_upper = f_c;
+    }
+
+    (lower / 2.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Rating {
+        Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        }
+    }
+
+    #[test]
+    fn matches_glickman_example() {
+        let player = rating(1500.0, 200.0);
+        let updated = player.updated(&[
+            (rating(1400.0, 30.0), Outcome::Win),
+            (rating(1550.0, 100.0), Outcome::Loss),
+            (rating(1700.0, 300.0), Outcome::Loss),
+        ]);
+
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{updated:?}");
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{updated:?}");
+        assert!((updated.volatility - 0.05999).abs() < 1e-5, "{updated:?}");
+    }
+
+    #[test]
+    fn idle_period_only_widens_deviation() {
+        let player = rating(1600.0, 100.0);
+        let updated = player.updated(&[]);
+
+        assert_eq!(updated.rating, player.rating);
+        assert!(updated.deviation > player.deviation);
+        assert!(Rating::default().updated(&[]).deviation <= MAX_DEVIATION);
+    }
+}
@@ -0,0 +1,28 @@
+//! Player ratings and match history, stored in an embedded SQLite database that the
+//! game server writes and the web site reads.
+
+mod db;
+pub mod glicko2;
+
+pub use db::{
+    HistoryEntry, MatchId, MatchParticipant, MatchRecord, PlayerId, PlayerRecord, StatsDb,
+    StatsError,
+};
+pub use glicko2::{Outcome, Rating};
+
+/// Rate at which `duration_ticks` are counted, the game's fixed simulation rate.
+pub const TICK_RATE: u32 = 60;
+
+/// Environment variable naming the database file, shared by the server and the site.
+pub const DATABASE_ENV: &str = "GAME_STATS_DB";
+
+/// Database file used when `GAME_STATS_DB` is unset.
+pub const DEFAULT_DATABASE_PATH: &str = "game-stats.db";
+
+/// The database path from `GAME_STATS_DB`, or the default.
+pub fn database_path() -> std::path::PathBuf {
+    std::env::var_os(DATABASE_ENV)
+        .filter(|path| !path.is_empty())
+        .map(Into::into)
+        .unwrap_or_else(|| DEFAULT_DATABASE_PATH.into())
+}
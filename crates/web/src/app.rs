@@ -1,4 +1,5 @@
-use crate::components::*;
+use crate::{components::*, models::stats};
+use game_stats::PlayerId;
 use topcoat::{
     Result,
     router::{RouterBuilder, RouterBuilderDiscoverExt, layout, page},
@@ -45,3 +46,66 @@ async fn home() -> Result {
         </main>
     }
 }
+
+#[page("/leaderboard")]
+async fn leaderboard_page() -> Result {
+    let players = stats::leaderboard().await;
+    view! {
+        <main
+            class="w-full max-w-2xl px-6 sm:px-10 lg:px-16 py-12 sm:py-16 lg:py-24 flex-1 flex flex-col justify-between relative z-10"
+        >
+            <div class="space-y-12 mb-16">
+                back_link()
+                if let Ok(players) = players {
+                    leaderboard_table(players)
+                } else {
+                    stats_unavailable()
+                }
+            </div>
+
+            site_footer()
+        </main>
+    }
+}
+
+#[page("/players/{id}")]
+async fn player_page(id: PlayerId) -> Result {
+    let player = stats::player_history(id).await;
+    view! {
+        <main
+            class="w-full max-w-2xl px-6 sm:px-10 lg:px-16 py-12 sm:py-16 lg:py-24 flex-1 flex flex-col justify-between relative z-10"
+        >
+            <div class="space-y-12 mb-16">
+                back_link()
+                if let Ok(Some((player, history))) = player {
+                    <header class="space-y-1.5">
+                        <h1
+                            class="text-3xl sm:text-4xl font-bold tracking-tight text-stone-900 dark:text-stone-100 font-serif"
+                        >
+                            (player.name)
+                        </h1>
+                        <p class="text-xs sm:text-sm font-mono text-stone-500 dark:text-stone-400">
+                            (format!(
+                                "rating {:.0} ±{:.0} · {}-{}-{}",
+                                player.rating.rating,
+                                player.rating.deviation,
+                                player.wins,
+                                player.losses,
+                                player.draws,
+                            ))
+                        </p>
+                    </header>
+                    match_history(history)
+                } else if let Ok(None) = player {
+                    <p class="text-sm font-serif italic text-stone-500 dark:text-stone-400">
+                        "No such player."
+                    </p>
+                } else {
+                    stats_unavailable()
+                }
+            </div>
+
+            site_footer()
+        </main>
+    }
+}
@@ -50,6 +50,15 @@ pub async fn profile_header() -> Result {
                         "@khanhtimn"
                     </a>
                 </div>
+                <div>
+                    <span class="text-stone-500 dark:text-stone-400">"Game: "</span>
+                    <a
+                        href="/leaderboard"
+                        class="underline underline-offset-4 decoration-stone-300 hover:decoration-stone-800 dark:decoration-stone-600 dark:hover:decoration-stone-200 transition-colors"
+                    >
+                        "leaderboard"
+                    </a>
+                </div>
             </div>
         </header>
     }
@@ -0,0 +1,81 @@
+use game_stats::PlayerRecord;
+use topcoat::{
+    Result,
+    view::{component, view},
+};
+
+#[component]
+pub async fn back_link() -> Result {
+    view! {
+        <a
+            href="/"
+            class="text-xs sm:text-sm font-mono text-stone-600 dark:text-stone-400 underline underline-offset-4 decoration-stone-300 hover:decoration-stone-800 dark:decoration-stone-600 dark:hover:decoration-stone-200 transition-colors"
+        >
+            "← home"
+        </a>
+    }
+}
+
+#[component]
+pub async fn stats_unavailable() -> Result {
+    view! {
+        <p class="text-sm font-serif italic text-stone-500 dark:text-stone-400">
+            "Stats are unavailable right now, try again later."
+        </p>
+    }
+}
+
+#[component]
+pub async fn leaderboard_table(players: Vec<PlayerRecord>) -> Result {
+    view! {
+        <section aria-label="Leaderboard" class="space-y-4">
+            <h2
+                class="text-2xl font-bold font-serif text-stone-900 dark:text-stone-100 tracking-tight"
+            >
+                "Leaderboard"
+            </h2>
+
+            if players.is_empty() {
+                <p class="text-sm font-serif italic text-stone-500 dark:text-stone-400">
+                    "No ranked matches yet. Go play one!"
+                </p>
+            } else {
+                <table class="w-full text-sm sm:text-base font-serif border-collapse">
+                    <thead>
+                        <tr
+                            class="text-left text-xs font-mono text-stone-600 dark:text-stone-400 border-b border-stone-200/60 dark:border-stone-800"
+                        >
+                            <th class="py-2 pr-3">"#"</th>
+                            <th class="py-2 pr-3">"Player"</th>
+                            <th class="py-2 pr-3 text-right">"Rating"</th>
+                            <th class="py-2 text-right">"W-L-D"</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        for (rank, player) in players.iter().enumerate() {
+                            <tr class="border-b border-stone-100 dark:border-stone-900">
+                                <td class="py-2 pr-3 font-mono text-stone-500 dark:text-stone-400">
+                                    (rank + 1)
+                                </td>
+                                <td class="py-2 pr-3">
+                                    <a
+                                        href=(format!("/players/{}", player.id))
+                                        class="text-stone-900 dark:text-stone-100 underline underline-offset-4 decoration-stone-300 hover:decoration-stone-800 dark:decoration-stone-600 dark:hover:decoration-stone-200 transition-colors font-medium"
+                                    >
+                                        (player.name)
+                                    </a>
+                                </td>
+                                <td class="py-2 pr-3 text-right font-mono">
+                                    (format!("{:.0} ±{:.0}", player.rating.rating, player.rating.deviation))
+                                </td>
+                                <td class="py-2 text-right font-mono text-stone-600 dark:text-stone-400">
+                                    (format!("{}-{}-{}", player.wins, player.losses, player.draws))
+                                </td>
+                            </tr>
+                        }
+                    </tbody>
+                </table>
+            }
+        </section>
+    }
+}
@@ -0,0 +1,86 @@
+use game_stats::{HistoryEntry, Outcome};
+use topcoat::{
+    Result,
+    view::{component, view},
+};
+
+use crate::models::stats::{format_date, format_duration};
+
+#[component]
+pub async fn match_history(entries: Vec<HistoryEntry>) -> Result {
+    view! {
+        <section aria-label="Match history" class="space-y-4">
+            <h2
+                class="text-2xl font-bold font-serif text-stone-900 dark:text-stone-100 tracking-tight"
+            >
+                "Match history"
+            </h2>
+
+            if entries.is_empty() {
+                <p class="text-sm font-serif italic text-stone-500 dark:text-stone-400">
+                    "No matches played yet."
+                </p>
+            } else {
+                <ul class="space-y-4 list-none p-0 m-0">
+                    for entry in &entries {
+                        <li
+                            class="flex flex-col sm:flex-row sm:items-baseline gap-1.5 sm:gap-3 text-sm sm:text-base font-serif"
+                        >
+                            <span
+                                class="text-xs sm:text-sm font-mono text-stone-600 dark:text-stone-400 shrink-0 select-none"
+                            >
+                                "("
+                                (format_date(entry.played_at))
+                                ")"
+                            </span>
+                            <div class="space-y-1">
+                                <div class="flex flex-wrap items-baseline gap-2">
+                                    <span class="font-medium">
+                                        (match entry.outcome {
+                                            Some(Outcome::Win) => "Win",
+                                            Some(Outcome::Loss) => "Loss",
+                                            Some(Outcome::Draw) => "Draw",
+                                            None => "No result",
+                                        })
+                                    </span>
+                                    if let Some((won, lost)) = entry.round_scores {
+                                        <span class="font-mono text-stone-600 dark:text-stone-400">
+                                            (format!("{won}–{lost}"))
+                                        </span>
+                                    }
+                                    <span>
+                                        (format!("{} vs ", entry.character))
+                                        <a
+                                            href=(format!("/players/{}", entry.opponent))
+                                            class="underline underline-offset-4 decoration-stone-300 hover:decoration-stone-800 dark:decoration-stone-600 dark:hover:decoration-stone-200 transition-colors"
+                                        >
+                                            (entry.opponent_name)
+                                        </a>
+                                        (format!(" ({})", entry.opponent_character))
+                                    </span>
+                                </div>
+                                <p
+                                    class="text-xs sm:text-sm font-mono text-stone-500 dark:text-stone-400"
+                                >
+                                    (format_duration(entry.duration_ticks))
+                                    if entry.outcome.is_some() {
+                                        (format!(
+                                            " · rating {:.0} → {:.0}",
+                                            entry.rating_before,
+                                            entry.rating_after,
+                                        ))
+                                    } else {
+                                        " · unrated"
+                                    }
+                                    if let Some(replay) = &entry.replay {
+                                        (format!(" · replay {replay}"))
+                                    }
+                                </p>
+                            </div>
+                        </li>
+                    }
+                </ul>
+            }
+        </section>
+    }
+}
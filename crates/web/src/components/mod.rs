@@ -4,6 +4,8 @@ pub mod footer;
 pub mod game_canvas;
 pub mod head;
 pub mod header;
+pub mod leaderboard;
+pub mod match_history;
 pub mod project_list;
 
 pub use audio_unlock::audio_unlock_script;
@@ -12,4 +14,6 @@ pub use footer::site_footer;
 pub use game_canvas::game_sandbox;
 pub use head::doc_head;
 pub use header::profile_header;
+pub use leaderboard::{back_link, leaderboard_table, stats_unavailable};
+pub use match_history::match_history;
 pub use project_list::project_section;
@@ -1,2 +1,3 @@
 pub mod post;
+pub mod stats;
 pub use post::{BLOG_POSTS, PROJECTS};
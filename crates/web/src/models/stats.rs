@@ -0,0 +1,70 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use game_stats::{HistoryEntry, PlayerId, PlayerRecord, StatsDb, StatsError, TICK_RATE};
+
+/// Players shown on the leaderboard.
+pub const LEADERBOARD_SIZE: u32 = 100;
+/// Matches shown on a player's page.
+pub const HISTORY_SIZE: u32 = 50;
+
+/// Runs a query against the game server's stats database on the blocking pool.
+/// `None` until the server has created the database, as when no volume is shared
+/// with it.
+async fn query<T, F>(f: F) -> Result<Option<T>, StatsError>
+where
+    T: Send + 'static,
+    F: FnOnce(&StatsDb) -> Result<T, StatsError> + Send + 'static,
+{
+    let path = game_stats::database_path();
+    tokio::task::spawn_blocking(move || match StatsDb::open_read_only(&path)? {
+        Some(db) => f(&db).map(Some),
+        None => Ok(None),
+    })
+    .await
+    .expect("stats query panicked")
+}
+
+pub async fn leaderboard() -> Result<Vec<PlayerRecord>, StatsError> {
+    let players = query(|db| db.leaderboard(LEADERBOARD_SIZE)).await?;
+    Ok(players.unwrap_or_default())
+}
+
+/// A player and their latest matches, or `None` for unknown ids.
+pub async fn player_history(
+    id: PlayerId,
+) -> Result<Option<(PlayerRecord, Vec<HistoryEntry>)>, StatsError> {
+    let found = query(move |db| {
+        let Some(player) = db.player(id)? else {
+            return Ok(None);
+        };
+        let history = db.history(id, HISTORY_SIZE)?;
+        Ok(Some((player, history)))
+    })
+    .await?;
+    Ok(found.flatten())
+}
+
+/// `m:ss` for a match length in simulation ticks.
+pub fn format_duration(ticks: u32) -> String {
+    let seconds = ticks / TICK_RATE;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// `YYYY-MM-DD` in UTC.
+pub fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+
+    // Civil-from-days (Howard Hinnant), counting eras of 400 years from 0000-03-01.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}